@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use std::iter::FromIterator;
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PatternCollection {
     pub patterns: BTreeMap<String, Pattern>,
 }
@@ -1,6 +1,10 @@
 use std::fmt;
 
+use crate::pattern::Pattern;
+use crate::stitch::Thread;
+
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub red: u8,
     pub green: u8,
@@ -13,6 +17,58 @@ impl Color {
     }
 }
 
+/// Pick the `idx`th of `total` colors spread evenly around the hue circle; for when a pattern
+/// doesn't say what color its threads are.
+pub fn generate_color(idx: usize, total: usize) -> Color {
+    let color: palette::Srgb = palette::Lch::new(50., 100., (idx as f32) * 360.0 / (total as f32)).into();
+    color.into()
+}
+
+/// The index of the thread in `palette` that's closest in color to `color`.
+///
+/// # Panics
+/// When the palette is empty.
+pub fn closest_color(color: Color, palette: &[(Color, &str, &str)]) -> usize {
+    let distance = |other: &Color| {
+        let dr = i32::from(other.red) - i32::from(color.red);
+        let dg = i32::from(other.green) - i32::from(color.green);
+        let db = i32::from(other.blue) - i32::from(color.blue);
+        dr * dr + dg * dg + db * db
+    };
+    let (idx, _) = palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, (other, _, _))| distance(other))
+        .expect("The palette must not be empty");
+    idx
+}
+
+/// The thread for each color group; making some up for the groups without one.
+pub fn pattern_threads(pattern: &Pattern) -> Vec<Thread> {
+    let total_generated = pattern.color_groups.iter().filter(|cg| cg.thread.is_none()).count();
+    let mut generated = 0;
+    pattern
+        .color_groups
+        .iter()
+        .map(|cg| match &cg.thread {
+            Some(thread) => thread.clone(),
+            None => {
+                generated += 1;
+                let color = generate_color(generated - 1, total_generated);
+                Thread::new(color, color.to_string(), String::new())
+            },
+        })
+        .collect()
+}
+
+/// The color of each color group; making some up for the groups without a thread.
+pub fn thread_colors(pattern: &Pattern) -> Vec<Color> {
+    pattern_threads(pattern)
+        .into_iter()
+        .map(|thread| thread.color)
+        .collect()
+}
+
 impl From<Color> for palette::Srgb {
     fn from(color: Color) -> Self {
         Self::new(
@@ -39,3 +95,51 @@ impl fmt::Display for Color {
         write!(f, "#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stitch::{ColorGroup, Thread};
+
+    #[test]
+    fn test_closest_color() {
+        let palette = [
+            (Color::rgb(0, 0, 0), "Black", ""),
+            (Color::rgb(255, 0, 0), "Red", ""),
+            (Color::rgb(255, 255, 255), "White", ""),
+        ];
+        assert_eq!(closest_color(Color::rgb(200, 10, 10), &palette), 1);
+        assert_eq!(closest_color(Color::rgb(200, 200, 200), &palette), 2);
+        assert_eq!(closest_color(Color::rgb(0, 0, 0), &palette[1..]), 0);
+    }
+
+    #[test]
+    fn test_thread_colors() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![],
+                },
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(1, 2, 3), &"", &"")),
+                    stitch_groups: vec![],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![],
+                },
+            ],
+        };
+        assert_eq!(
+            thread_colors(&pattern),
+            vec![generate_color(0, 2), Color::rgb(1, 2, 3), generate_color(1, 2)]
+        );
+
+        let threads = pattern_threads(&pattern);
+        assert_eq!(Some(&threads[1]), pattern.color_groups[1].thread.as_ref());
+        assert_eq!(threads[2].name, generate_color(1, 2).to_string());
+    }
+}
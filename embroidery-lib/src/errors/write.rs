@@ -12,6 +12,9 @@ pub enum Error {
         ctx: Vec<String>,
     },
 
+    #[fail(display = "The pattern has {} colors; but the format only supports {}", count, max)]
+    TooManyColors { count: usize, max: usize, ctx: Vec<String> },
+
     #[fail(display = "{}", _0)]
     Std(#[cause] StdError, Vec<String>),
 }
@@ -34,12 +37,20 @@ impl Error {
             ctx: vec![msg.into()],
         }
     }
+    pub fn too_many_colors(count: usize, max: usize) -> Self {
+        Self::TooManyColors {
+            count,
+            max,
+            ctx: vec![],
+        }
+    }
 }
 
 impl ErrorWithContext for Error {
     fn context(&self) -> Vec<String> {
         match self {
             Self::UnsupportedStitch { stitch: _, idx: _, ctx } => ctx.clone(),
+            Self::TooManyColors { count: _, max: _, ctx } => ctx.clone(),
             Self::Std(_, c) => c.clone(),
         }
     }
@@ -52,6 +63,10 @@ impl ErrorWithContext for Error {
                 ctx.push(extra.into());
                 Self::UnsupportedStitch { stitch, idx, ctx }
             },
+            Self::TooManyColors { count, max, mut ctx } => {
+                ctx.push(extra.into());
+                Self::TooManyColors { count, max, ctx }
+            },
             Self::Std(e, mut c) => {
                 c.push(extra.into());
                 Self::Std(e, c)
@@ -65,6 +80,11 @@ impl ErrorWithContext for Error {
                 idx,
                 ctx: vec![],
             },
+            Self::TooManyColors { count, max, ctx: _ } => Self::TooManyColors {
+                count,
+                max,
+                ctx: vec![],
+            },
             Self::Std(e, _) => Self::Std(e, vec![]),
         }
     }
@@ -1,5 +1,7 @@
 mod collection;
 mod pattern;
+mod sidecar;
 
 pub use self::collection::{CollectionFormat, CollectionReader, CollectionWriter};
 pub use self::pattern::{PatternFormat, PatternReader, PatternWriter};
+pub use self::sidecar::{SidecarFormat, SidecarReader, SidecarWriter};
@@ -0,0 +1,24 @@
+use std::io::Read;
+use std::io::Write;
+
+use crate::errors::{ReadResult, WriteResult};
+use crate::stitch::Thread;
+
+/// A file that sits next to a pattern and only holds the pattern's threads; for the formats that
+/// don't store colors.
+pub trait SidecarFormat {
+    fn name<'a>(&self) -> &'a str;
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str];
+    fn reader(&self) -> Option<Box<dyn SidecarReader>>;
+    fn writer(&self) -> Option<Box<dyn SidecarWriter>>;
+}
+
+pub trait SidecarReader {
+    /// Read the threads from the file; in the order the pattern uses them.
+    fn read_threads(&self, item: &mut dyn Read) -> ReadResult<Vec<Thread>>;
+}
+
+pub trait SidecarWriter {
+    /// Write the threads to a file; one for each color group.
+    fn write_threads(&self, threads: &[Thread], writer: &mut dyn Write) -> WriteResult<()>;
+}
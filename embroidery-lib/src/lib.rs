@@ -14,6 +14,8 @@ mod str_util;
 
 pub mod errors;
 pub mod format;
+pub mod render;
+pub mod sidecar;
 pub mod transforms;
 
 pub use crate::collection::PatternCollection;
@@ -24,7 +26,8 @@ pub use crate::stitch::{ColorGroup, Stitch, StitchGroup, Thread};
 
 pub mod utils {
     pub use crate::byte_utils::ReadByteIterator;
-    pub use crate::stitch_util::{build_stitch_list, StitchInfo};
+    pub use crate::colors::{closest_color, generate_color, pattern_threads, thread_colors};
+    pub use crate::stitch_util::{build_stitch_list, from_tenths, split_jump, to_tenths, StitchGrouper, StitchInfo};
     pub use crate::str_util::{c_trim, char_truncate};
 }
 
@@ -1,11 +1,15 @@
 use std::{f64, iter::Iterator};
 
-use crate::stitch::{ColorGroup, Stitch};
-use crate::transforms::{RemoveDuplicateStitches, SplitLongStitches};
+use euclid::Transform2D;
+
+use crate::stitch::{stitch_bounds, ColorGroup, Stitch};
+use crate::transforms::{RemoveDuplicateStitches, SplitLongStitches, Transform};
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub enum PatternAttribute {
+    #[cfg_attr(feature = "serde", serde(rename = "Arbitrary"))]
     Arbitary(String, String),
     Title(String),
     Author(String),
@@ -13,6 +17,7 @@ pub enum PatternAttribute {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pattern {
     pub name: String,
     pub attributes: Vec<PatternAttribute>,
@@ -25,21 +30,7 @@ impl Pattern {
     }
 
     pub fn get_bounds(self: &Self) -> (f64, f64, f64, f64) {
-        let mut min_x: f64 = f64::NAN;
-        let mut min_y: f64 = f64::NAN;
-        let mut max_x: f64 = f64::NAN;
-        let mut max_y: f64 = f64::NAN;
-        for stitch in self.iter_stitches() {
-            min_x = min_x.min(stitch.x);
-            min_y = min_y.min(stitch.y);
-            max_x = max_x.max(stitch.x);
-            max_y = max_y.max(stitch.y);
-        }
-        if min_x.is_nan() || min_y.is_nan() || max_x.is_nan() || max_y.is_nan() {
-            (0., 0., 0., 0.)
-        } else {
-            (min_x, min_y, max_x, max_y)
-        }
+        stitch_bounds(self.iter_stitches())
     }
 }
 
@@ -55,6 +46,21 @@ impl RemoveDuplicateStitches for Pattern {
         }
     }
 }
+impl Transform for Pattern {
+    fn transform(self, transform: &Transform2D<f64>) -> Self {
+        Pattern {
+            color_groups: self
+                .color_groups
+                .into_iter()
+                .map(|cg| cg.transform(transform))
+                .collect(),
+            ..self
+        }
+    }
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        self.get_bounds()
+    }
+}
 impl SplitLongStitches for Pattern {
     fn split_stitches(self, min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Self {
         Pattern {
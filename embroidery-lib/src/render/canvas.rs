@@ -0,0 +1,185 @@
+use crate::colors::Color;
+
+/// An RGBA image to draw on; with anti-aliased lines.
+///
+/// Pixels are kept as premultiplied sRGB, so drawing blends the same way a browser would.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[f32; 4]>,
+}
+
+fn channels(color: Color) -> [f32; 3] {
+    [
+        f32::from(color.red) / 255.,
+        f32::from(color.green) / 255.,
+        f32::from(color.blue) / 255.,
+    ]
+}
+
+impl Canvas {
+    /// A fully transparent canvas.
+    pub fn new(width: u32, height: u32) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![[0.; 4]; width as usize * height as usize],
+        }
+    }
+
+    /// A canvas covered in `background`.
+    pub fn filled(width: u32, height: u32, background: Color) -> Self {
+        let [r, g, b] = channels(background);
+        Canvas {
+            width,
+            height,
+            pixels: vec![[r, g, b, 1.]; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Draw `color` over the pixel at `(x, y)`; covering `alpha` of it.
+    pub fn blend(&mut self, x: u32, y: u32, color: [f32; 3], alpha: f32) {
+        if x >= self.width || y >= self.height || alpha <= 0. {
+            return;
+        }
+        let alpha = alpha.min(1.);
+        let pixel = &mut self.pixels[y as usize * self.width as usize + x as usize];
+        for (dst, src) in pixel.iter_mut().zip(&color) {
+            *dst = src * alpha + *dst * (1. - alpha);
+        }
+        pixel[3] = alpha + pixel[3] * (1. - alpha);
+    }
+
+    /// Draw a line with round ends; `width` and the points are in pixels.
+    pub fn draw_line(&mut self, from: (f64, f64), to: (f64, f64), width: f64, color: Color) {
+        let color = channels(color);
+        self.draw_segment(from, to, width, |_, _| color);
+    }
+
+    /// Draw a line with round ends, letting `shade` pick the color of each pixel.
+    ///
+    /// `shade` is given how far along the line the pixel is; from 0 at `from` to 1 at `to`, and
+    /// how far across it is; from -1 on the left edge to 1 on the right.
+    pub fn draw_segment<F>(&mut self, from: (f64, f64), to: (f64, f64), width: f64, mut shade: F)
+    where
+        F: FnMut(f64, f64) -> [f32; 3],
+    {
+        if ![from.0, from.1, to.0, to.1, width].iter().all(|v| v.is_finite()) {
+            return;
+        }
+        let radius = width / 2.;
+        let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+        let length_sq = dx * dx + dy * dy;
+        let length = length_sq.sqrt();
+
+        // Everything up to half a pixel past the edge gets some coverage.
+        let reach = radius + 0.5;
+        let min_x = (from.0.min(to.0) - reach).floor().max(0.);
+        let min_y = (from.1.min(to.1) - reach).floor().max(0.);
+        let max_x = (from.0.max(to.0) + reach).ceil().min(f64::from(self.width));
+        let max_y = (from.1.max(to.1) + reach).ceil().min(f64::from(self.height));
+        if !(min_x < max_x && min_y < max_y) {
+            return;
+        }
+
+        for y in (min_y as u32)..(max_y as u32) {
+            for x in (min_x as u32)..(max_x as u32) {
+                let (px, py) = (f64::from(x) + 0.5 - from.0, f64::from(y) + 0.5 - from.1);
+                let along = if length_sq > 0. {
+                    ((px * dx + py * dy) / length_sq).clamp(0., 1.)
+                } else {
+                    0.
+                };
+                let (ox, oy) = (px - along * dx, py - along * dy);
+                let distance = (ox * ox + oy * oy).sqrt();
+                let coverage = (radius - distance + 0.5).clamp(0., 1.);
+                if coverage <= 0. {
+                    continue;
+                }
+                let across = if length > 0. && radius > 0. {
+                    ((py * dx - px * dy) / length / radius).clamp(-1., 1.)
+                } else {
+                    0.
+                };
+                let color = shade(along, across);
+                self.blend(x, y, color, coverage as f32);
+            }
+        }
+    }
+
+    /// The pixels as straight (not premultiplied) 8-bit RGBA; row by row from the top left.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for &[r, g, b, a] in &self.pixels {
+            if a <= 0. {
+                data.extend_from_slice(&[0, 0, 0, 0]);
+            } else {
+                for channel in &[r / a, g / a, b / a, a] {
+                    data.push((channel.clamp(0., 1.) * 255.).round() as u8);
+                }
+            }
+        }
+        data
+    }
+
+    /// The pixels as 8-bit RGB, drawn over `background`.
+    pub fn to_rgb8(&self, background: Color) -> Vec<u8> {
+        let background = channels(background);
+        let mut data = Vec::with_capacity(self.pixels.len() * 3);
+        for pixel in &self.pixels {
+            for (channel, back) in pixel.iter().zip(&background) {
+                let value = channel + back * (1. - pixel[3]);
+                data.push((value.clamp(0., 1.) * 255.).round() as u8);
+            }
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(canvas: &Canvas, x: u32, y: u32) -> [u8; 4] {
+        let data = canvas.to_rgba8();
+        let idx = (y * canvas.width() + x) as usize * 4;
+        [data[idx], data[idx + 1], data[idx + 2], data[idx + 3]]
+    }
+
+    #[test]
+    fn test_draw_line() {
+        let mut canvas = Canvas::new(10, 5);
+        canvas.draw_line((1., 2.5), (8., 2.5), 1., Color::rgb(255, 0, 0));
+        assert_eq!(pixel(&canvas, 4, 2), [255, 0, 0, 255]);
+        assert_eq!(pixel(&canvas, 4, 0), [0, 0, 0, 0]);
+        // The round end reaches past the end point; but the edge is only partly covered.
+        let [_, _, _, alpha] = pixel(&canvas, 0, 2);
+        assert!(alpha > 0 && alpha < 255);
+    }
+
+    #[test]
+    fn test_draw_line_clipped() {
+        let mut canvas = Canvas::new(4, 4);
+        canvas.draw_line((-10., -10.), (-5., -5.), 1., Color::rgb(255, 0, 0));
+        canvas.draw_line((-10., 2.), (20., 2.), 2., Color::rgb(0, 0, 255));
+        assert_eq!(pixel(&canvas, 0, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(&canvas, 3, 1), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_to_rgb8() {
+        let mut canvas = Canvas::new(1, 1);
+        canvas.blend(0, 0, [1., 1., 1.], 0.5);
+        assert_eq!(canvas.to_rgb8(Color::rgb(0, 0, 0)), vec![128, 128, 128]);
+        assert_eq!(Canvas::filled(1, 1, Color::rgb(1, 2, 3)).to_rgba8(), vec![1, 2, 3, 255]);
+    }
+}
@@ -0,0 +1,160 @@
+//! Drawing patterns as pictures; for the writers that produce images rather than machine files.
+
+mod canvas;
+mod sewout;
+mod shaded;
+
+use std::io;
+
+use crate::colors::{thread_colors, Color};
+use crate::pattern::Pattern;
+use crate::stitch::Stitch;
+
+pub use self::canvas::Canvas;
+pub use self::sewout::{sewout_steps, SewoutOptions, SewoutStep, SewoutStepKind, JUMP_COLOR, TRIM_COLOR};
+pub use self::shaded::draw_pattern_shaded;
+
+pub const MM_PER_INCH: f64 = 25.4;
+
+/// The biggest image that will be drawn; to stop a stray stitch far from the rest of the pattern
+/// from using up all the memory.
+pub const MAX_PIXELS: u64 = 100_000_000;
+
+/// How the stitches are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderStyle {
+    /// A flat line for each stitch; see `draw_pattern`.
+    #[default]
+    Flat,
+    /// Lit and shaded like real thread; see `draw_pattern_shaded`.
+    Shaded,
+}
+
+/// Where a pattern sits on an image; `scale` is in pixels per mm, and `margin` in pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub scale: f64,
+    pub min_x: f64,
+    pub max_y: f64,
+    pub margin: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Viewport {
+    /// Fit the whole pattern at `dpi`; leaving `margin` mm around it.
+    pub fn for_pattern(pattern: &Pattern, dpi: f64, margin: f64) -> Self {
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        let scale = dpi / MM_PER_INCH;
+        let size = |length: f64| ((length + 2. * margin) * scale).ceil().max(1.) as u32;
+        Viewport {
+            scale,
+            min_x,
+            max_y,
+            margin: margin * scale,
+            width: size(max_x - min_x),
+            height: size(max_y - min_y),
+        }
+    }
+
+    /// The position of the stitch in pixels; flipping the y axis, as images go down the page.
+    pub fn to_pixel(&self, stitch: &Stitch) -> (f64, f64) {
+        (
+            (stitch.x - self.min_x) * self.scale + self.margin,
+            (self.max_y - stitch.y) * self.scale + self.margin,
+        )
+    }
+
+    /// A blank canvas the size of the viewport; as long as it isn't too big.
+    pub fn new_canvas(&self, background: Option<Color>) -> io::Result<Canvas> {
+        if u64::from(self.width) * u64::from(self.height) > MAX_PIXELS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "The image would be {}x{} pixels; which is too big to draw",
+                    self.width, self.height
+                ),
+            ));
+        }
+        Ok(match background {
+            Some(color) => Canvas::filled(self.width, self.height, color),
+            None => Canvas::new(self.width, self.height),
+        })
+    }
+}
+
+/// Draw each stitch as a flat line `line_width` mm wide, in sewing order.
+pub fn draw_pattern(pattern: &Pattern, viewport: &Viewport, line_width: f64, canvas: &mut Canvas) {
+    let width = line_width * viewport.scale;
+    for (cg, color) in pattern.color_groups.iter().zip(thread_colors(pattern)) {
+        for sg in &cg.stitch_groups {
+            let points: Vec<_> = sg.stitches.iter().map(|s| viewport.to_pixel(s)).collect();
+            match points.len() {
+                0 => {},
+                // A lone stitch still puts some thread on the fabric.
+                1 => canvas.draw_line(points[0], points[0], width, color),
+                _ => {
+                    for pair in points.windows(2) {
+                        canvas.draw_line(pair[0], pair[1], width, color);
+                    }
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stitch::{ColorGroup, StitchGroup, Thread};
+
+    fn pattern() -> Pattern {
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(0, 0, 255), &"Blue", &"")),
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(10., 0.)])],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(10., 5.)])],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_viewport() {
+        let viewport = Viewport::for_pattern(&pattern(), MM_PER_INCH * 2., 1.);
+        assert_eq!((viewport.width, viewport.height), (24, 14));
+        assert_eq!(viewport.to_pixel(&Stitch::new(0., 0.)), (2., 12.));
+        assert_eq!(viewport.to_pixel(&Stitch::new(10., 5.)), (22., 2.));
+    }
+
+    #[test]
+    fn test_new_canvas() {
+        let viewport = Viewport::for_pattern(&pattern(), MM_PER_INCH * 2., 1.);
+        assert!(viewport.new_canvas(None).is_ok());
+        let viewport = Viewport::for_pattern(&pattern(), MM_PER_INCH * 10_000., 1.);
+        assert!(viewport.new_canvas(None).is_err());
+    }
+
+    #[test]
+    fn test_draw_pattern() {
+        let pattern = pattern();
+        let viewport = Viewport::for_pattern(&pattern, MM_PER_INCH * 2., 1.);
+        let mut canvas = Canvas::new(viewport.width, viewport.height);
+        draw_pattern(&pattern, &viewport, 2., &mut canvas);
+        let data = canvas.to_rgba8();
+        let pixel = |x: u32, y: u32| {
+            let idx = (y * viewport.width + x) as usize * 4;
+            data[idx..idx + 4].to_vec()
+        };
+        assert_eq!(pixel(12, 11), vec![0, 0, 255, 255]);
+        let generated = thread_colors(&pattern)[1];
+        assert_eq!(pixel(21, 1), vec![generated.red, generated.green, generated.blue, 255]);
+        assert_eq!(pixel(12, 6), vec![0, 0, 0, 0]);
+    }
+}
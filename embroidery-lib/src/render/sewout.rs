@@ -0,0 +1,175 @@
+use crate::colors::{thread_colors, Color};
+use crate::pattern::Pattern;
+use crate::stitch::Stitch;
+use crate::stitch_util::{build_stitch_list, StitchInfo, ZERO_STITCH};
+
+/// The color jumps are flashed in.
+pub const JUMP_COLOR: Color = Color::rgb(0x60, 0x60, 0x60);
+/// The color trims are flashed in.
+pub const TRIM_COLOR: Color = Color::rgb(0xFF, 0x00, 0x00);
+
+/// How fast the pattern is sewn; times are in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SewoutOptions {
+    pub stitches_per_second: f64,
+    /// How long each jump is shown for.
+    pub jump_time: f64,
+    /// How long each trim is shown for.
+    pub trim_time: f64,
+    /// How long to wait while the thread is changed.
+    pub color_change_time: f64,
+}
+
+impl Default for SewoutOptions {
+    fn default() -> Self {
+        SewoutOptions {
+            stitches_per_second: 100.,
+            jump_time: 0.25,
+            trim_time: 0.25,
+            color_change_time: 1.,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SewoutStepKind {
+    /// Thread is sewn from `from` to `to`.
+    Stitch(Color),
+    /// The needle moves from `from` to `to` without sewing.
+    Jump,
+    /// The thread is cut at `to`.
+    Trim,
+    /// The machine waits at `to` for the next thread.
+    ColorChange(Color),
+}
+
+/// One thing the machine does while sewing the pattern; starting `start` seconds in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SewoutStep {
+    pub kind: SewoutStepKind,
+    pub from: Stitch,
+    pub to: Stitch,
+    pub start: f64,
+    pub duration: f64,
+}
+
+impl SewoutStep {
+    pub fn end(&self) -> f64 {
+        self.start + self.duration
+    }
+}
+
+/// Everything the machine does to sew the pattern, in order; with when it happens.
+pub fn sewout_steps(pattern: &Pattern, options: &SewoutOptions) -> Vec<SewoutStep> {
+    let colors = thread_colors(pattern);
+    let mut steps = Vec::new();
+    let mut time = 0.;
+    let mut last = ZERO_STITCH;
+    let mut color_idx: Option<usize> = None;
+
+    let mut push = |kind, from: Stitch, to: Stitch, duration: f64| {
+        steps.push(SewoutStep {
+            kind,
+            from,
+            to,
+            start: time,
+            duration,
+        });
+        time += duration;
+    };
+
+    for info in build_stitch_list(pattern) {
+        match info {
+            StitchInfo::Color(_, &stitch) => {
+                let idx = color_idx.map_or(0, |idx| idx + 1);
+                let color = colors.get(idx).copied().unwrap_or_default();
+                // The first thread is already on the machine.
+                if color_idx.is_some() {
+                    push(
+                        SewoutStepKind::ColorChange(color),
+                        last,
+                        last,
+                        options.color_change_time,
+                    );
+                }
+                color_idx = Some(idx);
+                if stitch != last {
+                    push(SewoutStepKind::Jump, last, stitch, options.jump_time);
+                }
+                last = stitch;
+            },
+            StitchInfo::Jump(&stitch) => {
+                if stitch != last {
+                    push(SewoutStepKind::Jump, last, stitch, options.jump_time);
+                }
+                last = stitch;
+            },
+            StitchInfo::Stitch(&stitch) => {
+                let color = colors.get(color_idx.unwrap_or(0)).copied().unwrap_or_default();
+                push(
+                    SewoutStepKind::Stitch(color),
+                    last,
+                    stitch,
+                    1. / options.stitches_per_second,
+                );
+                last = stitch;
+            },
+            StitchInfo::Cut(&stitch) => push(SewoutStepKind::Trim, stitch, stitch, options.trim_time),
+            StitchInfo::End(_) => {},
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stitch::{ColorGroup, StitchGroup, Thread};
+
+    #[test]
+    fn test_sewout_steps() {
+        let red = Color::rgb(255, 0, 0);
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(red, &"Red", &"")),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(1., 0.)]).with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(5., 0.), Stitch::new(6., 0.)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(6., 0.), Stitch::new(6., 1.)])],
+                },
+            ],
+        };
+        let options = SewoutOptions {
+            stitches_per_second: 2.,
+            jump_time: 1.,
+            trim_time: 1.,
+            color_change_time: 3.,
+        };
+        let generated = thread_colors(&pattern)[1];
+        let steps = sewout_steps(&pattern, &options);
+        let kinds: Vec<_> = steps.iter().map(|step| step.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                SewoutStepKind::Stitch(red),
+                SewoutStepKind::Trim,
+                SewoutStepKind::Jump,
+                SewoutStepKind::Stitch(red),
+                SewoutStepKind::ColorChange(generated),
+                SewoutStepKind::Stitch(generated),
+            ]
+        );
+        let starts: Vec<_> = steps.iter().map(|step| step.start).collect();
+        assert_eq!(starts, vec![0., 0.5, 1.5, 2.5, 3., 6.]);
+        assert_eq!(steps[2].from, Stitch::new(1., 0.));
+        assert_eq!(steps[2].to, Stitch::new(5., 0.));
+        assert_eq!(steps[5].end(), 6.5);
+    }
+}
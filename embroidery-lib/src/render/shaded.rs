@@ -0,0 +1,125 @@
+use palette::{LinSrgb, Srgb};
+
+use crate::colors::thread_colors;
+use crate::pattern::Pattern;
+use crate::render::{Canvas, Viewport};
+
+/// Where the light comes from; up and to the left of the fabric, as the image is seen.
+const LIGHT: [f64; 3] = [-0.4, -0.6, 0.7];
+const AMBIENT: f64 = 0.35;
+const DIFFUSE: f64 = 0.65;
+const SPECULAR: f64 = 0.35;
+const SHININESS: i32 = 24;
+/// How much darker the thread is where it goes into the fabric.
+const END_SHADOW: f64 = 0.35;
+
+fn normalize(v: [f64; 3]) -> [f64; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    [v[0] / length, v[1] / length, v[2] / length]
+}
+
+fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Lights a stitch as if it were a cylinder of thread lying on the fabric.
+struct StitchShader {
+    color: LinSrgb,
+    light: [f64; 3],
+    half: [f64; 3],
+}
+
+impl StitchShader {
+    fn new(color: Srgb) -> Self {
+        let light = normalize(LIGHT);
+        StitchShader {
+            color: color.into_linear(),
+            light,
+            // Halfway between the light and the viewer, who looks straight down.
+            half: normalize([light[0], light[1], light[2] + 1.]),
+        }
+    }
+
+    /// The color at `across` the stitch (-1 to 1), for a stitch going in the unit `direction`,
+    /// `from_end` pixels from the closest end of a stitch `width` pixels wide.
+    fn shade(&self, across: f64, direction: (f64, f64), from_end: f64, width: f64) -> [f32; 3] {
+        // The surface of the cylinder faces sideways at the edges and up in the middle.
+        let normal = [
+            -across * direction.1,
+            across * direction.0,
+            (1. - across * across).max(0.).sqrt(),
+        ];
+        let diffuse = DIFFUSE * dot(normal, self.light).max(0.);
+        let specular = SPECULAR * dot(normal, self.half).max(0.).powi(SHININESS);
+        let end = if width > 0. {
+            1. - END_SHADOW * (1. - (from_end / width).min(1.))
+        } else {
+            1.
+        };
+        let lit = ((AMBIENT + diffuse) * end) as f32;
+        let shine = (specular * end) as f32;
+        let color = LinSrgb::new(
+            self.color.red * lit + shine,
+            self.color.green * lit + shine,
+            self.color.blue * lit + shine,
+        );
+        let color = Srgb::from_linear(color);
+        [
+            color.red.clamp(0., 1.),
+            color.green.clamp(0., 1.),
+            color.blue.clamp(0., 1.),
+        ]
+    }
+}
+
+/// Draw each stitch as a lit thread `line_width` mm wide, in sewing order; so later stitches cover
+/// the ones sewn before them.
+pub fn draw_pattern_shaded(pattern: &Pattern, viewport: &Viewport, line_width: f64, canvas: &mut Canvas) {
+    let width = line_width * viewport.scale;
+    for (cg, color) in pattern.color_groups.iter().zip(thread_colors(pattern)) {
+        let shader = StitchShader::new(color.into());
+        for sg in &cg.stitch_groups {
+            let points: Vec<_> = sg.stitches.iter().map(|s| viewport.to_pixel(s)).collect();
+            let segments: Vec<_> = match points.len() {
+                0 => vec![],
+                1 => vec![(points[0], points[0])],
+                _ => points.windows(2).map(|pair| (pair[0], pair[1])).collect(),
+            };
+            for (from, to) in segments {
+                let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+                let length = (dx * dx + dy * dy).sqrt();
+                let direction = if length > 0. {
+                    (dx / length, dy / length)
+                } else {
+                    (1., 0.)
+                };
+                canvas.draw_segment(from, to, width, |along, across| {
+                    let from_end = along.min(1. - along) * length;
+                    shader.shade(across, direction, from_end, width)
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+
+    fn brightness(color: [f32; 3]) -> f32 {
+        color.iter().sum()
+    }
+
+    #[test]
+    fn test_shade() {
+        let shader = StitchShader::new(Color::rgb(200, 40, 40).into());
+        let direction = (1., 0.);
+        let middle = shader.shade(0., direction, 10., 4.);
+        // The light comes from above; so the top edge is brighter than the bottom one.
+        assert!(brightness(shader.shade(-0.9, direction, 10., 4.)) > brightness(shader.shade(0.9, direction, 10., 4.)));
+        assert!(brightness(middle) > brightness(shader.shade(0.9, direction, 10., 4.)));
+        assert!(brightness(shader.shade(0., direction, 0., 4.)) < brightness(middle));
+        assert!(middle[0] > middle[1]);
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::{Read, Write};
+
+use crate::colors::Color;
+use crate::errors::{ReadError, ReadResult, WriteResult};
+use crate::format::{SidecarFormat, SidecarReader, SidecarWriter};
+use crate::stitch::Thread;
+
+const NAME: &str = "col";
+const EXTENSIONS: [&str; 1] = ["col"];
+
+/// Embroidermodder's text format; the number of threads, followed by `index,red,green,blue` for
+/// each thread. Every line ends with `\r\n`.
+#[derive(Default)]
+pub struct ColSidecarFormat {}
+
+impl SidecarFormat for ColSidecarFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn SidecarReader>> {
+        Some(Box::from(ColSidecarFormat::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn SidecarWriter>> {
+        Some(Box::from(ColSidecarFormat::default()))
+    }
+}
+
+impl SidecarReader for ColSidecarFormat {
+    fn read_threads(&self, item: &mut dyn Read) -> ReadResult<Vec<Thread>> {
+        let mut data = vec![];
+        item.read_to_end(&mut data)?;
+        let text = String::from_utf8_lossy(&data);
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        let count: usize = match lines.next().map(str::parse) {
+            Some(Ok(count)) => count,
+            _ => {
+                return Err(ReadError::invalid_format(
+                    "The file doesn't start with the number of threads",
+                ))
+            },
+        };
+        let threads = lines.map(parse_line).collect::<ReadResult<Vec<_>>>()?;
+        if threads.len() != count {
+            return Err(ReadError::invalid_format(format!(
+                "The file should have {} threads; but it has {}",
+                count,
+                threads.len()
+            )));
+        }
+        Ok(threads)
+    }
+}
+
+fn parse_line(line: &str) -> ReadResult<Thread> {
+    let parts: Vec<_> = line.split(',').map(str::trim).collect();
+    let channel = |part: &str| part.parse::<u8>().ok();
+    match parts[..] {
+        [index, red, green, blue] if index.parse::<usize>().is_ok() => {
+            match (channel(red), channel(green), channel(blue)) {
+                (Some(red), Some(green), Some(blue)) => {
+                    let color = Color::rgb(red, green, blue);
+                    Ok(Thread::new(color, color.to_string(), String::new()))
+                },
+                _ => Err(ReadError::invalid_format(format!("Invalid thread color: {:?}", line))),
+            }
+        },
+        _ => Err(ReadError::invalid_format(format!("Invalid thread line: {:?}", line))),
+    }
+}
+
+impl SidecarWriter for ColSidecarFormat {
+    fn write_threads(&self, threads: &[Thread], writer: &mut dyn Write) -> WriteResult<()> {
+        write!(writer, "{}\r\n", threads.len())?;
+        for (i, thread) in threads.iter().enumerate() {
+            let color = thread.color;
+            write!(writer, "{},{},{},{}\r\n", i, color.red, color.green, color.blue)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_threads() {
+        let data = b"2\r\n0,255,0,0\r\n1, 0, 67, 177\r\n";
+        let threads = ColSidecarFormat::default().read_threads(&mut &data[..]).unwrap();
+        assert_eq!(
+            threads,
+            vec![
+                Thread::new_str(Color::rgb(0xFF, 0x00, 0x00), &"#FF0000", &""),
+                Thread::new_str(Color::rgb(0x00, 0x43, 0xB1), &"#0043B1", &""),
+            ]
+        );
+        let reader = ColSidecarFormat::default();
+        assert!(reader.read_threads(&mut &b"3\r\n0,255,0,0\r\n"[..]).is_err());
+        assert!(reader.read_threads(&mut &b"1\r\n0,256,0,0\r\n"[..]).is_err());
+        assert!(reader.read_threads(&mut &b"0,255,0,0\r\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_write_threads() {
+        let threads = vec![Thread::new_str(Color::rgb(1, 2, 3), &"", &"")];
+        let mut data = vec![];
+        ColSidecarFormat::default().write_threads(&threads, &mut data).unwrap();
+        assert_eq!(data, b"1\r\n0,1,2,3\r\n");
+    }
+}
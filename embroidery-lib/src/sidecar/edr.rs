@@ -0,0 +1,100 @@
+use std::io::{Read, Write};
+
+use crate::colors::Color;
+use crate::errors::{ReadError, ReadResult, WriteResult};
+use crate::format::{SidecarFormat, SidecarReader, SidecarWriter};
+use crate::stitch::Thread;
+
+const EDR_NAME: &str = "edr";
+const EDR_EXTENSIONS: [&str; 1] = ["edr"];
+const RGB_NAME: &str = "rgb";
+const RGB_EXTENSIONS: [&str; 1] = ["rgb"];
+
+/// Embird's `.edr` and Embroidermodder's `.rgb` files are the same; each thread is just
+/// `red, green, blue, 0x00`.
+#[derive(Default)]
+pub struct RawColorSidecar {}
+
+#[derive(Default)]
+pub struct EdrSidecarFormat {}
+
+impl SidecarFormat for EdrSidecarFormat {
+    fn name<'a>(&self) -> &'a str {
+        EDR_NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EDR_EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn SidecarReader>> {
+        Some(Box::from(RawColorSidecar::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn SidecarWriter>> {
+        Some(Box::from(RawColorSidecar::default()))
+    }
+}
+
+#[derive(Default)]
+pub struct RgbSidecarFormat {}
+
+impl SidecarFormat for RgbSidecarFormat {
+    fn name<'a>(&self) -> &'a str {
+        RGB_NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &RGB_EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn SidecarReader>> {
+        Some(Box::from(RawColorSidecar::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn SidecarWriter>> {
+        Some(Box::from(RawColorSidecar::default()))
+    }
+}
+
+impl SidecarReader for RawColorSidecar {
+    fn read_threads(&self, item: &mut dyn Read) -> ReadResult<Vec<Thread>> {
+        let mut data = vec![];
+        item.read_to_end(&mut data)?;
+        if data.len() % 4 != 0 {
+            return Err(ReadError::invalid_format(format!(
+                "Expected 4 bytes for each thread; but the file has {} bytes",
+                data.len()
+            )));
+        }
+        Ok(data
+            .chunks(4)
+            .map(|bytes| {
+                let color = Color::rgb(bytes[0], bytes[1], bytes[2]);
+                Thread::new(color, color.to_string(), String::new())
+            })
+            .collect())
+    }
+}
+
+impl SidecarWriter for RawColorSidecar {
+    fn write_threads(&self, threads: &[Thread], writer: &mut dyn Write) -> WriteResult<()> {
+        for thread in threads {
+            writer.write_all(&[thread.color.red, thread.color.green, thread.color.blue, 0x00])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_threads() {
+        let data = [0xFF, 0x00, 0x00, 0x00, 0x00, 0x43, 0xB1, 0x00];
+        let threads = RawColorSidecar::default().read_threads(&mut &data[..]).unwrap();
+        assert_eq!(
+            threads,
+            vec![
+                Thread::new_str(Color::rgb(0xFF, 0x00, 0x00), &"#FF0000", &""),
+                Thread::new_str(Color::rgb(0x00, 0x43, 0xB1), &"#0043B1", &""),
+            ]
+        );
+        assert!(RawColorSidecar::default().read_threads(&mut &data[..7]).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::{Read, Write};
+
+use byteorder::{BigEndian, ByteOrder, WriteBytesExt};
+
+use crate::colors::Color;
+use crate::errors::{ReadError, ReadResult, WriteResult};
+use crate::format::{SidecarFormat, SidecarReader, SidecarWriter};
+use crate::stitch::Thread;
+
+const NAME: &str = "inf";
+const EXTENSIONS: [&str; 1] = ["inf"];
+
+// The header is 4 big endian u32s: the version, the header's size(excluding the thread count),
+// the size of the thread records, and the thread count.
+const VERSION: u32 = 1;
+const HEADER_LEN: usize = 16;
+// Each record starts with its own length, the thread's index, the color, and 2 unknown bytes;
+// followed by the nul terminated name and manufacturer.
+const RECORD_PREFIX_LEN: usize = 9;
+
+/// Melco's thread information file; which goes with EXP files.
+#[derive(Default)]
+pub struct InfSidecarFormat {}
+
+impl SidecarFormat for InfSidecarFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn SidecarReader>> {
+        Some(Box::from(InfSidecarFormat::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn SidecarWriter>> {
+        Some(Box::from(InfSidecarFormat::default()))
+    }
+}
+
+impl SidecarReader for InfSidecarFormat {
+    fn read_threads(&self, item: &mut dyn Read) -> ReadResult<Vec<Thread>> {
+        let mut data = vec![];
+        item.read_to_end(&mut data)?;
+        if data.len() < HEADER_LEN {
+            return Err(ReadError::invalid_format("The file is too short to have a header"));
+        }
+        let count = BigEndian::read_u32(&data[12..16]) as usize;
+        let mut remaining = &data[HEADER_LEN..];
+        let mut threads = Vec::with_capacity(count);
+        for _ in 0..count {
+            if remaining.len() < 2 {
+                return Err(ReadError::invalid_format("The file ends part way through a thread"));
+            }
+            let len = usize::from(BigEndian::read_u16(remaining));
+            if len < RECORD_PREFIX_LEN || remaining.len() < len {
+                return Err(ReadError::invalid_format(format!(
+                    "Invalid thread record length {}",
+                    len
+                )));
+            }
+            let (record, rest) = remaining.split_at(len);
+            threads.push(read_record(record));
+            remaining = rest;
+        }
+        Ok(threads)
+    }
+}
+
+fn read_record(record: &[u8]) -> Thread {
+    let color = Color::rgb(record[4], record[5], record[6]);
+    let mut strings = record[RECORD_PREFIX_LEN..]
+        .split(|&b| b == 0)
+        .map(|s| String::from_utf8_lossy(s).trim().to_string());
+    let name = strings.next().unwrap_or_default();
+    let manufacturer = strings.next().unwrap_or_default();
+    let mut thread = Thread::new(color, name, String::new());
+    if !manufacturer.is_empty() {
+        thread.manufacturer = Some(manufacturer);
+    }
+    thread
+}
+
+impl SidecarWriter for InfSidecarFormat {
+    fn write_threads(&self, threads: &[Thread], writer: &mut dyn Write) -> WriteResult<()> {
+        let mut records = vec![];
+        for (i, thread) in threads.iter().enumerate() {
+            let manufacturer = thread.manufacturer.as_ref().map_or("", String::as_str);
+            let len = RECORD_PREFIX_LEN + thread.name.len() + 1 + manufacturer.len() + 1;
+            records.write_u16::<BigEndian>(len as u16)?;
+            records.write_u16::<BigEndian>(i as u16)?;
+            records.write_all(&[thread.color.red, thread.color.green, thread.color.blue, 0x00, 0x00])?;
+            records.write_all(thread.name.as_bytes())?;
+            records.write_u8(0)?;
+            records.write_all(manufacturer.as_bytes())?;
+            records.write_u8(0)?;
+        }
+        writer.write_u32::<BigEndian>(VERSION)?;
+        writer.write_u32::<BigEndian>((HEADER_LEN - 4) as u32)?;
+        writer.write_u32::<BigEndian>(records.len() as u32)?;
+        writer.write_u32::<BigEndian>(threads.len() as u32)?;
+        writer.write_all(&records)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut madeira = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        madeira.manufacturer = Some("Madeira Rayon".to_string());
+        let threads = vec![madeira, Thread::new_str(Color::rgb(1, 2, 3), &"Ünïcode", &"")];
+        let mut data = vec![];
+        InfSidecarFormat::default().write_threads(&threads, &mut data).unwrap();
+        assert_eq!(data[12..16], [0, 0, 0, 2]);
+        assert_eq!(data[16..18], [0, 27]);
+
+        let read = InfSidecarFormat::default().read_threads(&mut &data[..]).unwrap();
+        assert_eq!(read, threads);
+
+        let reader = InfSidecarFormat::default();
+        assert!(reader.read_threads(&mut &data[..data.len() - 1]).is_err());
+        assert!(reader.read_threads(&mut &data[..10]).is_err());
+    }
+}
@@ -0,0 +1,79 @@
+//! Thread colors stored in a separate file next to the pattern.
+
+mod col;
+mod edr;
+mod inf;
+
+use crate::format::SidecarFormat;
+use crate::pattern::Pattern;
+use crate::stitch::Thread;
+
+pub use self::col::ColSidecarFormat;
+pub use self::edr::{EdrSidecarFormat, RgbSidecarFormat};
+pub use self::inf::InfSidecarFormat;
+
+pub fn get_all() -> Vec<Box<dyn SidecarFormat>> {
+    vec![
+        Box::new(ColSidecarFormat::default()),
+        Box::new(EdrSidecarFormat::default()),
+        Box::new(InfSidecarFormat::default()),
+        Box::new(RgbSidecarFormat::default()),
+    ]
+}
+
+/// Give each color group the thread at the same position; leaving the groups past the end of
+/// `threads` alone.
+pub fn apply_threads(pattern: &mut Pattern, threads: Vec<Thread>) {
+    for (cg, thread) in pattern.color_groups.iter_mut().zip(threads) {
+        cg.thread = Some(thread);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+    use crate::stitch::{ColorGroup, Stitch, StitchGroup};
+
+    fn pattern() -> Pattern {
+        let color_group = || ColorGroup {
+            thread: None,
+            stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(1., 0.)])],
+        };
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![color_group(), color_group()],
+        }
+    }
+
+    #[test]
+    fn test_apply_threads() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let mut pattern = pattern();
+        apply_threads(&mut pattern, vec![red.clone()]);
+        assert_eq!(pattern.color_groups[0].thread, Some(red.clone()));
+        assert_eq!(pattern.color_groups[1].thread, None);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let threads = vec![
+            Thread::new_str(Color::rgb(255, 0, 0), &"#FF0000", &""),
+            Thread::new_str(Color::rgb(0, 0x43, 0xb1), &"#0043B1", &""),
+        ];
+        for format in get_all() {
+            let mut data = vec![];
+            let writer = format.writer().unwrap();
+            writer.write_threads(&threads, &mut data).unwrap();
+            let reader = format.reader().unwrap();
+            let read = reader.read_threads(&mut &data[..]).unwrap();
+            assert_eq!(
+                read.iter().map(|t| t.color).collect::<Vec<_>>(),
+                vec![threads[0].color, threads[1].color],
+                "{}",
+                format.name()
+            );
+        }
+    }
+}
@@ -13,10 +13,13 @@ A stitch represents the x,y coordinates in millimeters.
 use std::collections::BTreeMap;
 use std::fmt::Display;
 
+use euclid::{Point2D, Transform2D};
+
 use crate::colors::Color;
-use crate::transforms::{RemoveDuplicateStitches, SplitLongStitches};
+use crate::transforms::{RemoveDuplicateStitches, SplitLongStitches, Transform};
 
 #[derive(Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Thread {
     pub color: Color,
     pub name: String,
@@ -44,6 +47,7 @@ impl Thread {
 
 /// Represents mm from an arbitrary (0, 0) where positive values move up and right
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Stitch {
     pub x: f64,
     pub y: f64,
@@ -71,6 +75,31 @@ impl Stitch {
     pub fn is_valid(&self) -> bool {
         self.x.is_finite() && self.y.is_finite()
     }
+    #[inline]
+    pub fn transform(&self, transform: &Transform2D<f64>) -> Self {
+        let point = transform.transform_point(&Point2D::new(self.x, self.y));
+        Self::new(point.x, point.y)
+    }
+}
+
+/// The smallest box holding every stitch, as `(min_x, min_y, max_x, max_y)`; or all zeros if there
+/// aren't any.
+pub(crate) fn stitch_bounds<'a>(stitches: impl Iterator<Item = &'a Stitch>) -> (f64, f64, f64, f64) {
+    let mut min_x: f64 = f64::NAN;
+    let mut min_y: f64 = f64::NAN;
+    let mut max_x: f64 = f64::NAN;
+    let mut max_y: f64 = f64::NAN;
+    for stitch in stitches {
+        min_x = min_x.min(stitch.x);
+        min_y = min_y.min(stitch.y);
+        max_x = max_x.max(stitch.x);
+        max_y = max_y.max(stitch.y);
+    }
+    if min_x.is_nan() || min_y.is_nan() || max_x.is_nan() || max_y.is_nan() {
+        (0., 0., 0., 0.)
+    } else {
+        (min_x, min_y, max_x, max_y)
+    }
 }
 
 impl Display for Stitch {
@@ -79,6 +108,7 @@ impl Display for Stitch {
     }
 }
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColorGroup {
     pub thread: Option<Thread>,
     pub stitch_groups: Vec<StitchGroup>,
@@ -103,6 +133,21 @@ impl RemoveDuplicateStitches for ColorGroup {
         }
     }
 }
+impl Transform for ColorGroup {
+    fn transform(self, transform: &Transform2D<f64>) -> Self {
+        ColorGroup {
+            stitch_groups: self
+                .stitch_groups
+                .into_iter()
+                .map(|sg| sg.transform(transform))
+                .collect(),
+            ..self
+        }
+    }
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        stitch_bounds(self.iter_stitches())
+    }
+}
 impl SplitLongStitches for ColorGroup {
     fn split_stitches(self, min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Self {
         ColorGroup {
@@ -117,6 +162,7 @@ impl SplitLongStitches for ColorGroup {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct StitchGroup {
     pub stitches: Vec<Stitch>,
@@ -164,6 +210,17 @@ impl RemoveDuplicateStitches for StitchGroup {
         }
     }
 }
+impl Transform for StitchGroup {
+    fn transform(self, transform: &Transform2D<f64>) -> Self {
+        StitchGroup {
+            stitches: self.stitches.iter().map(|s| s.transform(transform)).collect(),
+            ..self
+        }
+    }
+    fn bounds(&self) -> (f64, f64, f64, f64) {
+        stitch_bounds(self.iter_stitches())
+    }
+}
 impl SplitLongStitches for StitchGroup {
     #[allow(clippy::float_cmp)]
     fn split_stitches(self, min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Self {
@@ -272,6 +329,80 @@ impl SplitLongStitches for StitchGroup {
 mod tests {
     use super::*;
 
+    fn assert_stitches_eq(actual: &[Stitch], expected: &[Stitch]) {
+        assert_eq!(actual.len(), expected.len(), "{:?} != {:?}", actual, expected);
+        for (a, e) in actual.iter().zip(expected) {
+            assert!(a.distance_to(e) < 1e-9, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn square() -> StitchGroup {
+        StitchGroup::new(vec![Stitch::new(1., 1.), Stitch::new(3., 1.), Stitch::new(3., 2.)]).with_cut(true)
+    }
+
+    #[test]
+    fn transform_stitch_group() {
+        let sg = square().translate(1., -1.).scale(2., 3.);
+        assert!(sg.cut);
+        assert_stitches_eq(
+            &sg.stitches,
+            &[Stitch::new(4., 0.), Stitch::new(8., 0.), Stitch::new(8., 3.)],
+        );
+        let sg = square().rotate(90.);
+        assert_stitches_eq(
+            &sg.stitches,
+            &[Stitch::new(-1., 1.), Stitch::new(-1., 3.), Stitch::new(-2., 3.)],
+        );
+        let sg = square().skew(45., 0.);
+        assert_stitches_eq(
+            &sg.stitches,
+            &[Stitch::new(2., 1.), Stitch::new(4., 1.), Stitch::new(5., 2.)],
+        );
+    }
+
+    #[test]
+    fn transform_about_center() {
+        assert_eq!(square().bounds(), (1., 1., 3., 2.));
+        assert_stitches_eq(
+            &square().center_on_origin().stitches,
+            &[Stitch::new(-1., -0.5), Stitch::new(1., -0.5), Stitch::new(1., 0.5)],
+        );
+        assert_stitches_eq(
+            &square().rotate_about_center(180.).stitches,
+            &[Stitch::new(3., 2.), Stitch::new(1., 2.), Stitch::new(1., 1.)],
+        );
+        assert_stitches_eq(
+            &square().mirror_horizontal().stitches,
+            &[Stitch::new(3., 1.), Stitch::new(1., 1.), Stitch::new(1., 2.)],
+        );
+        assert_stitches_eq(
+            &square().mirror_vertical().stitches,
+            &[Stitch::new(1., 2.), Stitch::new(3., 2.), Stitch::new(3., 1.)],
+        );
+    }
+
+    #[test]
+    fn transform_scale_to_fit() {
+        assert_eq!(square().scale_to_fit(10., 10.).bounds(), (-3., -1., 7., 4.));
+        assert_eq!(square().scale_to_fit(10., 1.).bounds(), (1., 1., 3., 2.));
+        let line = StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(0., 2.)]);
+        assert_eq!(line.scale_to_fit(1., 4.).bounds(), (0., -1., 0., 3.));
+        let dot = StitchGroup::new(vec![Stitch::new(1., 1.)]);
+        assert_eq!(dot.clone().scale_to_fit(1., 4.), dot);
+    }
+
+    #[test]
+    fn transform_color_group() {
+        let cg = ColorGroup {
+            thread: Some(Thread::new_str(Color::rgb(1, 2, 3), &"Name", &"Code")),
+            stitch_groups: vec![square(), square().translate(10., 10.)],
+        };
+        assert_eq!(cg.bounds(), (1., 1., 13., 12.));
+        let cg = cg.center_on_origin();
+        assert_eq!(cg.bounds(), (-6., -5.5, 6., 5.5));
+        assert!(cg.thread.is_some());
+    }
+
     #[test]
     fn stitch_relative_to() {
         let s = Stitch { x: 1.0, y: 1.0 };
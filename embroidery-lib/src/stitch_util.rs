@@ -1,6 +1,7 @@
 use crate::pattern::Pattern;
 use crate::stitch::Stitch;
 use crate::stitch::Thread;
+use crate::stitch::{ColorGroup, StitchGroup};
 
 pub enum StitchInfo<'a> {
     Color(&'a Option<Thread>, &'a Stitch),
@@ -47,3 +48,180 @@ pub fn build_stitch_list<'a>(pattern: &'a Pattern) -> Vec<StitchInfo<'a>> {
     re.push(StitchInfo::End(last_stitch));
     re
 }
+
+/// Split a move of `(delta_x, delta_y)` into as few steps as possible, where neither part of a step
+/// is bigger than `max_step`. A move that doesn't go anywhere needs no steps at all.
+pub fn split_jump(delta_x: i32, delta_y: i32, max_step: i32) -> Vec<(i32, i32)> {
+    let chunks = f64::max(
+        (f64::from(delta_x.abs()) / f64::from(max_step)).ceil(),
+        (f64::from(delta_y.abs()) / f64::from(max_step)).ceil(),
+    ) as i32;
+    let mut re = Vec::with_capacity(chunks as usize);
+    let (mut cx, mut cy) = (0, 0);
+    for i in 1..=chunks {
+        let (nx, ny) = (delta_x * i / chunks, delta_y * i / chunks);
+        re.push((nx - cx, ny - cy));
+        cx = nx;
+        cy = ny;
+    }
+    re
+}
+
+/// Convert a position in mm to the 0.1mm steps that most formats store stitches in.
+pub fn to_tenths(value: f64) -> i32 {
+    (value * 10.).round() as i32
+}
+
+/// The stitch at `(x, y)`; given in 0.1mm steps.
+pub fn from_tenths(x: i32, y: i32) -> Stitch {
+    Stitch::new(f64::from(x) / 10., f64::from(y) / 10.)
+}
+
+/// Groups the needle's moves into stitch & color groups; the way most formats store them.
+///
+/// Every run of stitches between jumps becomes a stitch group, which starts where the jumps ended
+/// up. A trim anywhere in the jumps before a group marks the group before it as cut.
+#[derive(Debug, Clone)]
+pub struct StitchGrouper {
+    color_groups: Vec<ColorGroup>,
+    stitch_groups: Vec<StitchGroup>,
+    stitches: Vec<Stitch>,
+    position: Stitch,
+    jumped: bool,
+    trimmed: bool,
+}
+
+impl Default for StitchGrouper {
+    fn default() -> Self {
+        StitchGrouper {
+            color_groups: Vec::new(),
+            stitch_groups: Vec::new(),
+            stitches: Vec::new(),
+            // The needle starts at the origin, so the first stitch is sewn from there.
+            position: ZERO_STITCH,
+            jumped: true,
+            trimmed: false,
+        }
+    }
+}
+
+impl StitchGrouper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sew a stitch from the needle's current position to `to`.
+    pub fn stitch(&mut self, to: Stitch) {
+        if self.jumped {
+            self.end_stitch_group();
+            self.stitches.push(self.position);
+            self.jumped = false;
+            self.trimmed = false;
+        }
+        self.stitches.push(to);
+        self.position = to;
+    }
+
+    /// Move the needle to `to` without sewing.
+    pub fn jump(&mut self, to: Stitch) {
+        self.jumped = true;
+        self.position = to;
+    }
+
+    /// Cut the thread where the needle is.
+    pub fn trim(&mut self) {
+        self.jumped = true;
+        self.trimmed = true;
+    }
+
+    /// Finish the current color; which was sewn with `thread`. Nothing is added if no stitches
+    /// were sewn with it.
+    pub fn end_color(&mut self, thread: Option<Thread>) {
+        self.end_stitch_group();
+        if !self.stitch_groups.is_empty() {
+            self.color_groups.push(ColorGroup {
+                thread,
+                stitch_groups: std::mem::take(&mut self.stitch_groups),
+            });
+        }
+        self.jumped = true;
+        self.trimmed = false;
+    }
+
+    /// Finish the last color with `thread` and return all of the color groups.
+    pub fn finish(mut self, thread: Option<Thread>) -> Vec<ColorGroup> {
+        self.end_color(thread);
+        self.color_groups
+    }
+
+    fn end_stitch_group(&mut self) {
+        if !self.stitches.is_empty() {
+            self.stitch_groups.push(StitchGroup {
+                stitches: std::mem::take(&mut self.stitches),
+                trim: true,
+                cut: self.trimmed,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colors::Color;
+
+    #[test]
+    fn test_tenths() {
+        assert_eq!(to_tenths(1.26), 13);
+        assert_eq!(to_tenths(-0.04), 0);
+        assert_eq!(from_tenths(13, -4), Stitch::new(1.3, -0.4));
+    }
+
+    #[test]
+    fn test_stitch_grouper() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let mut grouper = StitchGrouper::new();
+        grouper.stitch(Stitch::new(1.0, 0.0));
+        grouper.stitch(Stitch::new(1.0, 1.0));
+        grouper.trim();
+        grouper.jump(Stitch::new(3.0, 1.0));
+        grouper.stitch(Stitch::new(3.0, 2.0));
+        grouper.end_color(Some(red.clone()));
+        // A color that nothing was sewn with is dropped.
+        grouper.end_color(None);
+        grouper.jump(Stitch::new(2.0, 2.0));
+        grouper.stitch(Stitch::new(2.0, 3.0));
+        assert_eq!(
+            grouper.finish(None),
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 2.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 2.0), Stitch::new(2.0, 3.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_jump() {
+        assert_eq!(split_jump(0, 0, 127), vec![]);
+        assert_eq!(split_jump(-127, 127, 127), vec![(-127, 127)]);
+        assert_eq!(split_jump(300, -10, 127), vec![(100, -3), (100, -3), (100, -4)]);
+        assert_eq!(split_jump(-5, 243, 121), vec![(-1, 81), (-2, 81), (-2, 81)]);
+    }
+}
@@ -1,3 +1,5 @@
+use euclid::Transform2D;
+
 pub trait RemoveDuplicateStitches {
     fn remove_duplicate_stitches(self) -> Self;
 }
@@ -5,3 +7,87 @@ pub trait RemoveDuplicateStitches {
 pub trait SplitLongStitches {
     fn split_stitches(self, min_x: f64, max_x: f64, min_y: f64, max_y: f64) -> Self;
 }
+
+/// An anticlockwise rotation about the origin; `euclid`'s own rotations go the other way, as it
+/// expects +ve y to go down.
+fn rotation(degrees: f64) -> Transform2D<f64> {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Transform2D::row_major(cos, sin, -sin, cos, 0., 0.)
+}
+
+/// Moves stitches with a 2D affine transform; working in mm, with +ve y going up.
+///
+/// Rotations are anticlockwise in degrees. The helpers that work about the centre use the middle
+/// of the `bounds`, so the stitches stay where they were.
+pub trait Transform: Sized {
+    fn transform(self, transform: &Transform2D<f64>) -> Self;
+
+    /// The smallest box holding every stitch, as `(min_x, min_y, max_x, max_y)`.
+    fn bounds(&self) -> (f64, f64, f64, f64);
+
+    fn translate(self, dx: f64, dy: f64) -> Self {
+        self.transform(&Transform2D::create_translation(dx, dy))
+    }
+
+    /// Rotate about the origin.
+    fn rotate(self, degrees: f64) -> Self {
+        self.transform(&rotation(degrees))
+    }
+
+    /// Scale away from the origin.
+    fn scale(self, sx: f64, sy: f64) -> Self {
+        self.transform(&Transform2D::create_scale(sx, sy))
+    }
+
+    /// Lean the stitches over; by `x_degrees` from the y axis and `y_degrees` from the x axis.
+    fn skew(self, x_degrees: f64, y_degrees: f64) -> Self {
+        let (tan_x, tan_y) = (x_degrees.to_radians().tan(), y_degrees.to_radians().tan());
+        self.transform(&Transform2D::row_major(1., tan_y, tan_x, 1., 0., 0.))
+    }
+
+    /// Apply `transform` as if the centre were the origin.
+    fn transform_about_center(self, transform: &Transform2D<f64>) -> Self {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let (cx, cy) = ((min_x + max_x) / 2., (min_y + max_y) / 2.);
+        let about_center = Transform2D::create_translation(-cx, -cy)
+            .post_mul(transform)
+            .post_mul(&Transform2D::create_translation(cx, cy));
+        self.transform(&about_center)
+    }
+
+    fn center_on_origin(self) -> Self {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        self.translate(-(min_x + max_x) / 2., -(min_y + max_y) / 2.)
+    }
+
+    fn rotate_about_center(self, degrees: f64) -> Self {
+        self.transform_about_center(&rotation(degrees))
+    }
+
+    /// Flip left to right.
+    fn mirror_horizontal(self) -> Self {
+        self.transform_about_center(&Transform2D::create_scale(-1., 1.))
+    }
+
+    /// Flip top to bottom.
+    fn mirror_vertical(self) -> Self {
+        self.transform_about_center(&Transform2D::create_scale(1., -1.))
+    }
+
+    /// Scale evenly so the stitches fill as much of `width` by `height` as they can. Stitches that
+    /// are all in the same place are left alone, as they can't be made any bigger.
+    fn scale_to_fit(self, width: f64, height: f64) -> Self {
+        let (min_x, min_y, max_x, max_y) = self.bounds();
+        let factors = [width / (max_x - min_x), height / (max_y - min_y)];
+        let factor = factors
+            .iter()
+            .copied()
+            .filter(|factor| factor.is_finite())
+            .fold(f64::INFINITY, f64::min);
+        if factor.is_finite() {
+            self.transform_about_center(&Transform2D::create_scale(factor, factor))
+        } else {
+            self
+        }
+    }
+}
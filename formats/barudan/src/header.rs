@@ -0,0 +1,75 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
+
+/// U01 files have a fixed size header; most of which is unused. The stitches start straight after.
+pub const U01_HEADER_LEN: usize = 0x100;
+const FIELDS_OFFSET: usize = 0x80;
+// Bounds + unknown + stitch count + end.
+const FIELDS_LEN: usize = 8 + 4 + 4 + 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct U01Header {
+    // Min x, min y, max x, max y; in 0.1mm.
+    pub bounds: (i16, i16, i16, i16),
+    pub number_of_stitches: u32,
+    /// Where the needle finishes; relative to where it started.
+    pub end: (i16, i16),
+}
+
+impl U01Header {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        read_exact!(file, [_; FIELDS_OFFSET])?;
+        let bounds = (
+            read_int!(file, i16, LittleEndian)?,
+            read_int!(file, i16, LittleEndian)?,
+            read_int!(file, i16, LittleEndian)?,
+            read_int!(file, i16, LittleEndian)?,
+        );
+        // Unknown; always 0 in the files seen so far.
+        let _ = read_int!(file, u32, LittleEndian)?;
+        let number_of_stitches = read_int!(file, u32, LittleEndian)?;
+        let end = (read_int!(file, i16, LittleEndian)?, read_int!(file, i16, LittleEndian)?);
+        read_exact!(file, [_; U01_HEADER_LEN - FIELDS_OFFSET - FIELDS_LEN])?;
+        Ok(U01Header {
+            bounds,
+            number_of_stitches,
+            end,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_all(&[0; FIELDS_OFFSET])?;
+        file.write_i16::<LittleEndian>(self.bounds.0)?;
+        file.write_i16::<LittleEndian>(self.bounds.1)?;
+        file.write_i16::<LittleEndian>(self.bounds.2)?;
+        file.write_i16::<LittleEndian>(self.bounds.3)?;
+        file.write_u32::<LittleEndian>(0)?;
+        file.write_u32::<LittleEndian>(self.number_of_stitches)?;
+        file.write_i16::<LittleEndian>(self.end.0)?;
+        file.write_i16::<LittleEndian>(self.end.1)?;
+        file.write_all(&[0; U01_HEADER_LEN - FIELDS_OFFSET - FIELDS_LEN])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = U01Header {
+            bounds: (-100, -200, 300, 400),
+            number_of_stitches: 1234,
+            end: (-10, 20),
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(data.len(), U01_HEADER_LEN);
+        assert_eq!(data[0x80..0x82], [0x9C, 0xFF]);
+        assert_eq!(data[0x8C..0x90], [0xD2, 0x04, 0x00, 0x00]);
+        assert_eq!(U01Header::build(&mut &data[..]).unwrap(), header);
+    }
+}
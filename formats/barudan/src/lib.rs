@@ -0,0 +1,49 @@
+mod header;
+mod read;
+mod stitch_info;
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use read::BarudanPatternReader;
+pub use write::BarudanPatternWriter;
+
+const DSB_NAME: &str = "dsb";
+const DSB_EXTENSIONS: [&str; 1] = ["dsb"];
+const U01_NAME: &str = "u01";
+const U01_EXTENSIONS: [&str; 1] = ["u01"];
+
+#[derive(Default)]
+pub struct DsbPatternFormat {}
+
+impl PatternFormat for DsbPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        DSB_NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &DSB_EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(BarudanPatternReader::dsb()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(BarudanPatternWriter::dsb()))
+    }
+}
+#[derive(Default)]
+pub struct U01PatternFormat {}
+
+impl PatternFormat for U01PatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        U01_NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &U01_EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(BarudanPatternReader::u01()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(BarudanPatternWriter::u01()))
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::Read;
+
+use embroidery_fmt_dst::read_dst_header;
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+use embroidery_lib::utils::{generate_color, ReadByteIterator};
+
+use crate::header::{U01Header, U01_HEADER_LEN};
+use crate::stitch_info::{BarudanFormat, MAX_NEEDLE};
+
+pub struct BarudanPatternReader {
+    format: BarudanFormat,
+}
+
+impl BarudanPatternReader {
+    pub fn dsb() -> Self {
+        Self {
+            format: BarudanFormat::Dsb,
+        }
+    }
+    pub fn u01() -> Self {
+        Self {
+            format: BarudanFormat::U01,
+        }
+    }
+}
+
+impl PatternReader for BarudanPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        // The headers don't have anything to check; so the best that can be done is checking every
+        // command is known.
+        let mut iter = ReadByteIterator::new(item);
+        let result = read_header(self.format, &mut iter).and_then(|_| loop {
+            match read_stitch(self.format, &mut iter)? {
+                Some(StitchInformation::End) => break Ok(()),
+                None => {
+                    break Err(ReadError::invalid_format(
+                        "The stitches don't finish with an end command",
+                    ))
+                },
+                Some(_) => {},
+            }
+        });
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+        match result {
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+            Ok(()) => Ok(true),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut iter = ReadByteIterator::new(item);
+        let attributes = read_header(self.format, &mut iter)?;
+        let color_groups = read_stitches(self.format, &mut iter)?;
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+        if color_groups.is_empty() {
+            return Err(ReadError::invalid_format("The file doesn't contain any stitches"));
+        }
+
+        let name = attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                PatternAttribute::Title(title) => Some(title.clone()),
+                _ => None,
+            })
+            .next()
+            .unwrap_or_else(|| "Untitled".to_string());
+        Ok(Pattern {
+            name,
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn read_header(format: BarudanFormat, item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<PatternAttribute>> {
+    match format {
+        BarudanFormat::Dsb => Ok(read_dst_header(item)?.attributes),
+        BarudanFormat::U01 => {
+            let header: Vec<u8> = item.take(U01_HEADER_LEN).collect();
+            U01Header::build(&mut &header[..])?;
+            Ok(vec![])
+        },
+    }
+}
+
+/// A made up thread for a needle, as the formats only say which needle to use.
+pub fn needle_thread(needle: u8) -> Thread {
+    let color = generate_color(usize::from(needle - 1), usize::from(MAX_NEEDLE));
+    let mut thread = Thread::new(color, format!("Needle {}", needle), String::new());
+    thread.attributes.insert("needle".to_string(), needle.to_string());
+    thread
+}
+
+fn read_stitch(format: BarudanFormat, in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let bytes: Vec<u8> = in_bytes.take(3).collect();
+    match bytes[..] {
+        [] => Ok(None),
+        [control, y, x] => Ok(Some(format.parse([control, y, x])?)),
+        _ => Err(ReadError::invalid_format("Stitch data ends part way through a stitch")),
+    }
+}
+
+fn read_stitches(format: BarudanFormat, item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<ColorGroup>> {
+    let mut grouper = StitchGrouper::new();
+    let mut needle = None;
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(stitch) = read_stitch(format, item)? {
+        match stitch {
+            StitchInformation::Move(dx, dy, StitchType::Regular) => {
+                cx += i32::from(dx);
+                cy += i32::from(dy);
+                grouper.stitch(from_tenths(cx, cy));
+            },
+            StitchInformation::Move(dx, dy, StitchType::Jump) => {
+                cx += i32::from(dx);
+                cy += i32::from(dy);
+                grouper.jump(from_tenths(cx, cy));
+            },
+            StitchInformation::Trim => grouper.trim(),
+            StitchInformation::Move(_, _, _) | StitchInformation::NeedleChange(_) => {
+                grouper.end_color(needle.map(needle_thread));
+                // A stop leaves the same needle in use.
+                if let StitchInformation::NeedleChange(new_needle) = stitch {
+                    needle = Some(new_needle);
+                }
+            },
+            StitchInformation::End => break,
+        }
+    }
+    Ok(grouper.finish(needle.map(needle_thread)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let data = [
+            0x0A, 0x00, 0x00, // Needle 2
+            0x80, 0x00, 0x0A, // Stitch to (1.0, 0.0)
+            0x80, 0x0A, 0x00, // Stitch to (1.0, 1.0)
+            0x07, 0x00, 0x00, // Trim
+            0x81, 0x00, 0x14, // Jump to (3.0, 1.0)
+            0xC0, 0x0A, 0x00, // Stitch to (3.0, 0.0)
+            0x08, 0x00, 0x00, // Stop
+            0xA1, 0x00, 0x0A, // Jump to (2.0, 0.0)
+            0x80, 0x0A, 0x0A, // Stitch to (3.0, 1.0)
+            0xF8, 0x00, 0x00, // End
+            0x80, 0x0A, 0x0A, // Ignored
+        ];
+        let color_groups = read_stitches(BarudanFormat::U01, to_u8_iter!(data)).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(needle_thread(2)),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(needle_thread(2)),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_needle_thread() {
+        let thread = needle_thread(3);
+        assert_eq!(thread.name, "Needle 3");
+        assert_eq!(thread.attributes.get("needle"), Some(&"3".to_string()));
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let mut data = vec![0; U01_HEADER_LEN];
+        data.extend_from_slice(&[0x80, 0x00, 0x0A, 0xF8, 0x00, 0x00]);
+        assert!(BarudanPatternReader::u01().is_loadable(&mut &data[..]).unwrap());
+        assert!(!BarudanPatternReader::dsb().is_loadable(&mut &data[..]).unwrap());
+        assert!(!BarudanPatternReader::u01()
+            .is_loadable(&mut &data[..U01_HEADER_LEN + 2])
+            .unwrap());
+    }
+}
@@ -0,0 +1,185 @@
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// Every stitch is 3 bytes: `control, |dy|, |dx|`; where the control byte holds the direction of
+// the move and a command. Moves are in 0.1mm increments with y increasing upwards.
+const NEGATIVE_Y: u8 = 0x40;
+const NEGATIVE_X: u8 = 0x20;
+const COMMAND_MASK: u8 = 0x1F;
+// Set on every move written by Barudan's software.
+const MOVE_FLAG: u8 = 0x80;
+const STITCH: u8 = 0x00;
+const JUMP: u8 = 0x01;
+const TRIM: u8 = 0x07;
+const STOP: u8 = 0x08;
+// Needle 1 is `NEEDLE_BASE + 1`.
+const NEEDLE_BASE: u8 = 0x08;
+pub const MAX_NEEDLE: u8 = 15;
+const END: u8 = 0xF8;
+
+pub const MAX_MOVE: i32 = 127;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BarudanFormat {
+    // Has a DST header; commands other than moves have the top 3 bits set.
+    Dsb,
+    // Has a mostly empty header; commands other than moves have the top 3 bits clear.
+    U01,
+}
+
+impl BarudanFormat {
+    fn command_prefix(self) -> u8 {
+        match self {
+            BarudanFormat::Dsb => 0xE0,
+            BarudanFormat::U01 => 0x00,
+        }
+    }
+
+    pub fn to_bytes(self, stitch: StitchInformation) -> Option<[u8; 3]> {
+        let command = |cmd| Some([self.command_prefix() | cmd, 0x00, 0x00]);
+        match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => {
+                let cmd = match stitch_type {
+                    StitchType::Regular => STITCH,
+                    StitchType::Jump => JUMP,
+                    StitchType::Stop if dx == 0 && dy == 0 => return command(STOP),
+                    _ => return None,
+                };
+                if dx == i8::MIN || dy == i8::MIN {
+                    return None;
+                }
+                let mut control = MOVE_FLAG | cmd;
+                if dx < 0 {
+                    control |= NEGATIVE_X;
+                }
+                if dy < 0 {
+                    control |= NEGATIVE_Y;
+                }
+                Some([control, dy.unsigned_abs(), dx.unsigned_abs()])
+            },
+            StitchInformation::Trim => command(TRIM),
+            StitchInformation::NeedleChange(needle) if (1..=MAX_NEEDLE).contains(&needle) => {
+                command(NEEDLE_BASE + needle)
+            },
+            StitchInformation::NeedleChange(_) => None,
+            StitchInformation::End => Some([END, 0x00, 0x00]),
+        }
+    }
+
+    pub fn parse(self, [control, y, x]: [u8; 3]) -> ReadResult<StitchInformation> {
+        if control == END {
+            return Ok(StitchInformation::End);
+        }
+        let cmd = control & COMMAND_MASK;
+        if cmd == STITCH || cmd == JUMP {
+            if x > i8::MAX as u8 || y > i8::MAX as u8 {
+                return Err(ReadError::invalid_format(format!("Move of ({}, {}) is too big", x, y)));
+            }
+            let dx = if control & NEGATIVE_X == 0 { x as i8 } else { -(x as i8) };
+            let dy = if control & NEGATIVE_Y == 0 { y as i8 } else { -(y as i8) };
+            let stitch_type = if cmd == JUMP {
+                StitchType::Jump
+            } else {
+                StitchType::Regular
+            };
+            return Ok(StitchInformation::Move(dx, dy, stitch_type));
+        }
+        if control & !COMMAND_MASK != self.command_prefix() {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown stitch command 0x{:02X}",
+                control
+            )));
+        }
+        match cmd {
+            TRIM => Ok(StitchInformation::Trim),
+            STOP => Ok(StitchInformation::Move(0, 0, StitchType::Stop)),
+            cmd if (NEEDLE_BASE + 1..=NEEDLE_BASE + MAX_NEEDLE).contains(&cmd) => {
+                Ok(StitchInformation::NeedleChange(cmd - NEEDLE_BASE))
+            },
+            _ => Err(ReadError::invalid_format(format!(
+                "Unknown stitch command 0x{:02X}",
+                control
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_bytes() {
+        let dsb = BarudanFormat::Dsb;
+        let u01 = BarudanFormat::U01;
+        assert_eq!(
+            dsb.to_bytes(StitchInformation::Move(5, -10, StitchType::Regular)),
+            Some([0xC0, 0x0A, 0x05])
+        );
+        assert_eq!(
+            u01.to_bytes(StitchInformation::Move(-127, 127, StitchType::Jump)),
+            Some([0xA1, 0x7F, 0x7F])
+        );
+        assert_eq!(dsb.to_bytes(StitchInformation::Move(-128, 0, StitchType::Jump)), None);
+        assert_eq!(dsb.to_bytes(StitchInformation::Move(1, 0, StitchType::Stop)), None);
+        assert_eq!(dsb.to_bytes(StitchInformation::Trim), Some([0xE7, 0x00, 0x00]));
+        assert_eq!(u01.to_bytes(StitchInformation::Trim), Some([0x07, 0x00, 0x00]));
+        assert_eq!(
+            dsb.to_bytes(StitchInformation::NeedleChange(1)),
+            Some([0xE9, 0x00, 0x00])
+        );
+        assert_eq!(
+            u01.to_bytes(StitchInformation::NeedleChange(15)),
+            Some([0x17, 0x00, 0x00])
+        );
+        assert_eq!(u01.to_bytes(StitchInformation::NeedleChange(16)), None);
+        assert_eq!(u01.to_bytes(StitchInformation::End), Some([0xF8, 0x00, 0x00]));
+    }
+
+    #[test]
+    fn test_parse() {
+        let dsb = BarudanFormat::Dsb;
+        let u01 = BarudanFormat::U01;
+        assert_eq!(
+            dsb.parse([0x60, 0x01, 0x02]).unwrap(),
+            StitchInformation::Move(-2, -1, StitchType::Regular)
+        );
+        assert_eq!(
+            u01.parse([0x01, 0x01, 0x02]).unwrap(),
+            StitchInformation::Move(2, 1, StitchType::Jump)
+        );
+        assert_eq!(
+            dsb.parse([0xE8, 0x00, 0x00]).unwrap(),
+            StitchInformation::Move(0, 0, StitchType::Stop)
+        );
+        assert_eq!(
+            u01.parse([0x0B, 0x00, 0x00]).unwrap(),
+            StitchInformation::NeedleChange(3)
+        );
+        assert_eq!(u01.parse([0xF8, 0x00, 0x00]).unwrap(), StitchInformation::End);
+        // Commands for the other format.
+        assert!(dsb.parse([0x07, 0x00, 0x00]).is_err());
+        assert!(u01.parse([0xE7, 0x00, 0x00]).is_err());
+        assert!(u01.parse([0x18, 0x00, 0x00]).is_err());
+        assert!(u01.parse([0x80, 0x80, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for &format in &[BarudanFormat::Dsb, BarudanFormat::U01] {
+            for &stitch in &[
+                StitchInformation::Move(0, 0, StitchType::Regular),
+                StitchInformation::Move(-127, 127, StitchType::Regular),
+                StitchInformation::Move(3, -4, StitchType::Jump),
+                StitchInformation::Move(0, 0, StitchType::Stop),
+                StitchInformation::Trim,
+                StitchInformation::NeedleChange(7),
+                StitchInformation::End,
+            ] {
+                let bytes = format.to_bytes(stitch).unwrap();
+                assert_eq!(format.parse(bytes).unwrap(), stitch);
+            }
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::Write;
+
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_fmt_dst::write_dst_header;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, to_tenths};
+
+use crate::header::U01Header;
+use crate::stitch_info::{BarudanFormat, MAX_MOVE, MAX_NEEDLE};
+
+pub struct BarudanPatternWriter {
+    format: BarudanFormat,
+}
+
+impl BarudanPatternWriter {
+    pub fn dsb() -> Self {
+        Self {
+            format: BarudanFormat::Dsb,
+        }
+    }
+    pub fn u01() -> Self {
+        Self {
+            format: BarudanFormat::U01,
+        }
+    }
+}
+
+impl PatternWriter for BarudanPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let (stitches, end) = into_barudan_stitches(pattern)?;
+        match self.format {
+            BarudanFormat::Dsb => write_dst_header(pattern, stitches.len(), end, writer)?,
+            BarudanFormat::U01 => {
+                let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+                U01Header {
+                    bounds: (
+                        to_tenths(min_x) as i16,
+                        to_tenths(min_y) as i16,
+                        to_tenths(max_x) as i16,
+                        to_tenths(max_y) as i16,
+                    ),
+                    number_of_stitches: stitches.len() as u32,
+                    end: (end.0 as i16, end.1 as i16),
+                }
+                .write(writer)?
+            },
+        }
+        for &st in &stitches {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&self.format.to_bytes(st).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+/// The needle to sew a color group with; which is kept from the file it was read from if possible.
+fn needle_for(idx: usize, cg: &ColorGroup) -> u8 {
+    cg.thread
+        .as_ref()
+        .and_then(|thread| thread.attributes.get("needle"))
+        .and_then(|needle| needle.parse().ok())
+        .filter(|needle| (1..=MAX_NEEDLE).contains(needle))
+        .unwrap_or_else(|| (idx % usize::from(MAX_NEEDLE)) as u8 + 1)
+}
+
+/// The stitches to write; along with where the needle ends up in 0.1mm.
+fn into_barudan_stitches(pattern: &Pattern) -> Result<(Vec<StitchInformation>, (i32, i32)), WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        re.push(StitchInformation::NeedleChange(needle_for(i, cg)));
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                for (dx, dy) in split_jump(x - ox, y - oy, MAX_MOVE) {
+                    re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Jump));
+                }
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_MOVE || dy.abs() > MAX_MOVE {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation::Trim);
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok((re, (ox, oy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::needle_thread;
+
+    fn pattern() -> Pattern {
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(needle_thread(4)),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0),
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(30.0, -1.0), Stitch::new(30.5, -2.5)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(-60.0, 40.0), Stitch::new(-59.0, 41.0)]).with_trim(true),
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_needle_for() {
+        let pattern = pattern();
+        assert_eq!(needle_for(0, &pattern.color_groups[0]), 4);
+        assert_eq!(needle_for(1, &pattern.color_groups[1]), 2);
+        assert_eq!(needle_for(15, &pattern.color_groups[1]), 1);
+    }
+
+    #[test]
+    fn test_empty_pattern() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![],
+        };
+        for writer in [BarudanPatternWriter::dsb(), BarudanPatternWriter::u01()] {
+            let mut data = vec![];
+            writer.write_pattern(&pattern, &mut data).unwrap();
+            assert!(!data.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(BarudanPatternWriter::u01().write_pattern(&pattern, &mut data).is_err());
+    }
+}
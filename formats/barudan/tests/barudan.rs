@@ -0,0 +1,33 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_barudan::{BarudanPatternReader, BarudanPatternWriter};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip() {
+    let pattern = load_sample();
+    for (writer, reader) in &[
+        (BarudanPatternWriter::dsb(), BarudanPatternReader::dsb()),
+        (BarudanPatternWriter::u01(), BarudanPatternReader::u01()),
+    ] {
+        let mut data = Vec::new();
+        writer.write_pattern(&pattern, &mut data).unwrap();
+        assert!(reader.is_loadable(&mut Cursor::new(&data)).unwrap());
+        let read = reader.read_pattern(&mut Cursor::new(&data)).unwrap();
+
+        assert_eq!(read.color_groups.len(), 3);
+        for (i, (read_cg, cg)) in read.color_groups.iter().zip(&pattern.color_groups).enumerate() {
+            assert_eq!(read_cg.stitch_groups, cg.stitch_groups);
+            // The format only has needle numbers; so each color gets its own needle.
+            assert_eq!(read_cg.thread.as_ref().unwrap().name, format!("Needle {}", i + 1));
+        }
+    }
+}
@@ -1,9 +1,9 @@
-// mod read;
+mod read;
 mod write;
 
 use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
 
-// pub use self::read::CsvPatternReader;
+pub use self::read::CsvPatternReader;
 pub use self::write::CsvPatternWriter;
 
 const NAME: &str = "csv";
@@ -20,8 +20,7 @@ impl PatternFormat for CsvPatternFormat {
         &EXTENSIONS
     }
     fn reader(&self) -> Option<Box<dyn PatternReader>> {
-        None
-        // Some(CsvPatternReader::default())
+        Some(Box::from(CsvPatternReader::default()))
     }
     fn writer(&self) -> Option<Box<dyn PatternWriter>> {
         let writer = Box::from(CsvPatternWriter::default());
@@ -1,324 +1,393 @@
 use std::io::Read;
-use std::iter::FromIterator;
 
 use embroidery_lib::format::PatternReader;
-use embroidery_lib::utils::ReadByteIterator;
 use embroidery_lib::prelude::*;
 
-pub struct CsvPatternReader {}
+// Variables that the writer calculates from the pattern, so there's no need to keep them.
+pub const CALCULATED_VARS: [&str; 8] = [
+    "STITCH_COUNT",
+    "THREAD_COUNT",
+    "EXTENTS_LEFT",
+    "EXTENTS_TOP",
+    "EXTENTS_RIGHT",
+    "EXTENTS_BOTTOM",
+    "EXTENTS_WIDTH",
+    "EXTENTS_HEIGHT",
+];
+// The pattern's name; when it's missing the title is used instead.
+pub const NAME_VAR: &str = "Name";
+// Variables that are read back as something other than an arbitrary attribute.
+pub const RESERVED_VARS: [&str; 4] = [NAME_VAR, "Title", "Author", "Copyright"];
+// Written before the name of any arbitrary attribute that would otherwise be read back as one of
+// the variables above.
+pub const VAR_ESCAPE: char = '\\';
 
-impl Default for CsvPatternReader {
-    fn default() -> Self {
-        CsvPatternReader {}
-    }
-}
+#[derive(Default)]
+pub struct CsvPatternReader {}
 
 impl PatternReader for CsvPatternReader {
     fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
-        // Load the header
-        // Check the last byte of the file? maybe
-        let mut iter = ReadByteIterator::new(item);
-        match read_dst_header(&mut iter) {
-            Err(ReadError::invalid_format(_)) => Ok(false),
-            Err(error) => Err(error),
-            Ok(_) => Ok(true),
-        }
+        let mut data = Vec::with_capacity(512);
+        item.take(512).read_to_end(&mut data)?;
+        let data = String::from_utf8_lossy(&data);
+        // Only check the first line as the rest may have been cut off part way through.
+        let first_line = data.lines().map(str::trim).find(|line| !line.is_empty());
+        Ok(match first_line {
+            Some(line) => {
+                let kind = line.trim_start_matches('"');
+                kind.starts_with('#') || kind.starts_with('>') || kind.starts_with('$') || kind.starts_with('*')
+            },
+            None => false,
+        })
     }
 
-    fn read_pattern(&self, file: &mut dyn Read) -> Result<Pattern, ReadError> {
-        // Read the header
-        let mut iter = ReadByteIterator::new(file);
-        let attributes = read_dst_header(&mut iter)?;
-        let color_groups = read_stitches(&mut iter)?;
-        let (title, attributes) = extract_title(attributes);
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut data = String::new();
+        if item.read_to_string(&mut data).is_err() {
+            return Err(ReadError::invalid_format("CSV file is not valid UTF-8"));
+        }
+
+        let mut name = None;
+        let mut title = None;
+        let mut attributes = Vec::new();
+        let mut threads = Vec::new();
+        let mut stitches = StitchBuilder::default();
+
+        for (line_no, line) in data.lines().enumerate() {
+            let fields = split_fields(line).map_err(|msg| line_error(line_no, msg))?;
+            let (kind, values) = match fields.split_first() {
+                Some((kind, values)) => (kind.as_str(), values),
+                None => continue,
+            };
+            match kind {
+                "#" => {},
+                ">" => match read_var(values).map_err(|msg| line_error(line_no, msg))? {
+                    Some(Var::Name(value)) => name = Some(value),
+                    Some(Var::Attribute(attr)) => {
+                        if let PatternAttribute::Title(value) = &attr {
+                            title = Some(value.clone());
+                        }
+                        attributes.push(attr);
+                    },
+                    None => {},
+                },
+                "$" => threads.push(read_thread(values).map_err(|msg| line_error(line_no, msg))?),
+                "*" => {
+                    if !stitches.add(values).map_err(|msg| line_error(line_no, msg))? {
+                        break;
+                    }
+                },
+                _ => return Err(line_error(line_no, format!("Unknown line type {:?}", kind))),
+            }
+        }
+
+        let mut threads = threads.into_iter();
+        let color_groups = stitches
+            .finish()
+            .into_iter()
+            .map(|stitch_groups| ColorGroup {
+                thread: threads.next().flatten(),
+                stitch_groups,
+            })
+            .collect();
+
         Ok(Pattern {
-            name: title,
+            name: name.or(title).unwrap_or_else(|| "Untitled".to_string()),
             attributes,
             color_groups,
         })
     }
 }
 
-fn extract_title(attrs: Vec<PatternAttribute>) -> (String, Vec<PatternAttribute>) {
-    let mut new_attrs: Vec<PatternAttribute> = Vec::new();
-    let mut title = "Untitled".to_string();
-    for attr in attrs {
-        if let PatternAttribute::Title(ttl) = attr {
-            title = ttl;
-        } else {
-            new_attrs.push(attr);
-        }
-    }
-    let title_attr = PatternAttribute::Title(title.to_string());
-    new_attrs.push(title_attr);
-    (title, new_attrs)
+fn line_error<S: Into<String>>(line_no: usize, msg: S) -> ReadError {
+    ReadError::invalid_format(format!("Line {}: {}", line_no + 1, msg.into()))
 }
 
-fn read_dst_header(item: &mut dyn Iterator<Item = u8>) -> Result<Vec<PatternAttribute>, ReadError> {
-    let mut attrs: Vec<PatternAttribute> = Vec::new();
-    let mut header_iter = item.take(512);
-
+/// Split a line into its fields; where each field is optionally surrounded by double quotes, and
+/// any double quotes inside a quoted field are doubled up.
+fn split_fields(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    if chars.peek().is_none() {
+        return Ok(fields);
+    }
     loop {
-        match read_header_item(&mut header_iter)? {
-            ParseResult::Some(header) => attrs.push(header),
-            ParseResult::Skip => (),
-            _ => break,
+        let mut field = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        field.push('"');
+                    },
+                    Some('"') => break,
+                    Some(c) => field.push(c),
+                    None => return Err("Unterminated quoted field".to_string()),
+                }
+            }
+            match chars.next() {
+                Some(',') => fields.push(field),
+                None => {
+                    fields.push(field);
+                    return Ok(fields);
+                },
+                Some(c) => return Err(format!("Unexpected {:?} after a quoted field", c)),
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    Some(',') => break,
+                    Some(c) => field.push(c),
+                    None => {
+                        fields.push(field.trim().to_string());
+                        return Ok(fields);
+                    },
+                }
+            }
+            fields.push(field.trim().to_string());
         }
     }
-    // Drain the rest of the iterator.
-    header_iter.last();
-    Ok(attrs)
 }
 
-fn read_header_item(mut header_iter: &mut dyn Iterator<Item = u8>) -> Result<ParseResult<PatternAttribute>, ReadError> {
-    let header = &match read_header_name(&mut header_iter) {
-        ParseResult::Some(header) => header,
-        ParseResult::Skip => return Ok(ParseResult::Skip),
-        ParseResult::Exhausted => return Ok(ParseResult::Exhausted),
-    };
-    let content = &match read_header_content(&mut header_iter) {
-        ParseResult::Some(content) => content,
-        ParseResult::Skip => return Ok(ParseResult::Skip),
-        ParseResult::Exhausted => return Ok(ParseResult::Exhausted),
+enum Var {
+    Name(String),
+    Attribute(PatternAttribute),
+}
+
+fn read_var(values: &[String]) -> Result<Option<Var>, String> {
+    let (key, value) = match values {
+        // The writer puts a single colon after every name.
+        [key, value, ..] => (key.strip_suffix(':').unwrap_or(key), value.clone()),
+        _ => return Err("Variables need a name and a value".to_string()),
     };
-    debug!(
-        "Read DST Header: {:?}:{:?}",
-        String::from_utf8_lossy(header).to_string(),
-        String::from_utf8_lossy(content).to_string()
-    );
-    match header {
-        b"LA" => Ok(ParseResult::Some(PatternAttribute::Title(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
-        b"AU" => Ok(ParseResult::Some(PatternAttribute::Author(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
-        b"CP" => Ok(ParseResult::Some(PatternAttribute::Copyright(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
-        // We can skip these because they're calculated from the stitches.
-        b"ST" => Ok(ParseResult::Skip),
-        b"CO" => Ok(ParseResult::Skip),
-        b"+X" => Ok(ParseResult::Skip),
-        b"+Y" => Ok(ParseResult::Skip),
-        b"-X" => Ok(ParseResult::Skip),
-        b"-Y" => Ok(ParseResult::Skip),
-        // We can skip these because they're all related to multi-file patterns, which we don't support
-        b"AX" => Ok(ParseResult::Skip),
-        b"AY" => Ok(ParseResult::Skip),
-        b"MX" => Ok(ParseResult::Skip),
-        b"MY" => Ok(ParseResult::Skip),
-        b"PD" => Ok(ParseResult::Skip),
-        _ => Ok(ParseResult::Some(PatternAttribute::Arbitary(
-            String::from_utf8_lossy(header).to_string(),
-            String::from_utf8_lossy(content).to_string(),
+    Ok(match key {
+        key if key.starts_with(VAR_ESCAPE) => Some(Var::Attribute(PatternAttribute::Arbitary(
+            key[VAR_ESCAPE.len_utf8()..].to_string(),
+            value,
         ))),
-    }
+        key if CALCULATED_VARS.contains(&key) => None,
+        NAME_VAR => Some(Var::Name(value)),
+        "Title" => Some(Var::Attribute(PatternAttribute::Title(value))),
+        "Author" => Some(Var::Attribute(PatternAttribute::Author(value))),
+        "Copyright" => Some(Var::Attribute(PatternAttribute::Copyright(value))),
+        key => Some(Var::Attribute(PatternAttribute::Arbitary(key.to_string(), value))),
+    })
 }
 
-fn read_header_name(in_bytes: &mut dyn Iterator<Item = u8>) -> ParseResult<[u8; 2]> {
-    let header_bytes = Vec::from_iter(in_bytes.take(3));
-    let items = header_bytes.as_slice();
-    if items.len() < 3 {
-        ParseResult::Exhausted
-    } else if items[2] != b':' && items[2] != b'*' {
-        ParseResult::Skip
-    } else {
-        ParseResult::Some([items[0], items[1]])
+fn read_thread(values: &[String]) -> Result<Option<Thread>, String> {
+    let (red, green, blue, name, code, extra) = match values {
+        [_, red, green, blue, name, code, extra @ ..] => (red, green, blue, name, code, extra),
+        _ => return Err("Threads need a number, red, green, blue, description and catalog number".to_string()),
+    };
+    if red.is_empty() && green.is_empty() && blue.is_empty() {
+        return Ok(None);
     }
-}
-
-fn read_header_content(in_bytes: &mut dyn Iterator<Item = u8>) -> ParseResult<Vec<u8>> {
-    let mut items = Vec::new();
-    let mut exhausted = true;
-    for item in in_bytes {
-        if item == b'\r' {
-            exhausted = false;
-            break;
-        } else {
-            items.push(item)
+    let parse = |value: &str| {
+        value
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid color component {:?}", value))
+    };
+    let color = Color::rgb(parse(red)?, parse(green)?, parse(blue)?);
+    let mut thread = Thread::new_str(color, name, code);
+    // Embroidermodder stops at the catalog number; the manufacturer and attributes are our own.
+    if let Some((manufacturer, attributes)) = extra.split_first() {
+        if !manufacturer.is_empty() {
+            thread.manufacturer = Some(manufacturer.clone());
+        }
+        if attributes.len() % 2 != 0 {
+            return Err("Thread attributes need both a name and a value".to_string());
+        }
+        for pair in attributes.chunks_exact(2) {
+            thread.attributes.insert(pair[0].clone(), pair[1].clone());
         }
     }
-    if !exhausted || !items.is_empty() {
-        ParseResult::Some(items)
-    } else {
-        ParseResult::Exhausted
-    }
+    Ok(Some(thread))
 }
 
-fn read_stitches(item: &mut dyn Iterator<Item = u8>) -> Result<Vec<ColorGroup>, ReadError> {
-    let mut color_groups = Vec::new();
-    let mut stitch_groups = Vec::new();
-    let mut stitches = Vec::new();
-    let mut last_irregulars: Vec<(i32, i32, StitchType)> = Vec::new();
-    let mut cx: i32 = 0;
-    let mut cy: i32 = 0;
-    loop {
-        let s = read_stitch(item);
-        match s {
-            ParseResult::Some(StitchInformation::Move(x, y, stitch_type)) => {
-                if !last_irregulars.is_empty() && stitch_type.is_regular() {
-                    debug!("Last Stitch: {:?}", stitches.last());
-                    if !stitches.is_empty() {
-                        let old_stitches = stitches;
-                        stitches = Vec::new();
-                        stitch_groups.push(StitchGroup {
-                            stitches: old_stitches,
-                            trim: true,
-                            cut: determine_cut(&last_irregulars),
-                        });
-                        debug!("Last cut: {}", stitch_groups[0].cut)
-                    }
-                    if !stitch_groups.is_empty() && last_irregulars.iter().any(|&(_, _, ref st)| st.is_stop()) {
-                        let old_stitch_groups = stitch_groups;
-                        stitch_groups = Vec::new();
-                        color_groups.push(ColorGroup {
-                            stitch_groups: old_stitch_groups,
-                            // TODO: threads
-                            thread: None,
-                        });
-                    }
-                    last_irregulars = Vec::new();
-                    // First stitch after a series of jumps should be the location where the
-                    // jumps ended up.
-                    stitches.push(Stitch {
-                        x: f64::from(cx) / 10.,
-                        y: f64::from(cy) / 10.,
-                    });
-                }
-                if !stitch_type.is_regular() && last_irregulars.is_empty() {
-                    debug!("Last Regular ({:?},{:?}). Delta: {},{}", cx, cy, x, y);
-                    last_irregulars.push((cx, cy, StitchType::Regular));
-                }
-                cx += i32::from(x);
-                cy += i32::from(y);
+#[derive(Default)]
+struct StitchBuilder {
+    color_groups: Vec<Vec<StitchGroup>>,
+    stitch_groups: Vec<StitchGroup>,
+    stitches: Vec<Stitch>,
+    // Where the needle moved to without making a stitch, which is where the next group starts.
+    jumped_to: Option<Stitch>,
+    trim: bool,
+    cut: bool,
+    color_started: bool,
+}
 
-                if stitch_type.is_regular() {
-                    stitches.push(Stitch {
-                        x: f64::from(cx) / 10.,
-                        y: f64::from(cy) / 10.,
-                    });
-                } else {
-                    debug!("Irregular {:?} {:?} {:?}", cx, cy, stitch_type);
-                    last_irregulars.push((cx, cy, stitch_type));
+impl StitchBuilder {
+    /// Add a stitch entry; returning false once the design has ended.
+    fn add(&mut self, values: &[String]) -> Result<bool, String> {
+        let (kind, x, y) = match values {
+            [kind, x, y, ..] => (kind.as_str(), x, y),
+            _ => return Err("Stitches need a type, x and y".to_string()),
+        };
+        let parse = |value: &str| {
+            value
+                .parse::<f64>()
+                .map_err(|_| format!("Invalid coordinate {:?}", value))
+        };
+        let stitch = Stitch::new(parse(x)?, parse(y)?);
+        match kind {
+            "STITCH" => {
+                if let Some(start) = self.jumped_to.take() {
+                    self.end_group();
+                    self.stitches.push(start);
+                    // A stitch where the needle jumped to is the start of the group, rather than
+                    // another stitch on top of it.
+                    if stitch == start {
+                        return Ok(true);
+                    }
                 }
+                self.stitches.push(stitch);
+            },
+            "JUMP" => {
+                self.end_group();
+                self.jumped_to = Some(stitch);
+            },
+            "TRIM" => {
+                self.trim = true;
+                self.end_group();
             },
-            ParseResult::Some(StitchInformation::End) => {
-                break;
+            "CUT" => {
+                self.cut = true;
+                self.end_group();
             },
-            ParseResult::Exhausted => {
-                break;
+            "COLOR" => {
+                self.end_color();
+                self.color_started = true;
+                self.jumped_to = Some(stitch);
             },
-            ParseResult::Skip => {},
+            "EMPTY" => {
+                self.end_group();
+                self.jumped_to = None;
+                self.stitch_groups.push(StitchGroup::new(vec![]));
+            },
+            "END" => return Ok(false),
+            "UNKNOWN" => {},
+            kind => return Err(format!("Unknown stitch type {:?}", kind)),
         }
+        Ok(true)
     }
-    if !stitches.is_empty() {
-        stitch_groups.push(StitchGroup {
-            stitches,
-            trim: true,
-            cut: determine_cut(&last_irregulars),
-        });
-    }
-    if !stitch_groups.is_empty() {
-        color_groups.push(ColorGroup {
-            stitch_groups,
-            thread: None,
-        });
-    }
-    Ok(color_groups)
-}
 
-fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ParseResult<StitchInformation> {
-    let header_bytes = Vec::from_iter(in_bytes.take(3));
-    let items = header_bytes.as_slice();
-    if items.len() < 3 {
-        ParseResult::Exhausted
-    } else {
-        ParseResult::Some(StitchInformation::from_bytes([items[0], items[1], items[2]]))
+    fn end_group(&mut self) {
+        if !self.stitches.is_empty() {
+            let stitches = std::mem::take(&mut self.stitches);
+            self.stitch_groups.push(StitchGroup::new(stitches));
+        }
+        // A trim or cut that follows the end of a group still belongs to that group.
+        if let Some(sg) = self.stitch_groups.last_mut() {
+            sg.trim |= self.trim;
+            sg.cut |= self.cut;
+        }
+        self.trim = false;
+        self.cut = false;
     }
-}
 
-fn determine_cut(stitches: &[(i32, i32, StitchType)]) -> bool {
-    debug!("determine_cut {} {:?}", stitches.len(), stitches);
-    for i in 0..(stitches.len()) {
-        debug!("Checking for moves {}: {:?}", i, stitches.get(i));
-        let mut st = stitches.iter();
-        let (fx, fy, _) = if let Some(x) = st.nth(i) {
-            x
-        } else {
-            return false;
-        };
-        for &(cx, cy, _) in st {
-            if (fx - 1 <= cx && cx <= fx + 1) && (fy - 1 <= cy && cy <= fy + 1) {
-                return true;
-            }
+    fn end_color(&mut self) {
+        self.end_group();
+        if self.color_started || !self.stitch_groups.is_empty() {
+            let stitch_groups = std::mem::take(&mut self.stitch_groups);
+            self.color_groups.push(stitch_groups);
         }
     }
-    false
+
+    fn finish(mut self) -> Vec<Vec<StitchGroup>> {
+        self.end_color();
+        self.color_groups
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    macro_rules! to_u8_iter {
-        ($t:expr) => {
-            &mut $t.iter().map(|&x| x)
-        };
-    }
-
     #[test]
-    fn test_read_header_name() {
-        // Less than 3 bytes means the iterator has been exhausted.
-        assert_eq!(read_header_name(to_u8_iter!(b"ab")), ParseResult::Exhausted);
-
-        let iter = to_u8_iter!(b"ab:d");
-        assert_eq!(read_header_name(iter), ParseResult::Some([b'a', b'b']));
-        // ':' is read by the read_header_name function
-        assert_eq!(iter.next(), Some(b'd'));
-
-        let iter = to_u8_iter!(b"ab*d");
-        assert_eq!(read_header_name(iter), ParseResult::Some([b'a', b'b']));
-        // '*' is read by the read_header_name function
-        assert_eq!(iter.next(), Some(b'd'));
-
-        let iter = to_u8_iter!(b"abc\rd");
-        // "abc" is an invalid header as it doesn't end with a ':'
-        assert_eq!(read_header_name(iter), ParseResult::Skip);
+    fn test_split_fields() {
+        assert_eq!(split_fields("").unwrap(), Vec::<String>::new());
+        assert_eq!(
+            split_fields("\"*\",\"JUMP\",\"1\",\"-2.5\"").unwrap(),
+            vec!["*", "JUMP", "1", "-2.5"]
+        );
+        assert_eq!(split_fields("*, STITCH ,1,").unwrap(), vec!["*", "STITCH", "1", ""]);
+        assert_eq!(
+            split_fields("\"$\",\"1\",\"Say \"\"Hi\"\", Bob\"").unwrap(),
+            vec!["$", "1", "Say \"Hi\", Bob"]
+        );
+        assert!(split_fields("\"$\",\"1").is_err());
+        assert!(split_fields("\"$\"x").is_err());
     }
 
     #[test]
-    fn test_read_header_content() {
-        // An empty iterator gets exhausted
-        assert_eq!(read_header_content(to_u8_iter!(b"")), ParseResult::Exhausted);
-        // A `CR` means the end of a value, so it should be an empty value
-        assert_eq!(read_header_content(to_u8_iter!(b"\r")), ParseResult::Some(vec![]));
+    fn test_read_pattern() {
+        let data = "\
+\"#\",\"Embroidermodder 2 CSV Embroidery File\"
+\">\",\"STITCH_COUNT:\",\"6\"
+\">\",\"Title:\",\"Test\"
+\">\",\"Author:\",\"Someone\"
+\">\",\"Hoop:\",\"50x50\"
+
+\"$\",\"1\",\"255\",\"0\",\"0\",\"Red\",\"R1\"
+\"$\",\"2\",\"\",\"\",\"\",\"Unknown\",\"\"
 
+\"*\",\"JUMP\",\"0\",\"0\"
+\"*\",\"COLOR\",\"0\",\"0\"
+\"*\",\"STITCH\",\"1\",\"0\"
+\"*\",\"TRIM\",\"1\",\"0\"
+\"*\",\"JUMP\",\"5\",\"5\"
+\"*\",\"STITCH\",\"5\",\"6\"
+\"*\",\"JUMP\",\"7\",\"7\"
+\"*\",\"COLOR\",\"7\",\"7\"
+\"*\",\"STITCH\",\"8\",\"7.5\"
+\"*\",\"END\",\"8\",\"7.5\"
+\"*\",\"STITCH\",\"9\",\"9\"
+";
+        let pattern = CsvPatternReader::default().read_pattern(&mut data.as_bytes()).unwrap();
+        assert_eq!(pattern.name, "Test");
         assert_eq!(
-            read_header_content(to_u8_iter!(b"ab")),
-            ParseResult::Some(vec![b'a', b'b'])
+            pattern.attributes,
+            vec![
+                PatternAttribute::Title("Test".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Arbitary("Hoop".to_string(), "50x50".to_string()),
+            ]
+        );
+        assert_eq!(
+            pattern.color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"R1")),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(1., 0.)]).with_trim(true),
+                        StitchGroup::new(vec![Stitch::new(5., 5.), Stitch::new(5., 6.)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(7., 7.), Stitch::new(8., 7.5)])],
+                },
+            ]
         );
-
-        let iter = to_u8_iter!(b"abc\rd");
-        assert_eq!(read_header_content(iter), ParseResult::Some(vec![b'a', b'b', b'c']));
-        assert_eq!(iter.next(), Some(b'd'));
     }
 
-    // TODO: Find more complex examples, possibly including: thread colours, copyright, author strings.
     #[test]
-    fn test_read_dst_header() {
-        // Taken from `tests/dst/crown.dst`
-
-        let result = read_dst_header(to_u8_iter!(BASIC_HEADER_SAMPLE)).unwrap();
-        let mut iter = result.iter();
-        assert_eq!(iter.next(), Some(&PatternAttribute::Title("crown FS 40".to_string())),);
-        // ST, CO, +X, -X, +Y, -Y, AX, AY, MX, MY are skipped intentionally.
-        // PD is skipped intentionally.
-        assert_eq!(iter.next(), None);
+    fn test_read_pattern_invalid() {
+        let reader = CsvPatternReader::default();
+        assert!(reader.read_pattern(&mut &b"\"*\",\"SEW\",\"1\",\"1\""[..]).is_err());
+        assert!(reader.read_pattern(&mut &b"\"*\",\"STITCH\",\"a\",\"1\""[..]).is_err());
+        assert!(reader
+            .read_pattern(&mut &b"\"$\",\"1\",\"256\",\"0\",\"0\",\"\",\"\""[..])
+            .is_err());
     }
 
-    const BASIC_HEADER_SAMPLE: &[u8] = b"\
-LA:crown FS 40     \rST:   4562\rCO:  7\r+X:  362\r\
--X:  357\r+Y:  240\r-Y:  267\rAX:+   15\rAY:-   24\r\
-MX:+    0\rMY:+    0\rPD:******\r\x1a                ";
+    #[test]
+    fn test_is_loadable() {
+        let reader = CsvPatternReader::default();
+        assert!(reader.is_loadable(&mut &b"\n\"#\",\"Embroidermodder\"\n"[..]).unwrap());
+        assert!(reader.is_loadable(&mut &b"*,STITCH,1,1\n"[..]).unwrap());
+        assert!(!reader.is_loadable(&mut &b"LA:crown FS 40     \r"[..]).unwrap());
+        assert!(!reader.is_loadable(&mut &b""[..]).unwrap());
+    }
 }
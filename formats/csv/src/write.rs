@@ -1,16 +1,13 @@
+use std::fmt::Display;
 use std::io::Write;
 
 use embroidery_lib::format::PatternWriter;
 use embroidery_lib::prelude::*;
-use embroidery_lib::utils::{build_stitch_list, StitchInfo};
 
-pub struct CsvPatternWriter {}
+use crate::read::{CALCULATED_VARS, NAME_VAR, RESERVED_VARS, VAR_ESCAPE};
 
-impl Default for CsvPatternWriter {
-    fn default() -> Self {
-        CsvPatternWriter {}
-    }
-}
+#[derive(Default)]
+pub struct CsvPatternWriter {}
 
 impl PatternWriter for CsvPatternWriter {
     fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
@@ -22,20 +19,31 @@ impl PatternWriter for CsvPatternWriter {
     }
 }
 
+/// Write a row of fields; quoting every field and doubling up any quotes inside of them.
+fn write_row(writer: &mut dyn Write, fields: &[&dyn Display]) -> Result<(), WriteError> {
+    let row = fields
+        .iter()
+        .map(|field| format!("\"{}\"", field.to_string().replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(",");
+    writeln!(writer, "{}", row)?;
+    Ok(())
+}
+
 macro_rules! write_csv_var {
     ($writer: expr, $key: expr, $value: expr) => {
-        writeln!($writer, "\">\",\"{}:\",\"{}\"", $key, $value)
+        write_row($writer, &[&">", &format!("{}:", $key), &$value])
     };
 }
 
 macro_rules! write_csv_comment {
-    ($writer: expr, $value: expr) => {
-        writeln!($writer, "\"#\",\"{}\"", $value)
+    ($writer: expr, $($value: expr),+) => {
+        write_row($writer, &[&"#", $(&$value),+])
     };
 }
 macro_rules! write_csv_stitch {
     ($writer: expr, $type: expr, $stitch: expr) => {
-        writeln!($writer, "\"*\",\"{}\",\"{}\",\"{}\"", $type, $stitch.x, $stitch.y)
+        write_row($writer, &[&"*", &$type, &$stitch.x, &$stitch.y])
     };
 }
 
@@ -55,7 +63,7 @@ fn write_vars(pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteErro
     let stitch_count = pattern.iter_stitches().count();
     let thread_count = pattern.color_groups.len();
 
-    write_csv_comment!(writer, "[VAR_NAME]\",\"[VAR_VALUE]")?;
+    write_csv_comment!(writer, "[VAR_NAME]", "[VAR_VALUE]")?;
     write_csv_var!(writer, "STITCH_COUNT", stitch_count)?;
     write_csv_var!(writer, "THREAD_COUNT", thread_count)?;
     write_csv_var!(writer, "EXTENTS_LEFT", min_x)?;
@@ -65,10 +73,11 @@ fn write_vars(pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteErro
     write_csv_var!(writer, "EXTENTS_WIDTH", max_x - min_x)?;
     write_csv_var!(writer, "EXTENTS_HEIGHT", max_y - min_y)?;
     writeln!(writer)?;
+    write_csv_var!(writer, NAME_VAR, pattern.name)?;
     for attr in pattern.attributes.iter() {
         match attr {
             PatternAttribute::Arbitary(key, value) => {
-                write_csv_var!(writer, key, value)?;
+                write_csv_var!(writer, escape_var(key), value)?;
             },
             PatternAttribute::Title(value) => {
                 write_csv_var!(writer, "Title", value)?;
@@ -85,49 +94,114 @@ fn write_vars(pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteErro
     Ok(())
 }
 
+/// Keep arbitrary attributes from being read back as one of the variables that mean something else.
+fn escape_var(key: &str) -> String {
+    if key.starts_with(VAR_ESCAPE) || CALCULATED_VARS.contains(&key) || RESERVED_VARS.contains(&key) {
+        format!("{}{}", VAR_ESCAPE, key)
+    } else {
+        key.to_string()
+    }
+}
+
 fn write_threads(pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
     write_csv_comment!(
         writer,
-        "[THREAD_NUMBER]\",\"[RED]\",\"[GREEN]\",\"[BLUE]\",\"[DESCRIPTION]\",\"[CATALOG_NUMBER]"
+        "[THREAD_NUMBER]",
+        "[RED]",
+        "[GREEN]",
+        "[BLUE]",
+        "[DESCRIPTION]",
+        "[CATALOG_NUMBER]",
+        "[MANUFACTURER]",
+        "[ATTRIBUTE_NAME]",
+        "[ATTRIBUTE_VALUE]"
     )?;
 
     for (i, cg) in pattern.color_groups.iter().enumerate() {
+        let number = i + 1;
         match &cg.thread {
-            Some(thread) => writeln!(
-                writer,
-                "\"$\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\",\"{}\"",
-                i + 1,
-                thread.color.red,
-                thread.color.green,
-                thread.color.blue,
-                thread.name,
-                thread.code
-            )?,
-            None => writeln!(writer, "\"$\",\"{}\",\"\",\"\",\"\",\"Unknown\",\"\"", i + 1)?,
+            Some(thread) => {
+                let manufacturer = thread.manufacturer.as_deref().unwrap_or("");
+                let mut fields: Vec<&dyn Display> = vec![
+                    &"$",
+                    &number,
+                    &thread.color.red,
+                    &thread.color.green,
+                    &thread.color.blue,
+                    &thread.name,
+                    &thread.code,
+                    &manufacturer,
+                ];
+                for (key, value) in &thread.attributes {
+                    fields.push(key);
+                    fields.push(value);
+                }
+                write_row(writer, &fields)?
+            },
+            None => write_row(writer, &[&"$", &number, &"", &"", &"", &"Unknown", &""])?,
         }
     }
     writeln!(writer)?;
     Ok(())
 }
 fn write_stitches(pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
-    write_csv_comment!(writer, "[STITCH_TYPE]\",\"[X]\",\"[Y]")?;
+    write_csv_comment!(writer, "[STITCH_TYPE]", "[X]", "[Y]")?;
 
-    for si in build_stitch_list(pattern) {
-        match si {
-            StitchInfo::Color(_, s) => {
-                write_csv_stitch!(writer, "JUMP", s)?;
-                write_csv_stitch!(writer, "COLOR", s)?;
-            },
-            StitchInfo::Cut(s) => write_csv_stitch!(writer, "TRIM", s)?,
-            StitchInfo::End(s) => write_csv_stitch!(writer, "END", s)?,
-            StitchInfo::Jump(s) => write_csv_stitch!(writer, "STITCH", s)?,
-            StitchInfo::Stitch(s) => write_csv_stitch!(writer, "STITCH", s)?,
+    let mut last_stitch = Stitch::default();
+    for cg in &pattern.color_groups {
+        let mut color_changed = false;
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            let first = match iter.next() {
+                Some(first) => first,
+                None => {
+                    if !color_changed {
+                        write_csv_stitch!(writer, "COLOR", last_stitch)?;
+                        color_changed = true;
+                    }
+                    write_csv_stitch!(writer, "EMPTY", last_stitch)?;
+                    write_group_end(writer, sg, last_stitch)?;
+                    continue;
+                },
+            };
+            write_csv_stitch!(writer, "JUMP", first)?;
+            if !color_changed {
+                write_csv_stitch!(writer, "COLOR", first)?;
+                color_changed = true;
+            }
+            // The reader starts the group where the needle jumped to; so a stitch on top of that is
+            // only needed when the group has just the one stitch, or the next stitch is on top of
+            // it too.
+            if sg.stitches.get(1).is_none_or(|second| second == first) {
+                write_csv_stitch!(writer, "STITCH", first)?;
+            }
+            last_stitch = *first;
+            for s in iter {
+                write_csv_stitch!(writer, "STITCH", s)?;
+                last_stitch = *s;
+            }
+            write_group_end(writer, sg, last_stitch)?;
+        }
+        if !color_changed {
+            // Keep color groups without any stitches so that the threads still line up.
+            write_csv_stitch!(writer, "COLOR", last_stitch)?;
         }
     }
+    write_csv_stitch!(writer, "END", last_stitch)?;
     writeln!(writer)?;
     Ok(())
 }
 
+fn write_group_end(writer: &mut dyn Write, sg: &StitchGroup, last_stitch: Stitch) -> Result<(), WriteError> {
+    if sg.trim {
+        write_csv_stitch!(writer, "TRIM", last_stitch)?;
+    }
+    if sg.cut {
+        write_csv_stitch!(writer, "CUT", last_stitch)?;
+    }
+    Ok(())
+}
+
 const HEADER: &str = "
 Embroidermodder 2 CSV Embroidery File
 http://embroidermodder.github.io
@@ -137,13 +211,17 @@ General Notes:
 This file can be read by Excel or LibreOffice as CSV (Comma Separated Value) or with a text editor.
 Lines beginning with # are comments.
 Lines beginning with > are variables: [VAR_NAME], [VAR_VALUE]
-Lines beginning with $ are threads: [THREAD_NUMBER], [RED], [GREEN], [BLUE], [DESCRIPTION], [CATALOG_NUMBER]
+  Variables that start with \\ are attributes whose name would otherwise clash with a variable.
+Lines beginning with $ are threads: [THREAD_NUMBER], [RED], [GREEN], [BLUE], [DESCRIPTION], [CATALOG_NUMBER], [MANUFACTURER]
+  followed by any number of [ATTRIBUTE_NAME], [ATTRIBUTE_VALUE] pairs.
 Lines beginning with * are stitch entries: [STITCH_TYPE], [X], [Y]
 
 Stitch Entry Notes:
 STITCH instructs the machine to move to the position [X][Y] and then make a stitch.
 JUMP instructs the machine to move to the position [X][Y] without making a stitch.
-TRIM instructs the machine to cut the thread before moving to the position [X][Y] without making a stitch.
+TRIM instructs the machine to trim the thread at the position [X][Y] without making a stitch.
+CUT instructs the machine to cut the thread at the position [X][Y] without making a stitch.
+EMPTY marks a stitch group with no stitches in it; any TRIM or CUT straight after it belongs to that group.
 COLOR instructs the machine to stop temporarily so that the user can change to a different color thread before resuming.
 END instructs the machine that the design is completed and there are no further instructions.
 UNKNOWN encompasses instructions that may not be supported currently.
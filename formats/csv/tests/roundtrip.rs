@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_csv::{CsvPatternReader, CsvPatternWriter};
+
+fn sample_pattern() -> Pattern {
+    let mut attributes = BTreeMap::new();
+    attributes.insert("weight".to_string(), "40".to_string());
+    attributes.insert("note, \"quoted\"".to_string(), "a,b".to_string());
+
+    Pattern {
+        name: "Round \"Trip\"".to_string(),
+        attributes: vec![
+            PatternAttribute::Title("Round \"Trip\"".to_string()),
+            PatternAttribute::Author("Someone, Somewhere".to_string()),
+            PatternAttribute::Copyright("(c) 2020".to_string()),
+            PatternAttribute::Arbitary("hoop".to_string(), "126x110".to_string()),
+        ],
+        color_groups: vec![
+            ColorGroup {
+                thread: Some(Thread {
+                    color: Color::rgb(255, 0, 17),
+                    name: "Red \"ish\", kinda".to_string(),
+                    code: "1234".to_string(),
+                    manufacturer: Some("Madeira".to_string()),
+                    attributes,
+                }),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(0.1 + 0.2, -1.0), Stitch::new(1.0, 1.0 / 3.0)]).with_trim(true),
+                    StitchGroup::new(vec![Stitch::new(5.0, 5.0), Stitch::new(6.0, 5.5)]).with_cut(true),
+                    StitchGroup::new(vec![Stitch::new(7.0, 5.0), Stitch::new(8.0, 5.5)])
+                        .with_trim(true)
+                        .with_cut(true),
+                    StitchGroup::new(vec![Stitch::new(9.0, 5.0), Stitch::new(10.0, 5.5)]),
+                ],
+            },
+            ColorGroup {
+                thread: None,
+                stitch_groups: vec![],
+            },
+            ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(0, 0, 0), &"Black", &"")),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![
+                        Stitch::new(-20.0, 3.0),
+                        Stitch::new(-21.0, 4.0),
+                        Stitch::new(-20.5, 4.0),
+                    ]),
+                    StitchGroup::new(vec![Stitch::new(-2.0, 3.0), Stitch::new(-2.0, 4.0)]).with_trim(true),
+                ],
+            },
+        ],
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let pattern = sample_pattern();
+    let mut data = Vec::new();
+    CsvPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+
+    let reader = CsvPatternReader::default();
+    assert!(reader.is_loadable(&mut &data[..]).unwrap());
+    let read = reader.read_pattern(&mut &data[..]).unwrap();
+    assert_eq!(read, pattern);
+}
+
+#[test]
+fn test_roundtrip_awkward_pattern() {
+    let pattern = Pattern {
+        name: "Design 7".to_string(),
+        attributes: vec![
+            PatternAttribute::Title("Sunflower".to_string()),
+            PatternAttribute::Arbitary("STITCH_COUNT".to_string(), "12".to_string()),
+            PatternAttribute::Arbitary("Title".to_string(), "Not the title".to_string()),
+            PatternAttribute::Arbitary("Name".to_string(), "Not the name".to_string()),
+            PatternAttribute::Arbitary("\\path".to_string(), "C:\\designs".to_string()),
+            PatternAttribute::Arbitary("ratio:".to_string(), "1:2".to_string()),
+        ],
+        color_groups: vec![
+            ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(255, 255, 0), &"Yellow", &"")),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(1.0, 1.0)]),
+                    StitchGroup::new(vec![Stitch::new(2.0, 2.0)]).with_trim(true),
+                    StitchGroup::new(vec![]).with_cut(true),
+                    StitchGroup::new(vec![
+                        Stitch::new(3.0, 3.0),
+                        Stitch::new(3.0, 3.0),
+                        Stitch::new(4.0, 3.0),
+                    ]),
+                    StitchGroup::new(vec![]),
+                ],
+            },
+            ColorGroup {
+                thread: None,
+                stitch_groups: vec![
+                    StitchGroup::new(vec![]).with_trim(true),
+                    StitchGroup::new(vec![Stitch::new(5.0, 5.0)])
+                        .with_trim(true)
+                        .with_cut(true),
+                ],
+            },
+        ],
+    };
+    let mut data = Vec::new();
+    CsvPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+
+    let read = CsvPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+    assert_eq!(read, pattern);
+}
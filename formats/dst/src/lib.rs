@@ -1,11 +1,11 @@
 mod read;
-mod stitch_info;
+pub mod stitch_info;
 mod write;
 
 use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
 
-pub use self::read::DstPatternReader;
-pub use self::write::DstPatternWriter;
+pub use self::read::{read_dst_header, DstHeader, DstPatternReader};
+pub use self::write::{write_dst_header, DstPatternWriter};
 
 const NAME: &str = "dst";
 const EXTENSIONS: [&str; 1] = ["dst"];
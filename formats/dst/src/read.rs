@@ -18,6 +18,20 @@ enum ParseResult<T> {
     Exhausted,
 }
 
+/// The parts of a DST header that aren't calculated from the stitches.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DstHeader {
+    pub attributes: Vec<PatternAttribute>,
+    /// The thread for each color, from the `TC` lines; which not all files have.
+    pub threads: Vec<Thread>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum HeaderItem {
+    Attribute(PatternAttribute),
+    Thread(Thread),
+}
+
 impl Default for DstPatternReader {
     fn default() -> Self {
         DstPatternReader {}
@@ -39,12 +53,16 @@ impl PatternReader for DstPatternReader {
     fn read_pattern(&self, file: &mut dyn Read) -> Result<Pattern, ReadError> {
         // Read the header
         let mut iter = ReadByteIterator::new(file);
-        let attributes = read_dst_header(&mut iter)?;
-        if attributes.is_empty() {
+        let header = read_dst_header(&mut iter)?;
+        if header.attributes.is_empty() {
             return Err(ReadError::invalid_format("File has no attributes."));
         }
-        let color_groups = read_stitches(&mut iter)?;
-        let (title, attributes) = extract_title(attributes);
+        let mut color_groups = read_stitches(&mut iter)?;
+        // Threads are listed in the order they're used.
+        for (cg, thread) in color_groups.iter_mut().zip(header.threads) {
+            cg.thread = Some(thread);
+        }
+        let (title, attributes) = extract_title(header.attributes);
         Ok(Pattern {
             name: title,
             attributes,
@@ -68,23 +86,25 @@ fn extract_title(attrs: Vec<PatternAttribute>) -> (String, Vec<PatternAttribute>
     (title, new_attrs)
 }
 
-fn read_dst_header(item: &mut dyn Iterator<Item = u8>) -> Result<Vec<PatternAttribute>, ReadError> {
-    let mut attrs: Vec<PatternAttribute> = Vec::new();
+/// Read the 512 byte header that DST shares with some of its relatives.
+pub fn read_dst_header(item: &mut dyn Iterator<Item = u8>) -> Result<DstHeader, ReadError> {
+    let mut header = DstHeader::default();
     let mut header_iter = item.take(512);
 
     loop {
         match read_header_item(&mut header_iter)? {
-            ParseResult::Some(header) => attrs.push(header),
+            ParseResult::Some(HeaderItem::Attribute(attr)) => header.attributes.push(attr),
+            ParseResult::Some(HeaderItem::Thread(thread)) => header.threads.push(thread),
             ParseResult::Skip => (),
             _ => break,
         }
     }
     // Drain the rest of the iterator.
     header_iter.last();
-    Ok(attrs)
+    Ok(header)
 }
 
-fn read_header_item(mut header_iter: &mut dyn Iterator<Item = u8>) -> Result<ParseResult<PatternAttribute>, ReadError> {
+fn read_header_item(mut header_iter: &mut dyn Iterator<Item = u8>) -> Result<ParseResult<HeaderItem>, ReadError> {
     let header = &match read_header_name(&mut header_iter) {
         ParseResult::Some(header) => header,
         ParseResult::Skip => return Ok(ParseResult::Skip),
@@ -100,34 +120,38 @@ fn read_header_item(mut header_iter: &mut dyn Iterator<Item = u8>) -> Result<Par
         String::from_utf8_lossy(header).to_string(),
         String::from_utf8_lossy(content).to_string()
     );
-    match header {
-        b"LA" => Ok(ParseResult::Some(PatternAttribute::Title(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
-        b"AU" => Ok(ParseResult::Some(PatternAttribute::Author(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
-        b"CP" => Ok(ParseResult::Some(PatternAttribute::Copyright(c_trim(
-            &String::from_utf8_lossy(content),
-        )))),
+    let attribute = match header {
+        b"LA" => PatternAttribute::Title(c_trim(&String::from_utf8_lossy(content))),
+        b"AU" => PatternAttribute::Author(c_trim(&String::from_utf8_lossy(content))),
+        b"CP" => PatternAttribute::Copyright(c_trim(&String::from_utf8_lossy(content))),
+        b"TC" => match parse_thread(&String::from_utf8_lossy(content)) {
+            Some(thread) => return Ok(ParseResult::Some(HeaderItem::Thread(thread))),
+            None => PatternAttribute::Arbitary("TC".to_string(), String::from_utf8_lossy(content).to_string()),
+        },
         // We can skip these because they're calculated from the stitches.
-        b"ST" => Ok(ParseResult::Skip),
-        b"CO" => Ok(ParseResult::Skip),
-        b"+X" => Ok(ParseResult::Skip),
-        b"+Y" => Ok(ParseResult::Skip),
-        b"-X" => Ok(ParseResult::Skip),
-        b"-Y" => Ok(ParseResult::Skip),
+        b"ST" | b"CO" | b"+X" | b"+Y" | b"-X" | b"-Y" | b"AX" | b"AY" => return Ok(ParseResult::Skip),
         // We can skip these because they're all related to multi-file patterns, which we don't support
-        b"AX" => Ok(ParseResult::Skip),
-        b"AY" => Ok(ParseResult::Skip),
-        b"MX" => Ok(ParseResult::Skip),
-        b"MY" => Ok(ParseResult::Skip),
-        b"PD" => Ok(ParseResult::Skip),
-        _ => Ok(ParseResult::Some(PatternAttribute::Arbitary(
+        b"MX" | b"MY" | b"PD" => return Ok(ParseResult::Skip),
+        _ => PatternAttribute::Arbitary(
             String::from_utf8_lossy(header).to_string(),
             String::from_utf8_lossy(content).to_string(),
-        ))),
+        ),
+    };
+    Ok(ParseResult::Some(HeaderItem::Attribute(attribute)))
+}
+
+/// Parse a thread color line; which looks like `#RRGGBB,Description,Catalog number`.
+fn parse_thread(content: &str) -> Option<Thread> {
+    let mut parts = content.splitn(3, ',');
+    let hex = parts.next()?.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
     }
+    let channel = |idx: usize| u8::from_str_radix(&hex[idx..idx + 2], 16).ok();
+    let color = Color::rgb(channel(0)?, channel(2)?, channel(4)?);
+    let name = c_trim(parts.next().unwrap_or(""));
+    let code = c_trim(parts.next().unwrap_or(""));
+    Some(Thread::new(color, name, code))
 }
 
 fn read_header_name(in_bytes: &mut dyn Iterator<Item = u8>) -> ParseResult<[u8; 2]> {
@@ -220,6 +244,8 @@ fn read_stitches(item: &mut dyn Iterator<Item = u8>) -> Result<Vec<ColorGroup>,
             ParseResult::Some(StitchInformation::End) => {
                 break;
             },
+            // Never read from a DST file.
+            ParseResult::Some(StitchInformation::Trim) | ParseResult::Some(StitchInformation::NeedleChange(_)) => {},
             ParseResult::Exhausted => {
                 break;
             },
@@ -324,13 +350,55 @@ mod tests {
         // Taken from `tests/dst/crown.dst`
 
         let result = read_dst_header(to_u8_iter!(BASIC_HEADER_SAMPLE)).unwrap();
-        let mut iter = result.iter();
+        assert_eq!(result.threads, vec![]);
+        let mut iter = result.attributes.iter();
         assert_eq!(iter.next(), Some(&PatternAttribute::Title("crown FS 40".to_string())),);
         // ST, CO, +X, -X, +Y, -Y, AX, AY, MX, MY are skipped intentionally.
         // PD is skipped intentionally.
         assert_eq!(iter.next(), None);
     }
 
+    #[test]
+    fn test_read_dst_header_threads() {
+        let result = read_dst_header(to_u8_iter!(THREAD_HEADER_SAMPLE)).unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                PatternAttribute::Title("threads".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Arbitary("TC".to_string(), "red,Red,".to_string()),
+            ]
+        );
+        assert_eq!(
+            result.threads,
+            vec![
+                Thread::new_str(Color::rgb(0xFF, 0x00, 0x00), &"Red", &"1147"),
+                Thread::new_str(Color::rgb(0x00, 0x43, 0xb1), &"Soldier Blue", &""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_thread() {
+        assert_eq!(
+            parse_thread("#00ff00,Green,2"),
+            Some(Thread::new_str(Color::rgb(0, 255, 0), &"Green", &"2"))
+        );
+        assert_eq!(
+            parse_thread("#00ff00"),
+            Some(Thread::new_str(Color::rgb(0, 255, 0), &"", &""))
+        );
+        assert_eq!(parse_thread("00ff00,Green,2"), None);
+        assert_eq!(parse_thread("#00ff0,Green,2"), None);
+        assert_eq!(parse_thread("#00fg00,Green,2"), None);
+    }
+
+    const THREAD_HEADER_SAMPLE: &[u8] = b"\
+LA:threads         \rST:     10\rCO:  1\r+X:   10\r\
+-X:   10\r+Y:   10\r-Y:   10\rAX:+    0\rAY:+    0\r\
+MX:+    0\rMY:+    0\rPD:******\rAU:Someone\rTC:#FF0000,Red,1147\r\
+TC:#0043b1,Soldier Blue,\rTC:red,Red,\r\x1a                ";
+
     const BASIC_HEADER_SAMPLE: &[u8] = b"\
 LA:crown FS 40     \rST:   4562\rCO:  7\r+X:  362\r\
 -X:  357\r+Y:  240\r-Y:  267\rAX:+   15\rAY:-   24\r\
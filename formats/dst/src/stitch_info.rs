@@ -31,7 +31,7 @@ macro_rules! expand_to_condition {
 macro_rules! stitch_definitions {
     ($($bits:tt -> ($var: ident $sign:tt $val:tt)),+) => {
         #[inline]
-        fn from_int(val: u32) -> (i8, i8) {
+        pub fn from_int(val: u32) -> (i8, i8) {
             let mut x: i8 = 0;
             let mut y: i8 = 0;
             $(
@@ -43,7 +43,7 @@ macro_rules! stitch_definitions {
         }
 
         #[inline]
-        fn to_int(_x: i8, _y: i8) -> Option<u32> {
+        pub fn to_int(_x: i8, _y: i8) -> Option<u32> {
             if _x < -121 || _x > 121 || _y < -121 || _y > 121 {
                 return None;
             }
@@ -149,6 +149,11 @@ impl StitchType {
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum StitchInformation {
     Move(i8, i8, StitchType),
+    /// Cut the threads without moving. DST has no command for this, but some of its relatives do.
+    Trim,
+    /// Change to the given needle(counting from 1) without moving. DST can only stop the machine
+    /// and leave the operator to change the thread.
+    NeedleChange(u8),
     End,
 }
 
@@ -180,6 +185,7 @@ impl StitchInformation {
                     option_bits | (val & 0xFF) as u8,
                 ])
             },
+            StitchInformation::Trim | StitchInformation::NeedleChange(_) => None,
             StitchInformation::End => Some([0x00, 0x00, 0xF3]),
         }
     }
@@ -267,4 +273,15 @@ mod tests {
             StitchInformation::End,
         );
     }
+
+    #[test]
+    fn test_stitch_information_to_bytes() {
+        assert_eq!(
+            StitchInformation::Move(0, 0, StitchType::Jump).to_bytes(),
+            Some([0x00, 0x00, 0x83])
+        );
+        assert_eq!(StitchInformation::End.to_bytes(), Some([0x00, 0x00, 0xF3]));
+        assert_eq!(StitchInformation::Trim.to_bytes(), None);
+        assert_eq!(StitchInformation::NeedleChange(2).to_bytes(), None);
+    }
 }
@@ -1,12 +1,14 @@
-use std::io::Write;
+use std::io::{self, Write};
 
 use embroidery_lib::format::PatternWriter;
 use embroidery_lib::prelude::*;
-use embroidery_lib::utils::{c_trim, char_truncate};
+use embroidery_lib::utils::{c_trim, char_truncate, pattern_threads, split_jump};
 
 use crate::stitch_info::{StitchInformation, StitchType};
 
 const MAX_JUMP: i32 = 121;
+/// The largest offset, in 0.1mm, that fits in the `AX`, `AY`, `MX` and `MY` header fields.
+const MAX_HEADER_OFFSET: u32 = 99_999;
 
 pub struct DstPatternWriter {}
 
@@ -19,20 +21,23 @@ impl Default for DstPatternWriter {
 impl PatternWriter for DstPatternWriter {
     fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
         let stitches = into_dst_stitches(pattern)?;
-        write_header(pattern, &stitches, writer)?;
+        write_dst_header(pattern, stitches.len(), needle_end(&stitches), writer)?;
         write_stitches(&stitches, writer)?;
 
         Ok(())
     }
 }
 
-fn write_header(
+/// Write the 512 byte header that DST shares with some of its relatives. `end` is where the needle
+/// finishes, in 0.1mm from where it started.
+pub fn write_dst_header(
     pattern: &Pattern,
-    dst_stitches: &[StitchInformation],
+    stitch_count: usize,
+    end: (i32, i32),
     writer: &mut dyn Write,
 ) -> Result<(), WriteError> {
     let mut header: Vec<u8> = Vec::with_capacity(512);
-    header.extend(build_header(pattern, dst_stitches)?);
+    header.extend(build_header(pattern, stitch_count, end)?);
     let rem_space = 512 - header.len();
     header.extend(build_extended_header(pattern, rem_space)?);
     assert!(header.len() <= 512);
@@ -46,7 +51,7 @@ fn write_stitches(dst_stitches: &[StitchInformation], writer: &mut dyn Write) ->
     assert_eq!(Some(&StitchInformation::End), dst_stitches.last());
     assert_eq!(1, dst_stitches.iter().filter(|&&s| s == StitchInformation::End).count());
     for &st in dst_stitches {
-        // Use unwrap because any stitch that's invalid here is definately a program error.
+        // Use unwrap because any stitch that's invalid here is definitely a program error.
         writer.write_all(&st.to_bytes().unwrap())?;
         if StitchInformation::End == st {
             break;
@@ -55,10 +60,9 @@ fn write_stitches(dst_stitches: &[StitchInformation], writer: &mut dyn Write) ->
     Ok(())
 }
 
-fn build_header(pattern: &Pattern, dst_stitches: &[StitchInformation]) -> Result<Vec<u8>, WriteError> {
+fn build_header(pattern: &Pattern, stitch_count: usize, (end_x, end_y): (i32, i32)) -> Result<Vec<u8>, WriteError> {
     let mut data: Vec<u8> = Vec::with_capacity(128);
     let color_count = pattern.color_groups.len();
-    let stitch_count = dst_stitches.len();
     let (minx, miny, maxx, maxy) = pattern.get_bounds();
 
     let title = pattern.name.to_string();
@@ -66,26 +70,41 @@ fn build_header(pattern: &Pattern, dst_stitches: &[StitchInformation]) -> Result
     write!(data, "LA:{: <17}\r", char_truncate(&c_trim(&title), 17))?;
     write!(data, "ST:{: >7}\r", stitch_count)?;
     // `CO` represents the number of color changes.
-    write!(data, "CO:{: >3}\r", color_count - 1)?;
+    write!(data, "CO:{: >3}\r", color_count.saturating_sub(1))?;
     write!(data, "+X:{: <5}\r", (10. * maxx) as i64)?;
     write!(data, "-X:{: <5}\r", (10. * minx) as i64)?;
     write!(data, "+Y:{: <5}\r", (10. * maxy) as i64)?;
     write!(data, "-Y:{: <5}\r", (10. * miny) as i64)?;
 
-    // Required fields; but not actually needing to be calculated
-    write!(data, "AX:{: <+6}\r", 0)?;
-    write!(data, "AY:{: <+6}\r", 0)?;
-    write!(data, "MX:{: <+6}\r", 0)?;
-    write!(data, "MY:{: <+6}\r", 0)?;
+    write!(data, "AX:{}\r", header_offset(end_x))?;
+    write!(data, "AY:{}\r", header_offset(end_y))?;
+    // Required fields; but only used for multi-file patterns.
+    write!(data, "MX:{}\r", header_offset(0))?;
+    write!(data, "MY:{}\r", header_offset(0))?;
     write!(data, "PD:{: <6}\r\0\0\0", ['*'; 6].iter().collect::<String>())?;
 
     debug!("{:?}", String::from_utf8_lossy(&data));
     debug!("{:?}", data.len());
 
-    assert!(data.len() == 128);
+    // The extents and stitch count widen their fields when they have too many digits.
+    if data.len() != 128 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The pattern is too large for the DST header",
+        )
+        .into());
+    }
     Ok(data)
 }
 
+/// Formats an offset in 0.1mm as the sign followed by the right aligned value; like `+   15`.
+///
+/// Offsets beyond the five digits of the field are clamped to it.
+fn header_offset(value: i32) -> String {
+    let sign = if value < 0 { '-' } else { '+' };
+    format!("{}{: >5}", sign, value.unsigned_abs().min(MAX_HEADER_OFFSET))
+}
+
 fn build_extended_header(pattern: &Pattern, rem: usize) -> Result<Vec<u8>, WriteError> {
     let mut data: Vec<u8> = Vec::with_capacity(128);
     let author = pattern
@@ -111,10 +130,31 @@ fn build_extended_header(pattern: &Pattern, rem: usize) -> Result<Vec<u8>, Write
     if let Some(c) = copyright {
         write!(data, "CP:{: <17}\r", char_truncate(&c_trim(&c), 17))?;
     }
+    for line in thread_lines(pattern) {
+        if data.len() + line.len() > rem {
+            debug!("Not enough space in the header for all the threads");
+            break;
+        }
+        data.extend(line.into_bytes());
+    }
     assert!(data.len() <= rem);
     Ok(data)
 }
 
+/// A `TC` line for each color; making up colors for the groups without a thread. Nothing is
+/// written if none of the groups have a thread, as the lines are matched to colors by position.
+fn thread_lines(pattern: &Pattern) -> Vec<String> {
+    if pattern.color_groups.iter().all(|cg| cg.thread.is_none()) {
+        return vec![];
+    }
+    // The separators can't be escaped.
+    let clean = |s: &str| char_truncate(&c_trim(s), 17).replace([',', '\r'], " ");
+    pattern_threads(pattern)
+        .iter()
+        .map(|thread| format!("TC:{},{},{}\r", thread.color, clean(&thread.name), clean(&thread.code)))
+        .collect()
+}
+
 fn into_dst_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
     let mut re = vec![];
     let mut inter_group_jumps = vec![];
@@ -144,13 +184,6 @@ fn into_dst_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteE
             for s in iter {
                 let dx = ((s.x * 10.).trunc() as i32) - ox;
                 let dy = ((s.y * 10.).trunc() as i32) - oy;
-                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
-                    return Err(WriteError::unsupported_stitch_msg(
-                        *s,
-                        Some(idx),
-                        "Stitch jump is too big for the format",
-                    ));
-                }
                 if idx < 10 {
                     debug!(
                         "Start: ({}, {}); Stitch: {:?}; Move: ({}, {}); Dest: ({}, {});",
@@ -159,7 +192,14 @@ fn into_dst_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteE
                 }
                 ox += dx;
                 oy += dy;
-                re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                    // Too long for one stitch; so it's sewn as several in a straight line.
+                    for (dx, dy) in split_jump(dx, dy, MAX_JUMP) {
+                        re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                    }
+                } else {
+                    re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                }
                 idx += 1;
             }
             if sg.cut {
@@ -180,57 +220,24 @@ fn into_dst_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteE
     Ok(re)
 }
 
+/// Where the needle finishes, in 0.1mm from where it started.
+fn needle_end(dst_stitches: &[StitchInformation]) -> (i32, i32) {
+    dst_stitches.iter().fold((0, 0), |(x, y), st| match *st {
+        StitchInformation::Move(dx, dy, _) => (x + i32::from(dx), y + i32::from(dy)),
+        _ => (x, y),
+    })
+}
+
 fn safe_jump_to(ox: i32, oy: i32, s: &Stitch) -> Vec<StitchInformation> {
     let delta_x = ((s.x * 10.) as i32) - ox;
     let delta_y = ((s.y * 10.) as i32) - oy;
 
     debug!("Target: ({}, {});", delta_x, delta_y);
 
-    if delta_x == 0 && delta_y == 0 {
-        Vec::new()
-    } else if delta_x.abs() <= MAX_JUMP && delta_y.abs() <= MAX_JUMP {
-        vec![StitchInformation::Move(delta_x as i8, delta_y as i8, StitchType::Jump)]
-    } else {
-        let abs_x = delta_x.abs();
-        let abs_y = delta_y.abs();
-        let sign_x = delta_x.signum() as i8;
-        let sign_y = delta_y.signum() as i8;
-        let chunks = f64::max(
-            (f64::from(abs_x) / f64::from(MAX_JUMP)).ceil(),
-            (f64::from(abs_y) / f64::from(MAX_JUMP)).ceil(),
-        ) as i32;
-        let step_x = (f64::from(abs_x) / f64::from(chunks)).ceil() as i32;
-        let step_y = (f64::from(abs_y) / f64::from(chunks)).ceil() as i32;
-        assert!(step_x <= MAX_JUMP);
-        assert!(step_y <= MAX_JUMP);
-        let mut cx = 0;
-        let mut cy = 0;
-        let mut re = Vec::with_capacity(chunks as usize);
-        debug!(
-            "Abs: ({}, {}); Signs: ({}, {}); Chunks: {}; Jump: ({}, {})",
-            abs_x, abs_y, sign_x, sign_y, chunks, step_x, step_y
-        );
-        for i in 0..=chunks {
-            let (nx, ny) = (i32::min(abs_x, i * step_x), i32::min(abs_y, i * step_y));
-            re.push(StitchInformation::Move(
-                sign_x * (nx - cx) as i8,
-                sign_y * (ny - cy) as i8,
-                StitchType::Jump,
-            ));
-            debug!(
-                "C: ({}, {}); N: ({}, {}), move: ({}, {})",
-                cx,
-                cy,
-                nx,
-                ny,
-                sign_x * (cx - nx) as i8,
-                sign_y * (cy - ny) as i8
-            );
-            cx = nx;
-            cy = ny;
-        }
-        re
-    }
+    split_jump(delta_x, delta_y, MAX_JUMP)
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation::Move(dx as i8, dy as i8, StitchType::Jump))
+        .collect()
 }
 
 fn generate_cut() -> Vec<StitchInformation> {
@@ -242,3 +249,161 @@ fn generate_cut() -> Vec<StitchInformation> {
         StitchInformation::Move(0, 0, StitchType::Jump),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::DstPatternReader;
+    use embroidery_lib::format::PatternReader;
+    use embroidery_lib::utils::generate_color;
+
+    fn pattern() -> Pattern {
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red, Bright", &"1147")),
+                    stitch_groups: vec![StitchGroup::new(vec![
+                        Stitch::new(0.0, 0.0),
+                        Stitch::new(1.0, 0.0),
+                        Stitch::new(1.0, 1.0),
+                    ])
+                    .with_trim(true)],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(-6.0, 4.0), Stitch::new(-5.0, -2.5)]).with_trim(true)
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_build_header() {
+        let header = build_header(&pattern(), 10, (-50, -25)).unwrap();
+        let header = String::from_utf8(header).unwrap();
+        assert!(header.contains("\rAX:-   50\rAY:-   25\rMX:+    0\rMY:+    0\r"));
+    }
+
+    #[test]
+    fn test_header_offset() {
+        assert_eq!(header_offset(0), "+    0");
+        assert_eq!(header_offset(-99_999), "-99999");
+        assert_eq!(header_offset(123_456), "+99999");
+        assert_eq!(header_offset(i32::MIN), "-99999");
+    }
+
+    #[test]
+    fn test_build_header_too_large() {
+        let mut pattern = pattern();
+        pattern.color_groups[1].stitch_groups[0]
+            .stitches
+            .push(Stitch::new(12_000.0, -10_000.0));
+        assert!(build_header(&pattern, 10, (0, 0)).is_err());
+        assert!(DstPatternWriter::default()
+            .write_pattern(&pattern, &mut vec![])
+            .is_err());
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(0.0, -12.5)])],
+            }],
+        };
+        let stitches = into_dst_stitches(&pattern).unwrap();
+        assert_eq!(
+            &stitches[..2],
+            &[
+                StitchInformation::Move(0, -62, StitchType::Regular),
+                StitchInformation::Move(0, -63, StitchType::Regular),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_into_dst_stitches() {
+        let stitches = into_dst_stitches(&pattern()).unwrap();
+        // The needle goes back to where it started; and stops there.
+        assert_eq!(
+            &stitches[stitches.len() - 3..],
+            &[
+                StitchInformation::Move(10, -65, StitchType::Regular),
+                StitchInformation::Move(50, 25, StitchType::JumpStop),
+                StitchInformation::End,
+            ]
+        );
+        assert_eq!(needle_end(&stitches), (0, 0));
+
+        let mut data = vec![];
+        DstPatternWriter::default()
+            .write_pattern(&pattern(), &mut data)
+            .unwrap();
+        let header = String::from_utf8_lossy(&data[..128]);
+        assert!(header.contains("\rAX:+    0\rAY:+    0\r"));
+    }
+
+    #[test]
+    fn test_into_dst_stitches_ends_at_origin() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(1.0, 0.0), Stitch::new(0.0, 0.0)])],
+            }],
+        };
+        let stitches = into_dst_stitches(&pattern).unwrap();
+        assert_eq!(
+            &stitches[stitches.len() - 3..],
+            &[
+                StitchInformation::Move(-10, 0, StitchType::Regular),
+                StitchInformation::Move(0, 0, StitchType::Stop),
+                StitchInformation::End,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thread_lines() {
+        let mut pattern = pattern();
+        assert_eq!(
+            thread_lines(&pattern),
+            vec![
+                "TC:#FF0000,Red  Bright,1147\r".to_string(),
+                format!("TC:{},{},\r", generate_color(0, 1), generate_color(0, 1)),
+            ]
+        );
+        pattern.color_groups[0].thread = None;
+        assert_eq!(thread_lines(&pattern), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let pattern = pattern();
+        let mut data = vec![];
+        DstPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+
+        let read = DstPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(read.color_groups.len(), 2);
+        assert_eq!(
+            read.color_groups[0].thread,
+            Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red  Bright", &"1147"))
+        );
+        assert_eq!(
+            read.color_groups[1].thread.as_ref().map(|t| t.color),
+            Some(generate_color(0, 1))
+        );
+        assert_eq!(
+            read.color_groups[1].stitch_groups,
+            pattern.color_groups[1].stitch_groups
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+pub const MAGIC: [u8; 4] = *b"EMBR";
+pub const VERSION: u8 = 1;
+/// The magic bytes, the version and the flags.
+pub const HEADER_LEN: usize = 6;
+pub const CHECKSUM_LEN: usize = 4;
+
+// The file holds a `PatternCollection` rather than a single `Pattern`.
+const FLAG_COLLECTION: u8 = 0x01;
+// The file ends with a CRC32 of everything before it.
+const FLAG_CHECKSUM: u8 = 0x02;
+// Coordinates are stored as whole thousandths of a millimetre; rather than the bits of an `f64`.
+const FLAG_FIXED_POINT: u8 = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Header {
+    pub collection: bool,
+    pub checksum: bool,
+    pub fixed_point: bool,
+}
+
+impl Header {
+    pub fn read(data: &[u8]) -> ReadResult<Self> {
+        if data.len() < HEADER_LEN || data[..4] != MAGIC {
+            return Err(ReadError::invalid_format("Magic bytes do not match"));
+        }
+        if data[4] != VERSION {
+            return Err(ReadError::invalid_format(format!(
+                "Unsupported version {}; expected {}",
+                data[4], VERSION
+            )));
+        }
+        let flags = data[5];
+        if flags & !(FLAG_COLLECTION | FLAG_CHECKSUM | FLAG_FIXED_POINT) != 0 {
+            return Err(ReadError::invalid_format(format!("Unknown flags 0x{:02X}", flags)));
+        }
+        Ok(Header {
+            collection: flags & FLAG_COLLECTION != 0,
+            checksum: flags & FLAG_CHECKSUM != 0,
+            fixed_point: flags & FLAG_FIXED_POINT != 0,
+        })
+    }
+
+    pub fn write(self, data: &mut Vec<u8>) {
+        let mut flags = 0;
+        if self.collection {
+            flags |= FLAG_COLLECTION;
+        }
+        if self.checksum {
+            flags |= FLAG_CHECKSUM;
+        }
+        if self.fixed_point {
+            flags |= FLAG_FIXED_POINT;
+        }
+        data.extend_from_slice(&MAGIC);
+        data.extend_from_slice(&[VERSION, flags]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = Header {
+            collection: false,
+            checksum: true,
+            fixed_point: true,
+        };
+        let mut data = vec![];
+        header.write(&mut data);
+        assert_eq!(data, b"EMBR\x01\x06");
+        assert_eq!(Header::read(&data).unwrap(), header);
+
+        assert!(Header::read(b"EMBR\x02\x00").is_err());
+        assert!(Header::read(b"EMBR\x01\x08").is_err());
+        assert!(Header::read(b"EMB").is_err());
+    }
+}
@@ -0,0 +1,51 @@
+mod header;
+mod read;
+mod strings;
+mod varint;
+mod write;
+
+use embroidery_lib::format::{
+    CollectionFormat, CollectionReader, CollectionWriter, PatternFormat, PatternReader, PatternWriter,
+};
+
+pub use self::read::{EmbrCollectionReader, EmbrPatternReader};
+pub use self::write::{EmbrCollectionWriter, EmbrPatternWriter};
+
+const NAME: &str = "embr";
+const EXTENSIONS: [&str; 1] = ["embr"];
+
+#[derive(Default)]
+pub struct EmbrPatternFormat {}
+
+impl PatternFormat for EmbrPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(EmbrPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(EmbrPatternWriter::default()))
+    }
+}
+
+#[derive(Default)]
+pub struct EmbrCollectionFormat {}
+
+impl CollectionFormat for EmbrCollectionFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn CollectionReader>> {
+        Some(Box::from(EmbrCollectionReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn CollectionWriter>> {
+        Some(Box::from(EmbrCollectionWriter::default()))
+    }
+}
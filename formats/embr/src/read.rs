@@ -0,0 +1,261 @@
+use std::io::Read;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::{CollectionReader, PatternReader};
+use embroidery_lib::prelude::*;
+
+use crate::header::{Header, CHECKSUM_LEN, HEADER_LEN};
+use crate::strings::StringTable;
+use crate::varint::{read_varint, unzigzag};
+use crate::write::{
+    ATTRIBUTE_ARBITARY, ATTRIBUTE_AUTHOR, ATTRIBUTE_COPYRIGHT, ATTRIBUTE_TITLE, FIXED_POINT_SCALE, STITCH_GROUP_CUT,
+    STITCH_GROUP_TRIM,
+};
+
+fn is_loadable(item: &mut dyn Read, collection: bool) -> ReadResult<bool> {
+    let mut data = Vec::with_capacity(HEADER_LEN);
+    item.take(HEADER_LEN as u64).read_to_end(&mut data)?;
+    match Header::read(&data) {
+        Ok(header) => Ok(header.collection == collection),
+        Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Check the header and the checksum; returning the header, and the content after the header.
+fn read_file(item: &mut dyn Read, collection: bool) -> ReadResult<(Header, Vec<u8>)> {
+    let mut data = Vec::new();
+    item.read_to_end(&mut data)?;
+    let header = Header::read(&data)?;
+    if header.collection != collection {
+        return Err(ReadError::invalid_format(if collection {
+            "The file holds a single pattern; not a collection"
+        } else {
+            "The file holds a collection; not a single pattern"
+        }));
+    }
+    if header.checksum {
+        if data.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(ReadError::invalid_format("The file is too short to have a checksum"));
+        }
+        let (content, checksum) = data.split_at(data.len() - CHECKSUM_LEN);
+        let expected = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let actual = crc32fast::hash(content);
+        if actual != expected {
+            return Err(ReadError::invalid_format(format!(
+                "Checksum doesn't match. Expected 0x{:08X}; got 0x{:08X}",
+                expected, actual
+            )));
+        }
+        data.truncate(data.len() - CHECKSUM_LEN);
+    }
+    data.drain(..HEADER_LEN);
+    Ok((header, data))
+}
+
+#[derive(Default)]
+pub struct EmbrPatternReader {}
+
+impl PatternReader for EmbrPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> ReadResult<bool> {
+        is_loadable(item, false)
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> ReadResult<Pattern> {
+        let (header, data) = read_file(item, false)?;
+        let mut decoder = Decoder::new(&data, header)?;
+        let pattern = decoder.pattern()?;
+        decoder.finish()?;
+        Ok(pattern)
+    }
+}
+
+#[derive(Default)]
+pub struct EmbrCollectionReader {}
+
+impl CollectionReader for EmbrCollectionReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> ReadResult<bool> {
+        is_loadable(item, true)
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> ReadResult<PatternCollection> {
+        let (header, data) = read_file(item, true)?;
+        let mut decoder = Decoder::new(&data, header)?;
+        let count = decoder.varint()?;
+        let mut collection = PatternCollection::new();
+        for _ in 0..count {
+            let key = decoder.string()?;
+            let pattern = decoder.pattern()?;
+            collection.patterns.insert(key, pattern);
+        }
+        decoder.finish()?;
+        Ok(collection)
+    }
+}
+
+struct Decoder<'a> {
+    data: &'a [u8],
+    pos: usize,
+    strings: StringTable,
+    fixed_point: bool,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(data: &'a [u8], header: Header) -> ReadResult<Self> {
+        let mut pos = 0;
+        let strings = StringTable::read(data, &mut pos)?;
+        Ok(Decoder {
+            data,
+            pos,
+            strings,
+            fixed_point: header.fixed_point,
+        })
+    }
+
+    fn finish(&self) -> ReadResult<()> {
+        if self.pos != self.data.len() {
+            return Err(ReadError::invalid_format(format!(
+                "Unexpected data after the pattern; {} bytes",
+                self.data.len() - self.pos
+            )));
+        }
+        Ok(())
+    }
+
+    fn byte(&mut self) -> ReadResult<u8> {
+        match self.data.get(self.pos) {
+            Some(&byte) => {
+                self.pos += 1;
+                Ok(byte)
+            },
+            None => Err(ReadError::invalid_format("The file ends part way through the pattern")),
+        }
+    }
+
+    fn varint(&mut self) -> ReadResult<u64> {
+        read_varint(self.data, &mut self.pos)
+    }
+
+    fn string(&mut self) -> ReadResult<String> {
+        let idx = self.varint()?;
+        self.strings.get(idx)
+    }
+
+    fn coord(&self, value: i64) -> f64 {
+        if self.fixed_point {
+            value as f64 / FIXED_POINT_SCALE
+        } else {
+            f64::from_bits(value as u64)
+        }
+    }
+
+    fn pattern(&mut self) -> ReadResult<Pattern> {
+        let name = self.string()?;
+        let attribute_count = self.varint()?;
+        let mut attributes = Vec::new();
+        for _ in 0..attribute_count {
+            let attr = match self.byte()? {
+                ATTRIBUTE_ARBITARY => PatternAttribute::Arbitary(self.string()?, self.string()?),
+                ATTRIBUTE_TITLE => PatternAttribute::Title(self.string()?),
+                ATTRIBUTE_AUTHOR => PatternAttribute::Author(self.string()?),
+                ATTRIBUTE_COPYRIGHT => PatternAttribute::Copyright(self.string()?),
+                kind => return Err(ReadError::invalid_format(format!("Unknown attribute kind {}", kind))),
+            };
+            attributes.push(attr);
+        }
+
+        let mut last: (i64, i64) = (0, 0);
+        let color_group_count = self.varint()?;
+        let mut color_groups = Vec::new();
+        for _ in 0..color_group_count {
+            let thread = match self.byte()? {
+                0 => None,
+                1 => Some(self.thread()?),
+                flag => return Err(ReadError::invalid_format(format!("Invalid thread flag {}", flag))),
+            };
+            let stitch_group_count = self.varint()?;
+            let mut stitch_groups = Vec::new();
+            for _ in 0..stitch_group_count {
+                let flags = self.byte()?;
+                let stitch_count = self.varint()?;
+                let mut stitches = Vec::new();
+                for _ in 0..stitch_count {
+                    let x = last.0.wrapping_add(unzigzag(self.varint()?));
+                    let y = last.1.wrapping_add(unzigzag(self.varint()?));
+                    stitches.push(Stitch::new(self.coord(x), self.coord(y)));
+                    last = (x, y);
+                }
+                stitch_groups.push(StitchGroup {
+                    stitches,
+                    trim: flags & STITCH_GROUP_TRIM != 0,
+                    cut: flags & STITCH_GROUP_CUT != 0,
+                });
+            }
+            color_groups.push(ColorGroup { thread, stitch_groups });
+        }
+        Ok(Pattern {
+            name,
+            attributes,
+            color_groups,
+        })
+    }
+
+    fn thread(&mut self) -> ReadResult<Thread> {
+        let color = Color::rgb(self.byte()?, self.byte()?, self.byte()?);
+        let name = self.string()?;
+        let code = self.string()?;
+        let mut thread = Thread::new(color, name, code);
+        thread.manufacturer = match self.varint()? {
+            0 => None,
+            idx => Some(self.strings.get(idx - 1)?),
+        };
+        let attribute_count = self.varint()?;
+        for _ in 0..attribute_count {
+            let key = self.string()?;
+            let value = self.string()?;
+            thread.attributes.insert(key, value);
+        }
+        Ok(thread)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_pattern() {
+        let data = [
+            b'E', b'M', b'B', b'R', 0x01, 0x04, // Header; with fixed point coordinates
+            0x02, 0x01, b'a', 0x01, b'b', // Strings
+            0x00, // Name
+            0x01, 0x01, 0x01, // Title
+            0x01, // 1 color group
+            0x01, 0xFF, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, // Thread
+            0x01, 0x03, 0x02, // 1 stitch group, with 2 stitches
+            0xD0, 0x0F, 0xCF, 0x0F, // (1.0, -1.0)
+            0x01, 0xD0, 0x0F, // (0.999, 0.0)
+        ];
+        let pattern = EmbrPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(
+            pattern,
+            Pattern {
+                name: "a".to_string(),
+                attributes: vec![PatternAttribute::Title("b".to_string())],
+                color_groups: vec![ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"b", &"a")),
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(1.0, -1.0), Stitch::new(0.999, 0.0)])
+                        .with_trim(true)
+                        .with_cut(true)],
+                }],
+            }
+        );
+        // Trailing data, and data cut short.
+        let mut longer = data.to_vec();
+        longer.push(0);
+        assert!(EmbrPatternReader::default().read_pattern(&mut &longer[..]).is_err());
+        assert!(EmbrPatternReader::default()
+            .read_pattern(&mut &data[..data.len() - 1])
+            .is_err());
+    }
+}
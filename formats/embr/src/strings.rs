@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+use crate::varint::{read_varint, write_varint};
+
+/// Every string is stored once, and referred to by its index.
+#[derive(Debug, Default)]
+pub struct StringTableBuilder {
+    strings: Vec<String>,
+    indexes: HashMap<String, u64>,
+}
+
+impl StringTableBuilder {
+    pub fn intern(&mut self, value: &str) -> u64 {
+        if let Some(&idx) = self.indexes.get(value) {
+            return idx;
+        }
+        let idx = self.strings.len() as u64;
+        self.strings.push(value.to_string());
+        self.indexes.insert(value.to_string(), idx);
+        idx
+    }
+
+    pub fn write(&self, data: &mut Vec<u8>) {
+        write_varint(data, self.strings.len() as u64);
+        for value in &self.strings {
+            write_varint(data, value.len() as u64);
+            data.extend_from_slice(value.as_bytes());
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct StringTable {
+    strings: Vec<String>,
+}
+
+impl StringTable {
+    pub fn read(data: &[u8], pos: &mut usize) -> ReadResult<Self> {
+        let count = read_varint(data, pos)?;
+        let mut strings = Vec::new();
+        for _ in 0..count {
+            let len = read_varint(data, pos)? as usize;
+            let bytes = match data.get(*pos..pos.saturating_add(len)) {
+                Some(bytes) => bytes,
+                None => return Err(ReadError::invalid_format("The file ends part way through a string")),
+            };
+            *pos += len;
+            match String::from_utf8(bytes.to_vec()) {
+                Ok(value) => strings.push(value),
+                Err(_) => return Err(ReadError::invalid_format("String is not valid UTF-8")),
+            }
+        }
+        Ok(StringTable { strings })
+    }
+
+    pub fn get(&self, idx: u64) -> ReadResult<String> {
+        match self.strings.get(idx as usize) {
+            Some(value) => Ok(value.clone()),
+            None => Err(ReadError::invalid_format(format!("Unknown string {}", idx))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut builder = StringTableBuilder::default();
+        assert_eq!(builder.intern("a"), 0);
+        assert_eq!(builder.intern("Ünïcode"), 1);
+        assert_eq!(builder.intern("a"), 0);
+        let mut data = vec![];
+        builder.write(&mut data);
+        assert_eq!(data[..3], [2, 1, b'a']);
+
+        let mut pos = 0;
+        let table = StringTable::read(&data, &mut pos).unwrap();
+        assert_eq!(pos, data.len());
+        assert_eq!(table.get(1).unwrap(), "Ünïcode");
+        assert!(table.get(2).is_err());
+        assert!(StringTable::read(&data[..data.len() - 1], &mut 0).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+/// Write `value` 7 bits at a time, least significant first; the top bit of each byte is set when
+/// more bytes follow.
+pub fn write_varint(data: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        data.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+pub fn read_varint(data: &[u8], pos: &mut usize) -> ReadResult<u64> {
+    let mut value: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = match data.get(*pos) {
+            Some(&byte) => byte,
+            None => return Err(ReadError::invalid_format("The file ends part way through a number")),
+        };
+        *pos += 1;
+        value |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ReadError::invalid_format("Number is too big"))
+}
+
+/// Maps signed values onto unsigned ones so small negative numbers stay small: 0, -1, 1, -2, ...
+pub fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        for &(value, ref bytes) in &[
+            (0, vec![0x00]),
+            (0x7F, vec![0x7F]),
+            (0x80, vec![0x80, 0x01]),
+            (300, vec![0xAC, 0x02]),
+            (
+                u64::MAX,
+                vec![0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x01],
+            ),
+        ] {
+            let mut data = vec![];
+            write_varint(&mut data, value);
+            assert_eq!(&data, bytes);
+            let mut pos = 0;
+            assert_eq!(read_varint(&data, &mut pos).unwrap(), value);
+            assert_eq!(pos, data.len());
+        }
+        assert!(read_varint(&[0x80], &mut 0).is_err());
+        assert!(read_varint(&[0xFF; 11], &mut 0).is_err());
+    }
+
+    #[test]
+    fn test_zigzag() {
+        assert_eq!(zigzag(0), 0);
+        assert_eq!(zigzag(-1), 1);
+        assert_eq!(zigzag(1), 2);
+        assert_eq!(zigzag(i64::MIN), u64::MAX);
+        for &value in &[0, 1, -1, 1000, -1000, i64::MAX, i64::MIN] {
+            assert_eq!(unzigzag(zigzag(value)), value);
+        }
+    }
+}
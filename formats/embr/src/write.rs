@@ -0,0 +1,304 @@
+use std::io::Write;
+
+use embroidery_lib::format::{CollectionWriter, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use crate::header::Header;
+use crate::strings::StringTableBuilder;
+use crate::varint::{write_varint, zigzag};
+
+// Thousandths of a millimetre.
+pub const FIXED_POINT_SCALE: f64 = 1000.;
+// Beyond this an `f64` can't hold every whole number; so the fixed point values wouldn't be exact.
+const FIXED_POINT_LIMIT: f64 = 9_007_199_254_740_992. / FIXED_POINT_SCALE;
+
+pub const ATTRIBUTE_ARBITARY: u8 = 0;
+pub const ATTRIBUTE_TITLE: u8 = 1;
+pub const ATTRIBUTE_AUTHOR: u8 = 2;
+pub const ATTRIBUTE_COPYRIGHT: u8 = 3;
+
+pub const STITCH_GROUP_TRIM: u8 = 0x01;
+pub const STITCH_GROUP_CUT: u8 = 0x02;
+
+pub struct EmbrPatternWriter {
+    checksum: bool,
+}
+
+impl Default for EmbrPatternWriter {
+    fn default() -> Self {
+        EmbrPatternWriter { checksum: true }
+    }
+}
+
+impl EmbrPatternWriter {
+    pub fn with_checksum(checksum: bool) -> Self {
+        EmbrPatternWriter { checksum }
+    }
+}
+
+impl PatternWriter for EmbrPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        writer.write_all(&encode(&[(None, pattern)], false, self.checksum))?;
+        Ok(())
+    }
+}
+
+pub struct EmbrCollectionWriter {
+    checksum: bool,
+}
+
+impl Default for EmbrCollectionWriter {
+    fn default() -> Self {
+        EmbrCollectionWriter { checksum: true }
+    }
+}
+
+impl EmbrCollectionWriter {
+    pub fn with_checksum(checksum: bool) -> Self {
+        EmbrCollectionWriter { checksum }
+    }
+}
+
+impl CollectionWriter for EmbrCollectionWriter {
+    fn write_pattern(&self, collection: &PatternCollection, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let patterns: Vec<_> = collection
+            .patterns
+            .iter()
+            .map(|(key, pattern)| (Some(key.as_str()), pattern))
+            .collect();
+        writer.write_all(&encode(&patterns, true, self.checksum))?;
+        Ok(())
+    }
+}
+
+/// Whether the value survives being stored as a fixed point number.
+fn fits_fixed_point(value: f64) -> bool {
+    value.abs() < FIXED_POINT_LIMIT && (value * FIXED_POINT_SCALE).round() / FIXED_POINT_SCALE == value
+}
+
+fn encode(patterns: &[(Option<&str>, &Pattern)], collection: bool, checksum: bool) -> Vec<u8> {
+    let fixed_point = patterns
+        .iter()
+        .flat_map(|(_, pattern)| pattern.iter_stitches())
+        .all(|s| fits_fixed_point(s.x) && fits_fixed_point(s.y));
+    let mut encoder = Encoder {
+        strings: StringTableBuilder::default(),
+        content: Vec::new(),
+        fixed_point,
+    };
+    if collection {
+        write_varint(&mut encoder.content, patterns.len() as u64);
+    }
+    for (key, pattern) in patterns {
+        if let Some(key) = key {
+            encoder.string(key);
+        }
+        encoder.pattern(pattern);
+    }
+
+    let mut data = Vec::with_capacity(encoder.content.len() + 64);
+    Header {
+        collection,
+        checksum,
+        fixed_point,
+    }
+    .write(&mut data);
+    encoder.strings.write(&mut data);
+    data.append(&mut encoder.content);
+    if checksum {
+        let crc = crc32fast::hash(&data);
+        data.extend_from_slice(&crc.to_le_bytes());
+    }
+    data
+}
+
+struct Encoder {
+    strings: StringTableBuilder,
+    content: Vec<u8>,
+    fixed_point: bool,
+}
+
+impl Encoder {
+    fn varint(&mut self, value: u64) {
+        write_varint(&mut self.content, value);
+    }
+
+    fn string(&mut self, value: &str) {
+        let idx = self.strings.intern(value);
+        self.varint(idx);
+    }
+
+    fn coord(&self, value: f64) -> i64 {
+        if self.fixed_point {
+            (value * FIXED_POINT_SCALE).round() as i64
+        } else {
+            value.to_bits() as i64
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        self.string(&pattern.name);
+        self.varint(pattern.attributes.len() as u64);
+        for attr in &pattern.attributes {
+            match attr {
+                PatternAttribute::Arbitary(key, value) => {
+                    self.content.push(ATTRIBUTE_ARBITARY);
+                    self.string(key);
+                    self.string(value);
+                },
+                PatternAttribute::Title(value) => {
+                    self.content.push(ATTRIBUTE_TITLE);
+                    self.string(value);
+                },
+                PatternAttribute::Author(value) => {
+                    self.content.push(ATTRIBUTE_AUTHOR);
+                    self.string(value);
+                },
+                PatternAttribute::Copyright(value) => {
+                    self.content.push(ATTRIBUTE_COPYRIGHT);
+                    self.string(value);
+                },
+            }
+        }
+
+        // Stitches are stored relative to the one before; starting from the origin.
+        let mut last = (0, 0);
+        self.varint(pattern.color_groups.len() as u64);
+        for cg in &pattern.color_groups {
+            match &cg.thread {
+                Some(thread) => {
+                    self.content.push(1);
+                    self.thread(thread);
+                },
+                None => self.content.push(0),
+            }
+            self.varint(cg.stitch_groups.len() as u64);
+            for sg in &cg.stitch_groups {
+                let mut flags = 0;
+                if sg.trim {
+                    flags |= STITCH_GROUP_TRIM;
+                }
+                if sg.cut {
+                    flags |= STITCH_GROUP_CUT;
+                }
+                self.content.push(flags);
+                self.varint(sg.stitches.len() as u64);
+                for s in &sg.stitches {
+                    let (x, y) = (self.coord(s.x), self.coord(s.y));
+                    self.varint(zigzag(x.wrapping_sub(last.0)));
+                    self.varint(zigzag(y.wrapping_sub(last.1)));
+                    last = (x, y);
+                }
+            }
+        }
+    }
+
+    fn thread(&mut self, thread: &Thread) {
+        self.content
+            .extend_from_slice(&[thread.color.red, thread.color.green, thread.color.blue]);
+        self.string(&thread.name);
+        self.string(&thread.code);
+        // 0 when there isn't a manufacturer; otherwise one more than the string's index.
+        match &thread.manufacturer {
+            Some(manufacturer) => {
+                let idx = self.strings.intern(manufacturer);
+                self.varint(idx + 1);
+            },
+            None => self.varint(0),
+        }
+        self.varint(thread.attributes.len() as u64);
+        for (key, value) in &thread.attributes {
+            self.string(key);
+            self.string(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::{EmbrCollectionReader, EmbrPatternReader};
+    use embroidery_lib::format::{CollectionReader, PatternReader};
+
+    fn pattern(x: f64) -> Pattern {
+        let mut thread = Thread::new_str(Color::rgb(0, 0x43, 0xb1), &"Soldier Blue", &"2527");
+        thread.manufacturer = Some("Robison-Anton".to_string());
+        thread.attributes.insert("weight".to_string(), "40".to_string());
+        Pattern {
+            name: "Ünïcode".to_string(),
+            attributes: vec![
+                PatternAttribute::Title("Ünïcode".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Copyright("2020".to_string()),
+                PatternAttribute::Arbitary("notes".to_string(), "Soldier Blue".to_string()),
+            ],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(thread),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0.1, 0.2), Stitch::new(-1.25, 1e-3)])
+                            .with_trim(true)
+                            .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(x, -1.0)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(-300.0, 20.5)]).with_trim(true)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_fits_fixed_point() {
+        assert!(fits_fixed_point(0.1));
+        assert!(fits_fixed_point(-1234.567));
+        assert!(!fits_fixed_point(1.0 / 3.0));
+        assert!(!fits_fixed_point(1e300));
+        assert!(!fits_fixed_point(f64::NAN));
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for &x in &[30.0, 1.0 / 3.0] {
+            for &checksum in &[true, false] {
+                let pattern = pattern(x);
+                let mut data = vec![];
+                EmbrPatternWriter::with_checksum(checksum)
+                    .write_pattern(&pattern, &mut data)
+                    .unwrap();
+                assert_eq!(data[5] & 0x04 != 0, x == 30.0);
+                assert!(EmbrPatternReader::default().is_loadable(&mut &data[..]).unwrap());
+                assert!(!EmbrCollectionReader::default().is_loadable(&mut &data[..]).unwrap());
+                let read = EmbrPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+                assert_eq!(read, pattern);
+            }
+        }
+    }
+
+    #[test]
+    fn test_collection_roundtrip() {
+        let collection: PatternCollection = vec![("a".to_string(), pattern(30.0)), ("b".to_string(), pattern(0.5))]
+            .into_iter()
+            .collect();
+        let mut data = vec![];
+        EmbrCollectionWriter::default()
+            .write_pattern(&collection, &mut data)
+            .unwrap();
+        assert!(EmbrCollectionReader::default().is_loadable(&mut &data[..]).unwrap());
+        let read = EmbrCollectionReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(read, collection);
+    }
+
+    #[test]
+    fn test_checksum() {
+        let mut data = vec![];
+        EmbrPatternWriter::default()
+            .write_pattern(&pattern(30.0), &mut data)
+            .unwrap();
+        let idx = data.len() / 2;
+        data[idx] ^= 0x01;
+        assert!(EmbrPatternReader::default().read_pattern(&mut &data[..]).is_err());
+    }
+}
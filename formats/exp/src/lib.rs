@@ -0,0 +1,29 @@
+mod read;
+mod stitch_info;
+mod write;
+
+pub use read::ExpPatternReader;
+pub use write::ExpPatternWriter;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+const NAME: &str = "exp";
+const EXTENSIONS: [&str; 1] = ["exp"];
+
+#[derive(Default)]
+pub struct ExpPatternFormat {}
+
+impl PatternFormat for ExpPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(ExpPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(ExpPatternWriter::default()))
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::Read;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+
+use crate::stitch_info::{StitchInformation, StitchType, ESCAPE, JUMP, STITCH, STOP, TRIM};
+
+/// The widest or tallest design that's accepted, in mm; no machine has a hoop this big.
+const MAX_EXTENT: f64 = 1000.;
+
+#[derive(Default)]
+pub struct ExpPatternReader {}
+
+impl PatternReader for ExpPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        // There's no header to check; so the best that can be done is decoding the whole file.
+        match self.read_pattern(item) {
+            Ok(_) => Ok(true),
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut data = Vec::new();
+        item.read_to_end(&mut data)?;
+        if data.len() % 2 != 0 {
+            return Err(ReadError::invalid_format(format!(
+                "The file is made of 2 byte records; but it's {} bytes long",
+                data.len()
+            )));
+        }
+        let color_groups = read_stitches(&mut data.into_iter())?;
+        if color_groups.is_empty() {
+            return Err(ReadError::invalid_format("The file doesn't contain any stitches"));
+        }
+
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups,
+        };
+        // Without a header, files in other formats can decode as nonsense that wanders off.
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        if max_x - min_x > MAX_EXTENT || max_y - min_y > MAX_EXTENT {
+            return Err(ReadError::invalid_format(format!(
+                "The design would be {:.0}x{:.0}mm; which is too big to be an EXP file",
+                max_x - min_x,
+                max_y - min_y
+            )));
+        }
+        Ok(pattern)
+    }
+}
+
+fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let (first, second) = match (in_bytes.next(), in_bytes.next()) {
+        (Some(first), Some(second)) => (first, second),
+        (None, _) => return Ok(None),
+        (Some(_), None) => return Err(ReadError::invalid_format("The file ends part way through a stitch")),
+    };
+    if first != ESCAPE {
+        return Ok(Some(StitchInformation(first as i8, second as i8, StitchType::Regular)));
+    }
+    let stitch_type = match second {
+        STOP => StitchType::Stop,
+        STITCH => StitchType::Regular,
+        JUMP => StitchType::Jump,
+        TRIM => StitchType::Trim,
+        cmd => {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown stitch command 0x{:02X}",
+                cmd
+            )))
+        },
+    };
+    match (in_bytes.next(), in_bytes.next()) {
+        // The arguments to a trim don't move the needle.
+        (Some(_), Some(_)) if stitch_type == StitchType::Trim => Ok(Some(StitchInformation(0, 0, stitch_type))),
+        (Some(dx), Some(dy)) => Ok(Some(StitchInformation(dx as i8, dy as i8, stitch_type))),
+        _ => Err(ReadError::invalid_format("The file ends part way through a command")),
+    }
+}
+
+fn read_stitches(item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<ColorGroup>> {
+    let mut grouper = StitchGrouper::new();
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(StitchInformation(dx, dy, stitch_type)) = read_stitch(item)? {
+        cx += i32::from(dx);
+        cy += i32::from(dy);
+        let to = from_tenths(cx, cy);
+        match stitch_type {
+            StitchType::Regular => grouper.stitch(to),
+            StitchType::Jump => grouper.jump(to),
+            StitchType::Trim => {
+                grouper.trim();
+                grouper.jump(to);
+            },
+            StitchType::Stop => {
+                grouper.end_color(None);
+                grouper.jump(to);
+            },
+        }
+    }
+    Ok(grouper.finish(None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(read_stitch(to_u8_iter!(b"")).unwrap(), None);
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x01, 0xFF])).unwrap(),
+            Some(StitchInformation(1, -1, StitchType::Regular))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x04, 0x00, 0x0A])).unwrap(),
+            Some(StitchInformation(0, 10, StitchType::Jump))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x02, 0xF6, 0x00])).unwrap(),
+            Some(StitchInformation(-10, 0, StitchType::Regular))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x01, 0x00, 0x00])).unwrap(),
+            Some(StitchInformation(0, 0, StitchType::Stop))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x80, 0x07, 0x00])).unwrap(),
+            Some(StitchInformation(0, 0, StitchType::Trim))
+        );
+        assert!(read_stitch(to_u8_iter!([0x01])).is_err());
+        assert!(read_stitch(to_u8_iter!([0x80, 0x04, 0x00])).is_err());
+        assert!(read_stitch(to_u8_iter!([0x80, 0x42, 0x00, 0x00])).is_err());
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let data = [
+            0x0A, 0x00, // Stitch to (1.0, 0.0)
+            0x00, 0x0A, // Stitch to (1.0, 1.0)
+            0x80, 0x80, 0x07, 0x00, // Trim
+            0x80, 0x04, 0x14, 0x00, // Jump to (3.0, 1.0)
+            0x00, 0xF6, // Stitch to (3.0, 0.0)
+            0x80, 0x01, 0xF6, 0x00, // Stop, moving to (2.0, 0.0)
+            0x0A, 0x0A, // Stitch to (3.0, 1.0)
+        ];
+        let color_groups = read_stitches(to_u8_iter!(data)).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = ExpPatternReader::default();
+        assert!(reader
+            .is_loadable(&mut &[0x0A, 0x00, 0x80, 0x04, 0x14, 0x00][..])
+            .unwrap());
+        assert!(!reader
+            .is_loadable(&mut &[0x0A, 0x00, 0x80, 0x42, 0x00, 0x00][..])
+            .unwrap());
+        assert!(!reader.is_loadable(&mut &[0x0A, 0x00, 0x0A][..]).unwrap());
+    }
+
+    #[test]
+    fn test_read_empty() {
+        match ExpPatternReader::default().read_pattern(&mut &b""[..]) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            result => panic!("Expected an invalid format error; got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_read_odd_length() {
+        let reader = ExpPatternReader::default();
+        assert!(!reader.is_loadable(&mut &[0x0A, 0x00, 0x0A][..]).unwrap());
+        assert!(reader.read_pattern(&mut &[0x0A, 0x00, 0x0A][..]).is_err());
+    }
+
+    #[test]
+    fn test_read_too_big() {
+        // 100 stitches of 12.7mm each.
+        let data = [0x7F, 0x00].repeat(100);
+        let reader = ExpPatternReader::default();
+        assert!(reader.read_pattern(&mut &data[..50]).is_ok());
+        assert!(!reader.is_loadable(&mut &data[..]).unwrap());
+        assert!(reader.read_pattern(&mut &data[..]).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+// Every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm increments; except when the first
+// byte is `ESCAPE`, in which case the second byte is a command which is followed by a `(dx, dy)`
+// pair. There's no end command; the stitches just stop at the end of the file.
+pub const ESCAPE: u8 = 0x80;
+pub const STOP: u8 = 0x01;
+// Only written by some software; it's the same as a regular stitch.
+pub const STITCH: u8 = 0x02;
+pub const JUMP: u8 = 0x04;
+pub const TRIM: u8 = 0x80;
+// Melco's software writes these after a trim; they don't move the needle.
+const TRIM_ARGS: [u8; 2] = [0x07, 0x00];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    /// Stop the machine so that the thread can be changed.
+    Stop,
+    Trim,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StitchInformation(pub i8, pub i8, pub StitchType);
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            // -128 is reserved as it's the same byte as `ESCAPE`.
+            StitchInformation(dx, dy, _) if dx == i8::MIN || dy == i8::MIN => None,
+            StitchInformation(dx, dy, StitchType::Regular) => Some(vec![dx as u8, dy as u8]),
+            StitchInformation(dx, dy, StitchType::Jump) => Some(vec![ESCAPE, JUMP, dx as u8, dy as u8]),
+            StitchInformation(dx, dy, StitchType::Stop) => Some(vec![ESCAPE, STOP, dx as u8, dy as u8]),
+            StitchInformation(0, 0, StitchType::Trim) => Some(vec![ESCAPE, TRIM, TRIM_ARGS[0], TRIM_ARGS[1]]),
+            StitchInformation(_, _, StitchType::Trim) => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::Write;
+
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, to_tenths};
+
+use crate::stitch_info::{StitchInformation, StitchType};
+
+const MAX_JUMP: i32 = 127;
+
+#[derive(Default)]
+pub struct ExpPatternWriter {}
+
+impl PatternWriter for ExpPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        for &st in &into_exp_stitches(pattern)? {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&st.to_bytes().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+fn into_exp_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            re.push(StitchInformation(0, 0, StitchType::Stop));
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                re.append(&mut safe_jump_to(x - ox, y - oy));
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation(dx as i8, dy as i8, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation(0, 0, StitchType::Trim));
+            }
+        }
+    }
+    Ok(re)
+}
+
+/// Split a move into as few jumps as possible; none at all if it doesn't go anywhere.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<StitchInformation> {
+    split_jump(delta_x, delta_y, MAX_JUMP)
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation(dx as i8, dy as i8, StitchType::Jump))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_exp_stitches() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0)]).with_cut(true)
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(30.0, -1.0), Stitch::new(30.5, -2.5)])],
+                },
+            ],
+        };
+        assert_eq!(
+            into_exp_stitches(&pattern).unwrap(),
+            vec![
+                StitchInformation(10, 0, StitchType::Regular),
+                StitchInformation(0, 0, StitchType::Trim),
+                StitchInformation(0, 0, StitchType::Stop),
+                StitchInformation(96, -3, StitchType::Jump),
+                StitchInformation(97, -3, StitchType::Jump),
+                StitchInformation(97, -4, StitchType::Jump),
+                StitchInformation(5, -15, StitchType::Regular),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(ExpPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
@@ -0,0 +1,29 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_exp::{ExpPatternReader, ExpPatternWriter};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip() {
+    let pattern = load_sample();
+    let mut data = Vec::new();
+    ExpPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+    let loader = ExpPatternReader::default();
+    assert!(loader.is_loadable(&mut Cursor::new(&data)).unwrap());
+    let read = loader.read_pattern(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(read.color_groups.len(), 3);
+    for (read_cg, cg) in read.color_groups.iter().zip(&pattern.color_groups) {
+        assert_eq!(read_cg.stitch_groups, cg.stitch_groups);
+        // The format has no colors.
+        assert_eq!(read_cg.thread, None);
+    }
+}
@@ -0,0 +1,26 @@
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use self::write::{GcodeDialect, GcodePatternWriter, GcodeWriterOptions};
+
+const NAME: &str = "gcode";
+const EXTENSIONS: [&str; 2] = ["gcode", "nc"];
+
+#[derive(Default)]
+pub struct GcodePatternFormat {}
+
+impl PatternFormat for GcodePatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        None
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(GcodePatternWriter::default()))
+    }
+}
@@ -0,0 +1,371 @@
+use std::io::Write;
+
+use embroidery_lib::errors::WriteResult as Result;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{build_stitch_list, StitchInfo};
+
+/// The flavour of G-code the machine understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcodeDialect {
+    /// Marlin; which pauses for thread changes and trims with `M0` and a message on the display.
+    Marlin,
+    /// Grbl and LinuxCNC; where each thread is a tool, changed with `T<n> M6`.
+    Grbl,
+}
+
+/// Controls the generated G-code; distances are in mm, and feed rates in mm/min.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeWriterOptions {
+    pub dialect: GcodeDialect,
+    /// The feed rate used while stitching (or drawing).
+    pub stitch_feed_rate: f64,
+    /// The feed rate used for jumps; Grbl always travels at the machine's rapid rate.
+    pub travel_feed_rate: f64,
+    /// The feed rate used when lowering the Z axis.
+    pub plunge_feed_rate: f64,
+    /// The height of the Z axis when the pen (or needle) is down.
+    pub z_down: f64,
+    /// The height the Z axis is lifted to for travel.
+    pub z_up: f64,
+    /// Lower and lift the Z axis at every stitch, for machines where the Z axis drives the needle.
+    /// Otherwise the Z axis stays down while stitching, as a pen plotter needs.
+    pub plunge_each_stitch: bool,
+}
+
+impl Default for GcodeWriterOptions {
+    fn default() -> Self {
+        GcodeWriterOptions {
+            dialect: GcodeDialect::Marlin,
+            stitch_feed_rate: 1500.,
+            travel_feed_rate: 3000.,
+            plunge_feed_rate: 600.,
+            z_down: 0.,
+            z_up: 2.,
+            plunge_each_stitch: false,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GcodePatternWriter {
+    options: GcodeWriterOptions,
+}
+
+impl GcodePatternWriter {
+    pub fn new(options: GcodeWriterOptions) -> Self {
+        GcodePatternWriter { options }
+    }
+}
+
+impl PatternWriter for GcodePatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+        write_pattern(pattern, &self.options, writer)
+    }
+}
+
+/// Format a number with at most 3 decimal places; without trailing zeros.
+fn num(value: f64) -> String {
+    let s = format!("{:.3}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn thread_label(idx: usize, thread: &Option<Thread>) -> String {
+    match thread {
+        Some(thread) => match (thread.name.is_empty(), thread.code.is_empty()) {
+            (true, true) => format!("{}", thread.color),
+            (false, true) => format!("{} {}", thread.name, thread.color),
+            (true, false) => format!("{} {}", thread.code, thread.color),
+            (false, false) => format!("{} ({}) {}", thread.name, thread.code, thread.color),
+        },
+        None => format!("Color {}", idx),
+    }
+}
+
+struct Gcode<'a> {
+    options: &'a GcodeWriterOptions,
+    writer: &'a mut dyn Write,
+    pen_down: bool,
+    feed_rate: Option<f64>,
+    stitch_idx: usize,
+}
+
+impl<'a> Gcode<'a> {
+    fn comment(&mut self, text: &str) -> Result<()> {
+        let text = self.clean(text);
+        match self.options.dialect {
+            GcodeDialect::Marlin => writeln!(self.writer, "; {}", text)?,
+            GcodeDialect::Grbl => writeln!(self.writer, "({})", text)?,
+        }
+        Ok(())
+    }
+
+    /// Pause the machine, with a message for the operator.
+    fn pause(&mut self, message: &str) -> Result<()> {
+        self.lift()?;
+        let message = self.clean(message);
+        match self.options.dialect {
+            GcodeDialect::Marlin => writeln!(self.writer, "M0 {}", message)?,
+            GcodeDialect::Grbl => writeln!(self.writer, "M0 ({})", message)?,
+        }
+        Ok(())
+    }
+
+    /// Make free text safe to put on the end of a line.
+    fn clean(&self, text: &str) -> String {
+        let text: String = text.chars().map(|c| if c.is_control() { ' ' } else { c }).collect();
+        match self.options.dialect {
+            GcodeDialect::Marlin => text,
+            // Comments can't be nested; so the brackets have to go.
+            GcodeDialect::Grbl => text.replace('(', "[").replace(')', "]"),
+        }
+    }
+
+    /// The feed rate word, if it needs to change.
+    fn feed(&mut self, feed_rate: f64) -> String {
+        if self.feed_rate == Some(feed_rate) {
+            String::new()
+        } else {
+            self.feed_rate = Some(feed_rate);
+            format!(" F{}", num(feed_rate))
+        }
+    }
+
+    fn lift(&mut self) -> Result<()> {
+        if self.pen_down {
+            writeln!(self.writer, "G0 Z{}", num(self.options.z_up))?;
+            self.pen_down = false;
+        }
+        Ok(())
+    }
+
+    fn lower(&mut self) -> Result<()> {
+        if !self.pen_down {
+            let feed = self.feed(self.options.plunge_feed_rate);
+            writeln!(self.writer, "G1 Z{}{}", num(self.options.z_down), feed)?;
+            self.pen_down = true;
+        }
+        Ok(())
+    }
+
+    fn needle(&mut self) -> Result<()> {
+        self.lower()?;
+        if self.options.plunge_each_stitch {
+            self.lift()?;
+        }
+        Ok(())
+    }
+
+    fn check(&self, stitch: &Stitch) -> Result<()> {
+        if stitch.is_valid() {
+            Ok(())
+        } else {
+            Err(WriteError::unsupported_stitch_msg(
+                *stitch,
+                Some(self.stitch_idx),
+                "Stitch isn't a finite position",
+            ))
+        }
+    }
+
+    fn travel(&mut self, stitch: &Stitch) -> Result<()> {
+        self.check(stitch)?;
+        self.lift()?;
+        let feed = match self.options.dialect {
+            GcodeDialect::Marlin => self.feed(self.options.travel_feed_rate),
+            GcodeDialect::Grbl => String::new(),
+        };
+        writeln!(self.writer, "G0 X{} Y{}{}", num(stitch.x), num(stitch.y), feed)?;
+        self.stitch_idx += 1;
+        self.needle()
+    }
+
+    fn stitch(&mut self, stitch: &Stitch) -> Result<()> {
+        self.check(stitch)?;
+        if !self.options.plunge_each_stitch {
+            self.lower()?;
+        }
+        let feed = self.feed(self.options.stitch_feed_rate);
+        writeln!(self.writer, "G1 X{} Y{}{}", num(stitch.x), num(stitch.y), feed)?;
+        self.stitch_idx += 1;
+        self.needle()
+    }
+}
+
+fn write_pattern(pattern: &Pattern, options: &GcodeWriterOptions, writer: &mut dyn Write) -> Result<()> {
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    let mut gcode = Gcode {
+        options,
+        writer,
+        // Unknown to start with; so the first lift always happens.
+        pen_down: true,
+        feed_rate: None,
+        stitch_idx: 0,
+    };
+    gcode.comment(&pattern.name)?;
+    for attr in &pattern.attributes {
+        match attr {
+            PatternAttribute::Title(title) => gcode.comment(&format!("Title: {}", title))?,
+            PatternAttribute::Author(author) => gcode.comment(&format!("Author: {}", author))?,
+            PatternAttribute::Copyright(copyright) => gcode.comment(&format!("Copyright: {}", copyright))?,
+            PatternAttribute::Arbitary(_, _) => {},
+        }
+    }
+    gcode.comment(&format!("Stitches: {}", pattern.iter_stitches().count()))?;
+    gcode.comment(&format!(
+        "Bounds: X{} Y{} to X{} Y{}",
+        num(min_x),
+        num(min_y),
+        num(max_x),
+        num(max_y)
+    ))?;
+    writeln!(gcode.writer, "G21")?;
+    writeln!(gcode.writer, "G90")?;
+    gcode.lift()?;
+
+    let mut colors = 0;
+    for info in build_stitch_list(pattern) {
+        match info {
+            StitchInfo::Color(thread, stitch) => {
+                colors += 1;
+                let label = thread_label(colors, thread);
+                gcode.comment(&label)?;
+                match options.dialect {
+                    // The first thread is already on the machine.
+                    GcodeDialect::Marlin if colors > 1 => gcode.pause(&format!("Change thread to {}", label))?,
+                    GcodeDialect::Marlin => {},
+                    GcodeDialect::Grbl => {
+                        gcode.lift()?;
+                        writeln!(gcode.writer, "T{} M6", colors)?;
+                    },
+                }
+                gcode.travel(stitch)?;
+            },
+            StitchInfo::Jump(stitch) => gcode.travel(stitch)?,
+            StitchInfo::Stitch(stitch) => gcode.stitch(stitch)?,
+            StitchInfo::Cut(_) => gcode.pause("Trim thread")?,
+            StitchInfo::End(_) => {
+                gcode.lift()?;
+                match options.dialect {
+                    GcodeDialect::Marlin => writeln!(gcode.writer, "M84")?,
+                    GcodeDialect::Grbl => writeln!(gcode.writer, "M2")?,
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(options: GcodeWriterOptions) -> String {
+        let pattern = Pattern {
+            name: "Sample (1)".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"1234")),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(1., 1.), Stitch::new(2.5, -1.)]).with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(10., 1.), Stitch::new(11., 1.)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(0.125, 0.)])],
+                },
+            ],
+        };
+        let mut data = Vec::new();
+        GcodePatternWriter::new(options)
+            .write_pattern(&pattern, &mut data)
+            .unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn test_num() {
+        assert_eq!(num(1.), "1");
+        assert_eq!(num(-0.0001), "0");
+        assert_eq!(num(2.5), "2.5");
+        assert_eq!(num(0.12345), "0.123");
+    }
+
+    #[test]
+    fn test_marlin() {
+        let expected = [
+            "; Sample (1)",
+            "; Stitches: 6",
+            "; Bounds: X0 Y-1 to X11 Y1",
+            "G21",
+            "G90",
+            "G0 Z2",
+            "; Red (1234) #FF0000",
+            "G0 X1 Y1 F3000",
+            "G1 Z0 F600",
+            "G1 X2.5 Y-1 F1500",
+            "G0 Z2",
+            "M0 Trim thread",
+            "G0 X10 Y1 F3000",
+            "G1 Z0 F600",
+            "G1 X11 Y1 F1500",
+            "; Color 2",
+            "G0 Z2",
+            "M0 Change thread to Color 2",
+            "G0 X0 Y0 F3000",
+            "G1 Z0 F600",
+            "G1 X0.125 Y0 F1500",
+            "G0 Z2",
+            "M84",
+            "",
+        ];
+        assert_eq!(write(GcodeWriterOptions::default()), expected.join("\n"));
+    }
+
+    #[test]
+    fn test_grbl_plunging() {
+        let gcode = write(GcodeWriterOptions {
+            dialect: GcodeDialect::Grbl,
+            plunge_each_stitch: true,
+            z_down: -1.5,
+            ..GcodeWriterOptions::default()
+        });
+        assert!(gcode.starts_with("(Sample [1])\n"));
+        assert!(gcode.contains(
+            "(Red [1234] #FF0000)\nT1 M6\nG0 X1 Y1\nG1 Z-1.5 F600\nG0 Z2\nG1 X2.5 Y-1 F1500\nG1 Z-1.5 F600\nG0 Z2\n"
+        ));
+        assert!(gcode.contains("M0 (Trim thread)\n"));
+        assert!(gcode.contains("T2 M6\n"));
+        assert!(gcode.ends_with("M2\n"));
+    }
+
+    #[test]
+    fn test_pause_message() {
+        for (dialect, expected) in &[
+            (GcodeDialect::Marlin, "M0 Change thread to Red (1)  Blue\n"),
+            (GcodeDialect::Grbl, "M0 (Change thread to Red [1]  Blue)\n"),
+        ] {
+            let options = GcodeWriterOptions {
+                dialect: *dialect,
+                ..GcodeWriterOptions::default()
+            };
+            let mut data = Vec::new();
+            let mut gcode = Gcode {
+                options: &options,
+                writer: &mut data,
+                pen_down: false,
+                feed_rate: None,
+                stitch_idx: 0,
+            };
+            gcode.pause("Change thread to Red (1)\r\nBlue").unwrap();
+            assert_eq!(String::from_utf8(data).unwrap(), *expected);
+        }
+    }
+}
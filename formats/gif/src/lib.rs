@@ -0,0 +1,26 @@
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use self::write::{GifPatternWriter, GifWriterOptions};
+
+const NAME: &str = "gif";
+const EXTENSIONS: [&str; 1] = ["gif"];
+
+#[derive(Default)]
+pub struct GifPatternFormat {}
+
+impl PatternFormat for GifPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        None
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(GifPatternWriter::default()))
+    }
+}
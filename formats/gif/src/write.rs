@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use embroidery_lib::errors::WriteResult as Result;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::render::{
+    sewout_steps, SewoutOptions, SewoutStep, SewoutStepKind, Viewport, JUMP_COLOR, TRIM_COLOR,
+};
+use embroidery_lib::utils::thread_colors;
+
+/// The most colors a GIF can have.
+const MAX_PALETTE_LEN: usize = 256;
+
+/// Controls the animation; sizes are in mm, and times in seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GifWriterOptions {
+    pub sewout: SewoutOptions,
+    /// Pixels per inch.
+    pub dpi: f64,
+    /// The width of the line drawn for each stitch.
+    pub line_width: f64,
+    /// The space left around the pattern.
+    pub margin: f64,
+    /// The color of the fabric; or `None` for a transparent background.
+    pub background: Option<Color>,
+    /// How long each frame is shown for.
+    pub frame_time: f64,
+    /// The most frames to write; longer patterns are sped up to fit.
+    pub max_frames: usize,
+    /// How long the finished pattern is shown before the animation starts again.
+    pub end_pause: f64,
+}
+
+impl Default for GifWriterOptions {
+    fn default() -> Self {
+        GifWriterOptions {
+            sewout: SewoutOptions::default(),
+            dpi: 96.,
+            line_width: 0.4,
+            margin: 2.,
+            background: Some(Color::rgb(0xFF, 0xFF, 0xFF)),
+            frame_time: 0.1,
+            max_frames: 600,
+            end_pause: 2.,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct GifPatternWriter {
+    options: GifWriterOptions,
+}
+
+impl GifPatternWriter {
+    pub fn new(options: GifWriterOptions) -> Self {
+        GifPatternWriter { options }
+    }
+}
+
+impl PatternWriter for GifPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+        write_pattern(pattern, &self.options, writer)
+    }
+}
+
+fn into_io_error(err: gif::EncodingError) -> io::Error {
+    match err {
+        gif::EncodingError::Io(err) => err,
+        gif::EncodingError::Format(err) => io::Error::new(io::ErrorKind::InvalidInput, err.to_string()),
+    }
+}
+
+/// The colors used by every frame: the background, each color that's drawn, and blends of them
+/// with the background for the anti-aliased edges.
+///
+/// Picking the colors up front keeps the threads exactly the right color; which wouldn't happen
+/// if each frame had its colors picked from what's in it.
+struct Palette {
+    colors: Vec<[u8; 4]>,
+    transparent: Option<u8>,
+    lookup: HashMap<[u8; 4], u8>,
+}
+
+impl Palette {
+    fn new(inks: &[Color], background: Option<Color>) -> Self {
+        let mut inks: Vec<Color> = inks.iter().filter(|&&ink| Some(ink) != background).copied().collect();
+        inks.sort_by_key(|ink| (ink.red, ink.green, ink.blue));
+        inks.dedup();
+        inks.truncate(MAX_PALETTE_LEN - 1);
+        let levels = ((MAX_PALETTE_LEN - 1) / inks.len().max(1)).max(1);
+
+        let mut colors = vec![match background {
+            Some(back) => [back.red, back.green, back.blue, 0xFF],
+            None => [0, 0, 0, 0],
+        }];
+        for ink in &inks {
+            for level in 1..=levels {
+                let amount = level as f64 / levels as f64;
+                let color = match background {
+                    Some(back) => {
+                        let blend = |ink: u8, back: u8| {
+                            (f64::from(ink) * amount + f64::from(back) * (1. - amount)).round() as u8
+                        };
+                        [
+                            blend(ink.red, back.red),
+                            blend(ink.green, back.green),
+                            blend(ink.blue, back.blue),
+                            0xFF,
+                        ]
+                    },
+                    None => [ink.red, ink.green, ink.blue, (amount * 255.).round() as u8],
+                };
+                colors.push(color);
+            }
+        }
+        Palette {
+            colors,
+            transparent: if background.is_none() { Some(0) } else { None },
+            lookup: HashMap::new(),
+        }
+    }
+
+    /// The palette as RGB triples; for the GIF's global color table.
+    fn rgb(&self) -> Vec<u8> {
+        self.colors.iter().flat_map(|color| color[..3].to_vec()).collect()
+    }
+
+    /// The index of the closest color; comparing them as they'd look over black.
+    fn index(&mut self, pixel: [u8; 4]) -> u8 {
+        let colors = &self.colors;
+        *self.lookup.entry(pixel).or_insert_with(|| {
+            let premultiply = |color: [u8; 4]| {
+                let alpha = i32::from(color[3]);
+                [
+                    i32::from(color[0]) * alpha / 255,
+                    i32::from(color[1]) * alpha / 255,
+                    i32::from(color[2]) * alpha / 255,
+                    alpha,
+                ]
+            };
+            let target = premultiply(pixel);
+            let distance = |color: &[u8; 4]| {
+                premultiply(*color)
+                    .iter()
+                    .zip(&target)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum::<i32>()
+            };
+            let (idx, _) = colors
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, color)| distance(color))
+                .unwrap();
+            idx as u8
+        })
+    }
+}
+
+/// Writes frames; merging frames that look the same into one that's shown for longer.
+struct FrameWriter<'a> {
+    encoder: gif::Encoder<&'a mut dyn Write>,
+    palette: Palette,
+    width: u16,
+    height: u16,
+    /// The last frame, and when it stops being shown.
+    pending: Option<(Vec<u8>, f64)>,
+    /// When the frames written so far stop being shown.
+    written_until: f64,
+}
+
+impl<'a> FrameWriter<'a> {
+    fn push(&mut self, rgba: Vec<u8>, until: f64) -> Result<()> {
+        match &mut self.pending {
+            Some((pending, pending_until)) if *pending == rgba => *pending_until = until,
+            _ => {
+                self.flush()?;
+                self.pending = Some((rgba, until));
+            },
+        }
+        Ok(())
+    }
+
+    fn extend(&mut self, time: f64) {
+        if let Some((_, until)) = &mut self.pending {
+            *until += time;
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        if let Some((rgba, until)) = self.pending.take() {
+            let buffer: Vec<u8> = rgba
+                .chunks(4)
+                .map(|pixel| self.palette.index([pixel[0], pixel[1], pixel[2], pixel[3]]))
+                .collect();
+            // Round the end times rather than each delay; so the errors don't add up.
+            let delay = (until * 100.).round() - (self.written_until * 100.).round();
+            let frame = gif::Frame {
+                width: self.width,
+                height: self.height,
+                buffer: buffer.into(),
+                transparent: self.palette.transparent,
+                delay: delay.clamp(0., f64::from(u16::MAX)) as u16,
+                ..gif::Frame::default()
+            };
+            self.encoder.write_frame(&frame).map_err(into_io_error)?;
+            self.written_until = until;
+        }
+        Ok(())
+    }
+}
+
+/// Whether a jump or trim is on screen at any point in the `frame_time` before `time`.
+fn is_flashing(step: &SewoutStep, time: f64, frame_time: f64) -> bool {
+    step.start < time && step.end() > time - frame_time
+}
+
+fn write_pattern(pattern: &Pattern, options: &GifWriterOptions, writer: &mut dyn Write) -> Result<()> {
+    let viewport = Viewport::for_pattern(pattern, options.dpi, options.margin);
+    if viewport.width > u32::from(u16::MAX) || viewport.height > u32::from(u16::MAX) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "The image would be {}x{} pixels; which is too big for a GIF",
+                viewport.width, viewport.height
+            ),
+        )
+        .into());
+    }
+    let (width, height) = (viewport.width as u16, viewport.height as u16);
+    let mut canvas = viewport.new_canvas(options.background)?;
+    let line_width = options.line_width * viewport.scale;
+
+    let mut inks = thread_colors(pattern);
+    inks.extend_from_slice(&[JUMP_COLOR, TRIM_COLOR]);
+    let palette = Palette::new(&inks, options.background);
+    let mut encoder = gif::Encoder::new(writer, width, height, &palette.rgb()).map_err(into_io_error)?;
+    encoder.set_repeat(gif::Repeat::Infinite).map_err(into_io_error)?;
+    let mut frames = FrameWriter {
+        encoder,
+        palette,
+        width,
+        height,
+        pending: None,
+        written_until: 0.,
+    };
+
+    let steps = sewout_steps(pattern, &options.sewout);
+    let total = steps.last().map_or(0., |step| step.end());
+    // How much of the sewing each frame shows.
+    let sewing_time = (total / options.max_frames as f64).max(options.frame_time);
+    let frame_count = (total / sewing_time).ceil() as usize;
+
+    let mut next_step = 0;
+    frames.push(canvas.to_rgba8(), options.frame_time)?;
+    for frame in 1..=frame_count {
+        let time = frame as f64 * sewing_time;
+        while let Some(step) = steps.get(next_step).filter(|step| step.start < time) {
+            if let SewoutStepKind::Stitch(color) = step.kind {
+                canvas.draw_line(
+                    viewport.to_pixel(&step.from),
+                    viewport.to_pixel(&step.to),
+                    line_width,
+                    color,
+                );
+            }
+            next_step += 1;
+        }
+
+        let flashes: Vec<_> = steps[..next_step]
+            .iter()
+            .rev()
+            .take_while(|step| step.end() > time - sewing_time)
+            .filter(|step| is_flashing(step, time, sewing_time))
+            .collect();
+        let rgba = if flashes.is_empty() {
+            canvas.to_rgba8()
+        } else {
+            let mut overlay = canvas.clone();
+            for step in flashes {
+                let (from, to) = (viewport.to_pixel(&step.from), viewport.to_pixel(&step.to));
+                match step.kind {
+                    SewoutStepKind::Jump => overlay.draw_line(from, to, line_width.max(1.), JUMP_COLOR),
+                    SewoutStepKind::Trim => overlay.draw_line(to, to, line_width * 4., TRIM_COLOR),
+                    SewoutStepKind::Stitch(_) | SewoutStepKind::ColorChange(_) => {},
+                }
+            }
+            overlay.to_rgba8()
+        };
+        frames.push(rgba, (frame + 1) as f64 * options.frame_time)?;
+    }
+    frames.extend(options.end_pause);
+    frames.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern() -> Pattern {
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(0, 255, 0), &"Green", &"")),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(10., 0.), Stitch::new(10., 5.)])
+                            .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(0., 5.), Stitch::new(5., 5.)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(Thread::new_str(Color::rgb(0, 0, 255), &"Blue", &"")),
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(5., 5.), Stitch::new(5., 0.)])],
+                },
+            ],
+        }
+    }
+
+    fn decode(data: &[u8]) -> Vec<(u16, Vec<u8>)> {
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(data).unwrap();
+        let mut frames = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            frames.push((frame.delay, frame.buffer.to_vec()));
+        }
+        frames
+    }
+
+    #[test]
+    fn test_sewout() {
+        let mut data = vec![];
+        GifPatternWriter::new(GifWriterOptions {
+            sewout: SewoutOptions {
+                stitches_per_second: 10.,
+                jump_time: 0.1,
+                trim_time: 0.1,
+                color_change_time: 0.5,
+            },
+            dpi: 254.,
+            ..GifWriterOptions::default()
+        })
+        .write_pattern(&pattern(), &mut data)
+        .unwrap();
+        let frames = decode(&data);
+        let delays: Vec<_> = frames.iter().map(|(delay, _)| *delay).collect();
+        // Blank, 2 stitches, trim, jump, then a stitch that's shown through the color change;
+        // the last stitch is shown with the pause at the end.
+        assert_eq!(delays, vec![10, 10, 10, 10, 10, 60, 210]);
+        let has_color = |frame: &[u8], color: [u8; 3]| frame.chunks(4).any(|pixel| pixel[..3] == color);
+        assert!(!has_color(&frames[0].1, [0, 255, 0]));
+        assert!(has_color(&frames[1].1, [0, 255, 0]));
+        assert!(has_color(&frames[3].1, [255, 0, 0]));
+        assert!(!has_color(&frames[4].1, [255, 0, 0]));
+        assert!(!has_color(&frames[5].1, [0, 0, 255]));
+        assert!(has_color(&frames[6].1, [0, 0, 255]));
+    }
+
+    #[test]
+    fn test_palette() {
+        let red = Color::rgb(255, 0, 0);
+        let mut palette = Palette::new(&[red, red, Color::rgb(0, 0, 0)], Some(Color::rgb(255, 255, 255)));
+        assert_eq!(palette.colors.len(), 255);
+        assert_eq!(palette.colors[0], [255, 255, 255, 255]);
+        assert_eq!(palette.colors[127], [0, 0, 0, 255]);
+        assert_eq!(palette.colors[254], [255, 0, 0, 255]);
+        assert_eq!(palette.index([254, 0, 1, 255]), 254);
+        assert_eq!(palette.transparent, None);
+
+        let mut palette = Palette::new(&[red], None);
+        assert_eq!(palette.transparent, Some(0));
+        assert_eq!(palette.index([1, 2, 3, 0]), 0);
+        assert_eq!(palette.index([255, 0, 0, 255]), 255);
+    }
+
+    #[test]
+    fn test_max_frames() {
+        let mut data = vec![];
+        GifPatternWriter::new(GifWriterOptions {
+            max_frames: 3,
+            ..GifWriterOptions::default()
+        })
+        .write_pattern(&pattern(), &mut data)
+        .unwrap();
+        assert!(decode(&data).len() <= 4);
+    }
+
+    #[test]
+    fn test_too_big() {
+        let mut pattern = pattern();
+        pattern.color_groups[0].stitch_groups[0]
+            .stitches
+            .push(Stitch::new(20_000., 20_000.));
+        let mut data = vec![];
+        let writer = GifPatternWriter::new(GifWriterOptions {
+            dpi: 25.4,
+            ..GifWriterOptions::default()
+        });
+        assert!(writer.write_pattern(&pattern, &mut data).is_err());
+    }
+}
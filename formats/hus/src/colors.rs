@@ -1,6 +1,9 @@
-use std::io::Read;
+use std::io::{Read, Result as IoResult, Write};
 
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::WriteResult;
 use embroidery_lib::prelude::*;
+use embroidery_lib::utils::closest_color;
 
 use crate::header::{PatternHeader, PatternType};
 
@@ -37,6 +40,9 @@ pub const HUS_THREADS: [(Color, &str, &str); 29] = [
     (Color::rgb(175, 90, 10), "Light Brown", "HUS:28"),
 ];
 
+/// Each VIP color takes 4 bytes of the decoding table.
+pub const MAX_VIP_COLORS: usize = VIP_COLOR_DECODING_TABLE.len() / 4;
+
 pub const VIP_COLOR_DECODING_TABLE: [u8; 400] = [
     0x2E, 0x82, 0xE4, 0x6F, 0x38, 0xA9, 0xDC, 0xC6, 0x7B, 0xB6, 0x28, 0xAC, 0xFD, 0xAA, 0x8A, 0x4E, 0x76, 0x2E, 0xF0,
     0xE4, 0x25, 0x1B, 0x8A, 0x68, 0x4E, 0x92, 0xB9, 0xB4, 0x95, 0xF0, 0x3E, 0xEF, 0xF7, 0x40, 0x24, 0x18, 0x39, 0x31,
@@ -97,9 +103,90 @@ fn read_vip_colors(values: &[u8]) -> Result<Vec<Thread>, ReadError> {
         prev = v;
     }
 
-    Ok(values
+    Ok(colors
         .chunks_exact(4)
         .map(|colors| Color::rgb(colors[0], colors[1], colors[2]))
         .map(|color| Thread::new_str(color, &"", &""))
         .collect())
 }
+
+pub fn write_threads(pattern_type: PatternType, threads: &[Thread], file: &mut dyn Write) -> WriteResult<()> {
+    match pattern_type {
+        PatternType::Hus => Ok(write_hus_threads(threads, file)?),
+        PatternType::Vip => write_vip_colors(threads, file),
+    }
+}
+
+fn write_hus_threads(threads: &[Thread], file: &mut dyn Write) -> IoResult<()> {
+    for thread in threads {
+        file.write_u16::<LittleEndian>(closest_hus_thread(thread))?;
+    }
+    Ok(())
+}
+
+fn write_vip_colors(threads: &[Thread], file: &mut dyn Write) -> WriteResult<()> {
+    if threads.len() > MAX_VIP_COLORS {
+        return Err(WriteError::too_many_colors(threads.len(), MAX_VIP_COLORS));
+    }
+
+    let mut prev = 0;
+    let mut values = Vec::with_capacity(threads.len() * 4);
+    for (i, c) in threads
+        .iter()
+        .flat_map(|thread| vec![thread.color.red, thread.color.green, thread.color.blue, 0])
+        .enumerate()
+    {
+        let v = c ^ prev ^ VIP_COLOR_DECODING_TABLE[i];
+        values.push(v);
+        prev = v;
+    }
+    file.write_all(&values)?;
+    // This was derrived from a number of files; Don't understand why though.
+    for _ in threads {
+        file.write_u32::<LittleEndian>(1)?;
+    }
+    file.write_all(&[0x01, 0x00, 0x00, 0x00, 0x00, 0x00])?;
+    Ok(())
+}
+
+/// Find the palette index of the HUS thread closest in color to `thread`.
+pub fn closest_hus_thread(thread: &Thread) -> u16 {
+    closest_color(thread.color, &HUS_THREADS) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vip_colors_roundtrip() {
+        let threads = vec![
+            Thread::new_str(Color::rgb(227, 49, 31), &"", &""),
+            Thread::new_str(Color::rgb(247, 243, 247), &"", &""),
+        ];
+        let mut data = vec![];
+        write_vip_colors(&threads, &mut data).unwrap();
+        // Taken from `tests/test_data/Star.vip`
+        assert_eq!(data[..8], [0xCD, 0x7E, 0x85, 0xEA, 0x25, 0x7F, 0x54, 0x92]);
+        assert_eq!(read_vip_colors(&data[..8]).unwrap(), threads);
+    }
+
+    #[test]
+    fn test_too_many_vip_colors() {
+        let threads = vec![Thread::new_str(Color::rgb(0, 0, 0), &"", &""); MAX_VIP_COLORS + 1];
+        assert!(write_vip_colors(&threads[..MAX_VIP_COLORS], &mut vec![]).is_ok());
+        match write_vip_colors(&threads, &mut vec![]) {
+            Err(WriteError::TooManyColors { count, max, ctx: _ }) => assert_eq!((count, max), (101, 100)),
+            res => panic!("Expected too many colors; got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn test_closest_hus_thread() {
+        assert_eq!(closest_hus_thread(&Thread::new_str(Color::rgb(255, 0, 0), &"", &"")), 3);
+        assert_eq!(
+            closest_hus_thread(&Thread::new_str(Color::rgb(250, 250, 250), &"", &"")),
+            12
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use byteorder::{LittleEndian, WriteBytesExt};
 use embroidery_lib::errors::{ReadError, ReadResult};
 use embroidery_lib::read_int;
-use embroidery_lib::utils::c_trim;
+use embroidery_lib::utils::{c_trim, char_truncate};
 use std::io::{Read, Result, Write};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -44,7 +44,6 @@ pub struct PatternHeader {
     pub attribute_offset: u32,
     pub x_offset: u32,
     pub y_offset: u32,
-    // [0x00; 10]
 }
 
 impl PatternHeader {
@@ -139,7 +138,10 @@ impl PatternHeader {
         file.write_u32::<LittleEndian>(self.x_offset)?;
         file.write_u32::<LittleEndian>(self.y_offset)?;
 
-        file.write_all(&[0x00; 10])?;
+        let mut title = [0x00; 10];
+        let title_bytes = char_truncate(&self.title, title.len());
+        title[..title_bytes.len()].copy_from_slice(title_bytes.as_bytes());
+        file.write_all(&title)?;
 
         if self.pattern_type == PatternType::Vip {
             // This was derrived from a number of files; Don't understand why though.
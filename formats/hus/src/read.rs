@@ -11,10 +11,23 @@ use crate::header::PatternHeader;
 pub enum HusVipStitchType {
     Normal,
     Jump,
+    Trim,
     ColorChange,
     LastStitch,
 }
 
+impl HusVipStitchType {
+    pub fn to_byte(self) -> u8 {
+        match self {
+            HusVipStitchType::Normal => 0x80,
+            HusVipStitchType::Jump => 0x81,
+            HusVipStitchType::ColorChange => 0x84,
+            HusVipStitchType::Trim => 0x88,
+            HusVipStitchType::LastStitch => 0x90,
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct HusVipPatternReader {}
 
@@ -76,7 +89,6 @@ fn decompress(item: &mut dyn Read, len_opt: Option<usize>) -> Result<Box<[u8]>,
         item.read_to_end(&mut d)?;
         d
     };
-    println!("{:X?}", data);
     match do_decompress_level(&data, CompressionLevel::Level4) {
         Ok(d) => Ok(d),
         Err(e) => Err(ReadError::invalid_format(format!("Decompression failed: {:?}", e))),
@@ -91,12 +103,8 @@ fn read_attributes(header: &PatternHeader, item: &mut dyn Read) -> Result<Vec<Hu
             0x80 => HusVipStitchType::Normal,      // Normal stitch
             0x81 => HusVipStitchType::Jump,        // Jump stitch
             0x84 => HusVipStitchType::ColorChange, // Color change
+            0x88 => HusVipStitchType::Trim,        // Likely to be a cut stitch.
             0x90 => HusVipStitchType::LastStitch,  // Last stitch in pattern
-
-            0x88 => {
-                // Additional stitch type. Likely to be a cut stitch.
-                HusVipStitchType::Jump
-            },
             _ => {
                 return Err(ReadError::invalid_format(format!(
                     "Invalid attribute({}) at stitch {}",
@@ -188,7 +196,7 @@ fn convert_stitches(
                     y: f64::from(y) / 10.0,
                 });
             },
-            HusVipStitchType::Jump => {
+            HusVipStitchType::Jump | HusVipStitchType::Trim => {
                 if !stitches.is_empty() {
                     let old_stitches = stitches;
                     stitches = Vec::new();
@@ -1,6 +1,16 @@
+use std::io::{self, Write};
+
+use archivelib::{do_compress_level, CompressionLevel};
 use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{char_truncate, split_jump, to_tenths};
+
+use crate::colors::{write_threads, HUS_THREADS};
+use crate::header::{PatternHeader, PatternType};
+use crate::read::HusVipStitchType;
 
-use crate::header::PatternType;
+const MAX_JUMP: i32 = 127;
+const TITLE_LEN: usize = 10;
 
 pub struct HusVipPatternWriter {
     mode: PatternType,
@@ -16,11 +26,177 @@ impl HusVipPatternWriter {
 }
 
 impl PatternWriter for HusVipPatternWriter {
-    fn write_pattern(
-        &self,
-        _: &embroidery_lib::Pattern,
-        _: &mut dyn std::io::Write,
-    ) -> std::result::Result<(), embroidery_lib::errors::write::Error> {
-        todo!()
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let (attributes, x_deltas, y_deltas) = into_hus_stitches(pattern)?;
+        let number_of_stitches = attributes.len() as u32;
+        let threads = threads_for(pattern);
+
+        let mut colors = Vec::new();
+        write_threads(self.mode, &threads, &mut colors)?;
+        let attributes = compress(&attributes)?;
+        let x_deltas = compress(&x_deltas)?;
+        let y_deltas = compress(&y_deltas)?;
+
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        let mut header = PatternHeader {
+            pattern_type: self.mode,
+            title: char_truncate(&pattern.name, TITLE_LEN),
+            number_of_stitches,
+            number_of_colors: threads.len() as u32,
+            postitive_x_hoop_size: to_tenths(max_x.max(0.)) as i16,
+            postitive_y_hoop_size: to_tenths(max_y.max(0.)) as i16,
+            negative_x_hoop_size: to_tenths(min_x.min(0.)) as i16,
+            negative_y_hoop_size: to_tenths(min_y.min(0.)) as i16,
+            attribute_offset: 0,
+            x_offset: 0,
+            y_offset: 0,
+        };
+        header.attribute_offset = (header.header_len() + colors.len()) as u32;
+        header.x_offset = header.attribute_offset + attributes.len() as u32;
+        header.y_offset = header.x_offset + x_deltas.len() as u32;
+
+        header.write(writer)?;
+        writer.write_all(&colors)?;
+        writer.write_all(&attributes)?;
+        writer.write_all(&x_deltas)?;
+        writer.write_all(&y_deltas)?;
+        Ok(())
+    }
+}
+
+fn compress(data: &[u8]) -> io::Result<Box<[u8]>> {
+    do_compress_level(data, CompressionLevel::Level4)
+        .map_err(|e| io::Error::other(format!("Compression failed: {}", e)))
+}
+
+fn threads_for(pattern: &Pattern) -> Vec<Thread> {
+    pattern
+        .color_groups
+        .iter()
+        .enumerate()
+        .map(|(i, cg)| match &cg.thread {
+            Some(thread) => thread.clone(),
+            None => {
+                let (color, name, code) = HUS_THREADS[i % HUS_THREADS.len()];
+                Thread::new_str(color, &name, &code)
+            },
+        })
+        .collect()
+}
+
+/// The attribute, x and y streams; each entry in the coordinate streams is a signed byte relative
+/// to the previous entry.
+type HusStitches = (Vec<u8>, Vec<u8>, Vec<u8>);
+
+fn into_hus_stitches(pattern: &Pattern) -> Result<HusStitches, WriteError> {
+    let mut attributes = vec![];
+    let mut x_deltas = vec![];
+    let mut y_deltas = vec![];
+    let mut push = |stitch_type: HusVipStitchType, dx: i32, dy: i32| {
+        attributes.push(stitch_type.to_byte());
+        x_deltas.push(dx as i8 as u8);
+        y_deltas.push(dy as i8 as u8);
+    };
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            push(HusVipStitchType::ColorChange, 0, 0);
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                // Always jump at least once so that the reader can tell where each group starts.
+                for (dx, dy) in safe_jump_to(x - ox, y - oy) {
+                    push(HusVipStitchType::Jump, dx, dy);
+                }
+                ox = x;
+                oy = y;
+                idx += 1;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                push(HusVipStitchType::Normal, dx, dy);
+                ox = x;
+                oy = y;
+                idx += 1;
+            }
+            if sg.cut {
+                push(HusVipStitchType::Trim, 0, 0);
+            }
+        }
+    }
+    push(HusVipStitchType::LastStitch, 0, 0);
+    Ok((attributes, x_deltas, y_deltas))
+}
+
+/// Split a move into as few jumps as possible; with a single empty jump if it doesn't go anywhere.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<(i32, i32)> {
+    let jumps = split_jump(delta_x, delta_y, MAX_JUMP);
+    if jumps.is_empty() {
+        vec![(0, 0)]
+    } else {
+        jumps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_jump_to() {
+        assert_eq!(safe_jump_to(0, 0), vec![(0, 0)]);
+        assert_eq!(safe_jump_to(-127, 127), vec![(-127, 127)]);
+        assert_eq!(safe_jump_to(300, -10), vec![(100, -3), (100, -3), (100, -4)]);
+    }
+
+    #[test]
+    fn test_into_hus_stitches() {
+        let pattern = Pattern {
+            name: "Test".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, -1.0)])
+                        .with_trim(true)
+                        .with_cut(true)],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(2.0, 1.0)])],
+                },
+            ],
+        };
+        let (attributes, x_deltas, y_deltas) = into_hus_stitches(&pattern).unwrap();
+        assert_eq!(attributes, vec![0x81, 0x80, 0x88, 0x84, 0x81, 0x80, 0x90]);
+        assert_eq!(x_deltas, vec![0x00, 0x0A, 0x00, 0x00, 0x0A, 0x00, 0x00]);
+        assert_eq!(y_deltas, vec![0x00, 0xF6, 0x00, 0x00, 0x0A, 0x0A, 0x00]);
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(HusVipPatternWriter::hus().write_pattern(&pattern, &mut data).is_err());
     }
 }
@@ -1,7 +1,7 @@
-use embroidery_lib::format::PatternReader;
+use embroidery_lib::format::{PatternReader, PatternWriter};
 use embroidery_lib::prelude::*;
 
-use embroidery_fmt_hus::HusVipPatternReader;
+use embroidery_fmt_hus::{HusVipPatternReader, HusVipPatternWriter};
 
 use std::collections::BTreeMap;
 use std::io::Cursor;
@@ -40,38 +40,38 @@ fn test_hus_file_load() {
     assert_eq!(sg.stitches.last(), Some(&Stitch::new(111.4, -21.1)));
 }
 
-// #[test]
-// fn test_star_hus_file_load() {
-//     let data: &[u8] = include_bytes!("test_data/Star.hus");
-//     let loader = HusVipPatternReader {};
-//
-//     // assert!(loader.is_loadable(&mut Cursor::new(data)).unwrap());
-//     let pattern = loader.read_pattern(&mut Cursor::new(data)).unwrap();
-//
-//     println!("{:?}", pattern);
-//
-//     let cgs = pattern.color_groups.clone();
-//     assert_eq!(cgs.len(), 2);
-//     assert_eq!(
-//         cgs[0].thread,
-//         Some(Thread {
-//             color: Color { red: 255, green: 0, blue: 0 }, name: "Red".to_string(), code:
-// "HUS:03".to_string(), manufacturer: None,             attributes: BTreeMap::new(),
-//         })
-//     );
-//
-//     let sgs = &cgs[0].stitch_groups;
-//     let sg = &sgs[0];
-//     assert_eq!(sg.cut, true);
-//     assert_eq!(sg.trim, true);
-//     assert_eq!(sg.stitches[0], Stitch::new(-88.9, -12.7));
-//     assert_eq!(sg.stitches.last(), Some(&Stitch::new(111.4, -21.1)));
-//
-//     assert_eq!(sgs.len(), 2);
-// }
-// #[test]
-// fn test_star_vip_file_load() {
-//     let loader = HusVipPatternReader {};
-//     let vip_data: &[u8] = include_bytes!("test_data/Star.vip");
-//     let vip_pattern = loader.read_pattern(&mut
-// Cursor::new(vip_data)).unwrap().remove_duplicate_stitches(); }
+fn assert_roundtrip(data: &[u8], writer: HusVipPatternWriter) {
+    let loader = HusVipPatternReader {};
+    let pattern = loader.read_pattern(&mut Cursor::new(data)).unwrap();
+
+    let mut written = Vec::new();
+    writer.write_pattern(&pattern, &mut written).unwrap();
+    assert!(loader.is_loadable(&mut Cursor::new(&written)).unwrap());
+    let reread = loader.read_pattern(&mut Cursor::new(&written)).unwrap();
+
+    assert_eq!(reread, pattern);
+}
+
+#[test]
+fn test_star_hus_roundtrip() {
+    let data: &[u8] = include_bytes!("test_data/Star.hus");
+    let pattern = HusVipPatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap();
+    let cgs = &pattern.color_groups;
+    assert_eq!(cgs.len(), 2);
+    assert_eq!(cgs[0].thread.as_ref().unwrap().name, "Red");
+    assert_eq!(cgs[1].thread.as_ref().unwrap().name, "White");
+
+    assert_roundtrip(data, HusVipPatternWriter::hus());
+}
+
+#[test]
+fn test_star_vip_roundtrip() {
+    let data: &[u8] = include_bytes!("test_data/Star.vip");
+    let pattern = HusVipPatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap();
+    let cgs = &pattern.color_groups;
+    assert_eq!(cgs.len(), 2);
+    assert_eq!(cgs[0].thread.as_ref().unwrap().color, Color::rgb(227, 49, 31));
+    assert_eq!(cgs[1].thread.as_ref().unwrap().color, Color::rgb(247, 243, 247));
+
+    assert_roundtrip(data, HusVipPatternWriter::vip());
+}
@@ -1,4 +1,5 @@
 use embroidery_lib::prelude::*;
+use embroidery_lib::utils::closest_color;
 
 // These colors are taken from EmbroideryModder
 pub const JEF_THREADS: [(Color, &str, &str); 79] = [
@@ -82,3 +83,36 @@ pub const JEF_THREADS: [(Color, &str, &str); 79] = [
     (Color::rgb(227, 196, 180), "Beige Grey", ""),
     (Color::rgb(227, 172, 129), "Bamboo", ""),
 ];
+
+/// The thread at `idx` in the palette; wrapping around when it's past the end.
+pub fn jef_thread(idx: usize) -> Thread {
+    let (color, name, code) = JEF_THREADS[idx % JEF_THREADS.len()];
+    Thread::new_str(color, &name, &code)
+}
+
+/// Find the palette index of the JEF thread closest in color to `thread`.
+pub fn closest_jef_thread(thread: &Thread) -> u32 {
+    // Index 0 is a placeholder which machines don't display; so never pick it.
+    (closest_color(thread.color, &JEF_THREADS[1..]) + 1) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_jef_thread() {
+        let exact = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        assert_eq!(closest_jef_thread(&exact), 10);
+        let near = Thread::new_str(Color::rgb(10, 10, 250), &"Not quite blue", &"");
+        assert_eq!(closest_jef_thread(&near), 12);
+        let black = Thread::new_str(Color::rgb(0, 0, 0), &"Black", &"");
+        assert_eq!(closest_jef_thread(&black), 1);
+    }
+
+    #[test]
+    fn test_jef_thread() {
+        assert_eq!(jef_thread(2), Thread::new_str(Color::rgb(255, 255, 255), &"White", &""));
+        assert_eq!(jef_thread(JEF_THREADS.len() + 2).name, "White");
+    }
+}
@@ -0,0 +1,99 @@
+const HOOP_110X110: u32 = 0;
+const HOOP_50X50: u32 = 1;
+const HOOP_140X200: u32 = 2;
+const HOOP_126X110: u32 = 3;
+const HOOP_200X200: u32 = 4;
+
+/// The hoops with known sizes, smallest first.
+const HOOPS_BY_SIZE: [JefHoop; 5] = [
+    JefHoop::Hoop50x50,
+    JefHoop::Hoop110x110,
+    JefHoop::Hoop126x110,
+    JefHoop::Hoop140x200,
+    JefHoop::Hoop200x200,
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum JefHoop {
+    Hoop110x110,
+    Hoop50x50,
+    Hoop140x200,
+    Hoop126x110,
+    Hoop200x200,
+    Other(u32),
+}
+
+impl JefHoop {
+    pub fn from_byte(hoop_code: u32) -> Self {
+        match hoop_code {
+            HOOP_50X50 => JefHoop::Hoop50x50,
+            HOOP_110X110 => JefHoop::Hoop110x110,
+            HOOP_126X110 => JefHoop::Hoop126x110,
+            HOOP_140X200 => JefHoop::Hoop140x200,
+            HOOP_200X200 => JefHoop::Hoop200x200,
+            other => JefHoop::Other(other),
+        }
+    }
+    pub fn to_bytes(self) -> u32 {
+        match self {
+            JefHoop::Hoop50x50 => HOOP_50X50,
+            JefHoop::Hoop110x110 => HOOP_110X110,
+            JefHoop::Hoop126x110 => HOOP_126X110,
+            JefHoop::Hoop140x200 => HOOP_140X200,
+            JefHoop::Hoop200x200 => HOOP_200X200,
+            JefHoop::Other(code) => code,
+        }
+    }
+    pub fn hoop_size(self) -> Option<(f64, f64)> {
+        match self {
+            JefHoop::Hoop50x50 => Some((50.0, 50.0)),
+            JefHoop::Hoop110x110 => Some((110.0, 110.0)),
+            JefHoop::Hoop126x110 => Some((126.0, 110.0)),
+            JefHoop::Hoop140x200 => Some((140.0, 200.0)),
+            JefHoop::Hoop200x200 => Some((200.0, 200.0)),
+            _ => None,
+        }
+    }
+    pub fn name(self) -> String {
+        match self.hoop_size() {
+            Some((width, height)) => format!("{}x{}", width, height),
+            None => format!("Unknown({})", self.to_bytes()),
+        }
+    }
+
+    /// The smallest hoop where a design extending `half_width`/`half_height` mm either side of the
+    /// hoop's centre will fit. Designs that are too big get the largest hoop.
+    pub fn smallest_fitting(half_width: f64, half_height: f64) -> Self {
+        HOOPS_BY_SIZE
+            .iter()
+            .copied()
+            .find(|hoop| match hoop.hoop_size() {
+                Some((width, height)) => half_width * 2. <= width && half_height * 2. <= height,
+                None => false,
+            })
+            .unwrap_or(JefHoop::Hoop200x200)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_byte_roundtrip() {
+        for code in 0..6 {
+            assert_eq!(JefHoop::from_byte(code).to_bytes(), code);
+        }
+    }
+
+    #[test]
+    fn test_smallest_fitting() {
+        assert_eq!(JefHoop::smallest_fitting(0., 0.), JefHoop::Hoop50x50);
+        assert_eq!(JefHoop::smallest_fitting(25., 25.), JefHoop::Hoop50x50);
+        assert_eq!(JefHoop::smallest_fitting(25.1, 10.), JefHoop::Hoop110x110);
+        assert_eq!(JefHoop::smallest_fitting(60., 50.), JefHoop::Hoop126x110);
+        assert_eq!(JefHoop::smallest_fitting(60., 60.), JefHoop::Hoop140x200);
+        assert_eq!(JefHoop::smallest_fitting(80., 60.), JefHoop::Hoop200x200);
+        assert_eq!(JefHoop::smallest_fitting(500., 500.), JefHoop::Hoop200x200);
+    }
+}
@@ -0,0 +1,7 @@
+//! The thread palette and hoops used by Janome's formats; shared by the JEF and SEW crates.
+
+mod colors;
+mod hoops;
+
+pub use colors::{closest_jef_thread, jef_thread, JEF_THREADS};
+pub use hoops::JefHoop;
@@ -1,12 +1,14 @@
-use byteorder::LittleEndian;
+use byteorder::{LittleEndian, WriteBytesExt};
 use embroidery_lib::errors::ReadResult;
 use embroidery_lib::prelude::*;
-use embroidery_lib::read_int;
-use std::io::Read;
+use embroidery_lib::utils::c_trim;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
 
-use crate::hoops::JefHoop;
+use embroidery_janome::{closest_jef_thread, jef_thread, JefHoop};
 
-use crate::colors::JEF_THREADS;
+// Offset + flags + date + padding + #colors + #stitches + hoop + 5 rectangles
+const HEADER_LEN: usize = 4 + 4 + 14 + 2 + 4 + 4 + 4 + (5 * 16);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct PatternHeader {
@@ -33,16 +35,31 @@ impl PatternHeader {
         let stitch_abs_offset = read_int!(file, u32, LittleEndian)?;
         let format_flags = read_int!(file, u32, LittleEndian)?; /* TODO: find out what this means */
         //
-        let datetime = {
-            // String of: yyyymmddHHMMSS
-            let mut v = [0; 14];
-            file.read_exact(&mut v)?;
-            v
-        };
-        assert_eq!(0, read_int!(file, u16, LittleEndian)?);
+        // String of: yyyymmddHHMMSS
+        let datetime = read_exact!(file, [_; 14])?;
+        let padding = read_int!(file, u16, LittleEndian)?;
+        if padding != 0 {
+            return Err(ReadError::invalid_format(format!(
+                "Expected padding after the date to be 0; got {:X}",
+                padding
+            )));
+        }
 
         let number_of_colors = read_int!(file, u32, LittleEndian)?;
         let number_of_stitches = read_int!(file, u32, LittleEndian)?;
+        if HEADER_LEN + (number_of_colors as usize) * 4 > stitch_abs_offset as usize {
+            return Err(ReadError::invalid_format(format!(
+                "Stitch offset({}) points inside the header of a pattern with {} colors",
+                stitch_abs_offset, number_of_colors
+            )));
+        }
+        // Only the thread types sit between the colors and the stitches.
+        if stitch_abs_offset as usize > HEADER_LEN + (number_of_colors as usize) * 8 {
+            return Err(ReadError::invalid_format(format!(
+                "Stitch offset({}) is past the thread types of a pattern with {} colors",
+                stitch_abs_offset, number_of_colors
+            )));
+        }
         let hoop = JefHoop::from_byte(read_int!(file, u32, LittleEndian)?);
 
         let bounds = (
@@ -75,11 +92,9 @@ impl PatternHeader {
             read_int!(file, u32, LittleEndian)?,
             read_int!(file, u32, LittleEndian)?,
         );
-        let mut threads = Vec::with_capacity(number_of_colors as usize);
+        let mut threads = Vec::new();
         for _ in 0..number_of_colors {
-            let idx = (read_int!(file, u32, LittleEndian)? as usize) % 79;
-            let (color, name, code) = JEF_THREADS[idx];
-            threads.push(Thread::new_str(color, &name, &code))
+            threads.push(jef_thread(read_int!(file, u32, LittleEndian)? as usize));
         }
         Ok(PatternHeader {
             stitch_abs_offset,
@@ -97,51 +112,72 @@ impl PatternHeader {
         })
     }
 
-    // pub fn header_len(&self) -> usize {
-    //     // Magic bytes + #stitches + #colors + [+ve x] + [+ve y] + [-ve x] + [-ve y]
-    //     //  + [attr offset] + [x offset] + [y offset] + [10 bytes]
-    //     let header = 4 + (2 * 4) + (4 * 2) + (3 * 4) + 10;
-    //     let header_color_extra = match self.pattern_type {
-    //         PatternType::Hus => 0,
-    //         PatternType::Vip => 4,
-    //     };
-    //     header + header_color_extra
-    // }
-    // pub fn color_len(&self) -> usize {
-    //     match self.pattern_type {
-    //         PatternType::Hus => (self.number_of_colors as usize) * 2,
-    //         PatternType::Vip => (self.number_of_colors as usize) * 4,
-    //     }
-    // }
-    // pub fn color_consume_len(&self) -> usize {
-    //     (self.attribute_offset as usize) - self.header_len()
-    // }
-    //
-    // pub fn attribute_len(&self) -> usize {
-    //     (self.x_offset as usize) - (self.attribute_offset as usize)
-    // }
-    // pub fn x_offset_len(&self) -> usize {
-    //     (self.y_offset as usize) - (self.x_offset as usize)
-    // }
-    //
-    // pub fn write(&self, file: &mut dyn Write) -> Result<()> {
-    //     file.write_all(&self.pattern_type.magic_bytes())?;
-    //     file.write_u32::<LittleEndian>(self.number_of_stitches)?;
-    //     file.write_u32::<LittleEndian>(self.number_of_colors)?;
-    //     file.write_i16::<LittleEndian>(self.postitive_x_hoop_size)?;
-    //     file.write_i16::<LittleEndian>(self.postitive_y_hoop_size)?;
-    //     file.write_i16::<LittleEndian>(self.negative_x_hoop_size)?;
-    //     file.write_i16::<LittleEndian>(self.negative_y_hoop_size)?;
-    //     file.write_u32::<LittleEndian>(self.attribute_offset)?;
-    //     file.write_u32::<LittleEndian>(self.x_offset)?;
-    //     file.write_u32::<LittleEndian>(self.y_offset)?;
-    //
-    //     file.write_all(&[0x00; 10])?;
-    //
-    //     if self.pattern_type == PatternType::Vip {
-    //         // This was derrived from a number of files; Don't understand why though.
-    //         file.write_u32::<LittleEndian>(0x2E + 8 * self.number_of_colors)?;
-    //     }
-    //     Ok(())
-    // }
+    pub fn datetime_string(&self) -> String {
+        c_trim(&String::from_utf8_lossy(&self.datetime))
+    }
+
+    /// The number of bytes consumed by `build`; the stitches start `stitch_abs_offset - header_len`
+    /// bytes after this.
+    pub fn header_len(&self) -> usize {
+        HEADER_LEN + (self.number_of_colors as usize) * 4
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_u32::<LittleEndian>(self.stitch_abs_offset)?;
+        file.write_u32::<LittleEndian>(self.format_flags)?;
+        file.write_all(&self.datetime)?;
+        file.write_u16::<LittleEndian>(0)?;
+        file.write_u32::<LittleEndian>(self.number_of_colors)?;
+        file.write_u32::<LittleEndian>(self.number_of_stitches)?;
+        file.write_u32::<LittleEndian>(self.hoop.to_bytes())?;
+        for &(a, b, c, d) in &[
+            self.bounds,
+            self.rect_from_110x110,
+            self.rect_from_50x50,
+            self.rect_from_200x140,
+            self.rect_from_custom,
+        ] {
+            file.write_u32::<LittleEndian>(a)?;
+            file.write_u32::<LittleEndian>(b)?;
+            file.write_u32::<LittleEndian>(c)?;
+            file.write_u32::<LittleEndian>(d)?;
+        }
+        for thread in &self.threads {
+            file.write_u32::<LittleEndian>(closest_jef_thread(thread))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_header_roundtrip() {
+        let data = [
+            0x84, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x32, 0x30, 0x32, 0x30, 0x30, 0x31, 0x30, 0x32, 0x30, 0x33,
+            0x30, 0x34, 0x30, 0x35, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x2A, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+            0x64, 0x00, 0x00, 0x00, 0x96, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x96, 0x00, 0x00, 0x00, 0xC2, 0x01,
+            0x00, 0x00, 0x90, 0x01, 0x00, 0x00, 0xC2, 0x01, 0x00, 0x00, 0x90, 0x01, 0x00, 0x00, 0x96, 0x00, 0x00, 0x00,
+            0x64, 0x00, 0x00, 0x00, 0x96, 0x00, 0x00, 0x00, 0x64, 0x00, 0x00, 0x00, 0x58, 0x02, 0x00, 0x00, 0x52, 0x03,
+            0x00, 0x00, 0x58, 0x02, 0x00, 0x00, 0x52, 0x03, 0x00, 0x00, 0x12, 0x02, 0x00, 0x00, 0x90, 0x01, 0x00, 0x00,
+            0x12, 0x02, 0x00, 0x00, 0x90, 0x01, 0x00, 0x00, 0x0A, 0x00, 0x00, 0x00, 0x0C, 0x00, 0x00, 0x00,
+        ];
+        let header = PatternHeader::build(&mut Cursor::new(&data[..])).unwrap();
+        assert_eq!(header.stitch_abs_offset, 0x84);
+        assert_eq!(header.datetime_string(), "20200102030405");
+        assert_eq!(header.number_of_colors, 2);
+        assert_eq!(header.number_of_stitches, 0x12A);
+        assert_eq!(header.hoop, JefHoop::Hoop50x50);
+        assert_eq!(header.bounds, (100, 150, 100, 150));
+        assert_eq!(header.threads[0].name, "Red");
+        assert_eq!(header.threads[1].name, "Blue");
+        assert_eq!(header.header_len(), data.len());
+
+        let mut out = Vec::with_capacity(data.len());
+        header.write(&mut Cursor::new(&mut out)).unwrap();
+        assert_eq!(&data[..], &out[..])
+    }
 }
@@ -1,9 +1,10 @@
-mod colors;
 mod header;
-mod hoops;
 mod read;
+mod stitch_info;
+mod write;
 
 pub use read::JefPatternReader;
+pub use write::JefPatternWriter;
 
 use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
 
@@ -24,6 +25,6 @@ impl PatternFormat for JefPatternFormat {
         Some(Box::from(JefPatternReader::default()))
     }
     fn writer(&self) -> Option<Box<dyn PatternWriter>> {
-        None
+        Some(Box::from(JefPatternWriter::default()))
     }
 }
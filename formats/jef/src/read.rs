@@ -1,10 +1,14 @@
 use std::io::Read;
 
+use embroidery_lib::errors::ReadResult;
 use embroidery_lib::format::PatternReader;
 use embroidery_lib::prelude::*;
+use embroidery_lib::read_exact;
+use embroidery_lib::utils::ReadByteIterator;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
 
-// use crate::colors::read_threads;
 use crate::header::PatternHeader;
+use crate::stitch_info::{StitchInformation, StitchType, COLOR_CHANGE, END, ESCAPE, JUMP};
 
 #[derive(Default)]
 pub struct JefPatternReader {}
@@ -13,36 +17,239 @@ impl PatternReader for JefPatternReader {
     fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
         // Load the header
         // Check the last byte of the file? maybe
-        return match PatternHeader::build(item) {
+        match PatternHeader::build(item) {
             Err(ReadError::InvalidFormat(_, _)) => Ok(false),
             Err(error) => Err(error),
             Ok(_) => Ok(true),
-        };
+        }
     }
 
     fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
         // Read the header
-        let _header = PatternHeader::build(item)?;
-        unimplemented!();
-        // let threads = read_threads(&header, item)?;
-        // let attributes = read_attributes(&header, item)?;
-        // let x_coords = read_x_coords(&header, item)?;
-        // let y_coords = read_y_coords(&header, item)?;
-        // if attributes.len() != x_coords.len() || attributes.len() != y_coords.len() {
-        //     return Err(ReadError::invalid_format(format!(
-        //         "Different numbers of attributes({}), x coordinates({}) and y coordinates({})",
-        //         attributes.len(),
-        //         x_coords.len(),
-        //         y_coords.len()
-        //     )));
-        // }
-        //
-        // // let color_groups = read_stitches(&mut iter)?;
-        // // let (title, attributes) = extract_title(attributes);
-        // Ok(Pattern {
-        //     name: "".to_string(),
-        //     attributes: vec![],
-        //     color_groups: convert_stitches(threads, attributes, x_coords, y_coords),
-        // })
+        let header = PatternHeader::build(item)?;
+        // Skip over the thread types between the header and the stitches.
+        let gap = (header.stitch_abs_offset as usize) - header.header_len();
+        match read_exact!(item, vec![_; gap]) {
+            Err(ReadError::UnexpectedEof(..)) => {
+                return Err(ReadError::invalid_format(format!(
+                    "File ends inside the {} bytes of thread types",
+                    gap
+                )))
+            },
+            result => result?,
+        };
+
+        let mut iter = ReadByteIterator::new(item);
+        let color_groups = read_stitches(&header.threads, &mut iter)?;
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+
+        let mut attributes = vec![];
+        let datetime = header.datetime_string();
+        if !datetime.is_empty() {
+            attributes.push(PatternAttribute::Arbitary("datetime".to_string(), datetime));
+        }
+        attributes.push(PatternAttribute::Arbitary("hoop".to_string(), header.hoop.name()));
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let (first, second) = match (in_bytes.next(), in_bytes.next()) {
+        (Some(first), Some(second)) => (first, second),
+        _ => return Ok(None),
+    };
+    if first != ESCAPE {
+        return Ok(Some(StitchInformation::Move(
+            first as i8,
+            second as i8,
+            StitchType::Regular,
+        )));
+    }
+    let stitch_type = match second {
+        END => return Ok(Some(StitchInformation::End)),
+        COLOR_CHANGE => StitchType::ColorChange,
+        JUMP => StitchType::Jump,
+        cmd => {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown stitch command 0x{:02X}",
+                cmd
+            )))
+        },
+    };
+    match (in_bytes.next(), in_bytes.next()) {
+        (Some(dx), Some(dy)) => Ok(Some(StitchInformation::Move(dx as i8, dy as i8, stitch_type))),
+        _ => Ok(None),
+    }
+}
+
+fn read_stitches(threads: &[Thread], item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<ColorGroup>> {
+    let mut thread_iter = threads.iter();
+    let mut grouper = StitchGrouper::new();
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(stitch) = read_stitch(item)? {
+        let (dx, dy, stitch_type) = match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => (i32::from(dx), i32::from(dy), stitch_type),
+            StitchInformation::End => break,
+        };
+        cx += dx;
+        cy += dy;
+        let to = from_tenths(cx, cy);
+        match stitch_type {
+            StitchType::Regular => grouper.stitch(to),
+            StitchType::Jump => {
+                if stitch.is_trim() {
+                    grouper.trim();
+                }
+                grouper.jump(to);
+            },
+            StitchType::ColorChange => {
+                // Each color change moves onto the next thread, even if nothing was sewn with the
+                // current one.
+                grouper.end_color(thread_iter.next().cloned());
+                grouper.jump(to);
+            },
+        }
+    }
+    Ok(grouper.finish(thread_iter.next().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(read_stitch(to_u8_iter!(b"")).unwrap(), None);
+        assert_eq!(read_stitch(to_u8_iter!([0x01])).unwrap(), None);
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x01, 0xFF])).unwrap(),
+            Some(StitchInformation::Move(1, -1, StitchType::Regular))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x02, 0x00, 0x0A])).unwrap(),
+            Some(StitchInformation::Move(0, 10, StitchType::Jump))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x01, 0x00, 0x00])).unwrap(),
+            Some(StitchInformation::Move(0, 0, StitchType::ColorChange))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x10])).unwrap(),
+            Some(StitchInformation::End)
+        );
+        assert!(read_stitch(to_u8_iter!([0x80, 0x42, 0x00, 0x00])).is_err());
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let blue = Thread::new_str(Color::rgb(0, 0, 240), &"Blue", &"");
+        let data = [
+            0x0A, 0x00, // Stitch to (1.0, 0.0)
+            0x00, 0x0A, // Stitch to (1.0, 1.0)
+            0x80, 0x02, 0x00, 0x00, // Trim
+            0x80, 0x02, 0x14, 0x00, // Jump to (3.0, 1.0)
+            0x00, 0xF6, // Stitch to (3.0, 0.0)
+            0x80, 0x01, 0xF6, 0x00, // Color change, moving to (2.0, 0.0)
+            0x0A, 0x0A, // Stitch to (3.0, 1.0)
+            0x80, 0x10, // End
+            0x0A, 0x0A, // Ignored
+        ];
+        let color_groups = read_stitches(&[red.clone(), blue.clone()], to_u8_iter!(data)).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(blue),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_pattern() {
+        let mut data = vec![];
+        // Stitch offset, format flags, date & padding
+        data.extend_from_slice(&[0x7C, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(b"20200102030405\0\0");
+        // Colors, stitches, hoop
+        data.extend_from_slice(&[0x01, 0, 0, 0, 0x03, 0, 0, 0, 0x01, 0, 0, 0]);
+        // Bounds & hoop rectangles
+        data.extend_from_slice(&[0x00; 5 * 16]);
+        // Thread index & thread type
+        data.extend_from_slice(&[0x0A, 0, 0, 0, 0x0D, 0, 0, 0]);
+        data.extend_from_slice(&[0x0A, 0x00, 0x0A, 0x0A, 0x80, 0x10]);
+
+        let pattern = JefPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(
+            pattern.attributes,
+            vec![
+                PatternAttribute::Arbitary("datetime".to_string(), "20200102030405".to_string()),
+                PatternAttribute::Arbitary("hoop".to_string(), "50x50".to_string()),
+            ]
+        );
+        assert_eq!(pattern.color_groups.len(), 1);
+        let thread = pattern.color_groups[0].thread.as_ref().unwrap();
+        assert_eq!(thread.name, "Red");
+        assert_eq!(thread.color, Color::rgb(255, 0, 0));
+        assert_eq!(
+            pattern.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0), Stitch::new(2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_read_truncated_thread_types() {
+        let mut data = vec![];
+        data.extend_from_slice(&[0x7C, 0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00]);
+        data.extend_from_slice(b"20200102030405\0\0");
+        data.extend_from_slice(&[0x01, 0, 0, 0, 0x03, 0, 0, 0, 0x01, 0, 0, 0]);
+        data.extend_from_slice(&[0x00; 5 * 16]);
+        // The thread type is cut off.
+        data.extend_from_slice(&[0x0A, 0, 0, 0, 0x0D]);
+
+        match JefPatternReader::default().read_pattern(&mut &data[..]) {
+            Err(ReadError::InvalidFormat(..)) => {},
+            other => panic!("Expected an invalid format error; got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = JefPatternReader::default();
+        assert!(!reader
+            .is_loadable(&mut &b"LA:crown FS 40     \rST:   4562\r"[..])
+            .unwrap());
     }
 }
@@ -0,0 +1,40 @@
+// Every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm increments; except when the first
+// byte is `ESCAPE`, in which case the second byte is a command which is (usually) followed by a
+// regular `(dx, dy)` pair.
+pub const ESCAPE: u8 = 0x80;
+pub const COLOR_CHANGE: u8 = 0x01;
+pub const JUMP: u8 = 0x02;
+pub const END: u8 = 0x10;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    ColorChange,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchInformation {
+    Move(i8, i8, StitchType),
+    End,
+}
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            // -128 is reserved as it's the same byte as `ESCAPE`.
+            StitchInformation::Move(dx, dy, _) if dx == i8::MIN || dy == i8::MIN => None,
+            StitchInformation::Move(dx, dy, StitchType::Regular) => Some(vec![dx as u8, dy as u8]),
+            StitchInformation::Move(dx, dy, StitchType::Jump) => Some(vec![ESCAPE, JUMP, dx as u8, dy as u8]),
+            StitchInformation::Move(dx, dy, StitchType::ColorChange) => {
+                Some(vec![ESCAPE, COLOR_CHANGE, dx as u8, dy as u8])
+            },
+            StitchInformation::End => Some(vec![ESCAPE, END]),
+        }
+    }
+
+    /// A jump that doesn't go anywhere is how JEF files encode a trim.
+    pub fn is_trim(self) -> bool {
+        self == StitchInformation::Move(0, 0, StitchType::Jump)
+    }
+}
@@ -0,0 +1,220 @@
+use std::io::Write;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, to_tenths};
+
+use embroidery_janome::{jef_thread, JefHoop, JEF_THREADS};
+
+use crate::header::PatternHeader;
+use crate::stitch_info::{StitchInformation, StitchType};
+
+const MAX_JUMP: i32 = 127;
+// Copied from files written by Janome's software; what it means is unknown.
+const FORMAT_FLAGS: u32 = 0x14;
+const THREAD_TYPE: u32 = 0x0D;
+
+#[derive(Default)]
+pub struct JefPatternWriter {}
+
+impl PatternWriter for JefPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let stitches = into_jef_stitches(pattern)?;
+        let header = build_header(pattern, &stitches);
+        header.write(writer)?;
+        for _ in &header.threads {
+            writer.write_u32::<LittleEndian>(THREAD_TYPE)?;
+        }
+        for &st in &stitches {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&st.to_bytes().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+fn build_header(pattern: &Pattern, jef_stitches: &[StitchInformation]) -> PatternHeader {
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    // Distances from the centre of the hoop in 0.1mm.
+    let left = -to_tenths(min_x.min(0.));
+    let top = to_tenths(max_y.max(0.));
+    let right = to_tenths(max_x.max(0.));
+    let bottom = -to_tenths(min_y.min(0.));
+    let hoop = JefHoop::smallest_fitting(f64::from(left.max(right)) / 10., f64::from(top.max(bottom)) / 10.);
+
+    let threads: Vec<Thread> = pattern
+        .color_groups
+        .iter()
+        .enumerate()
+        .map(|(i, cg)| match &cg.thread {
+            Some(thread) => thread.clone(),
+            // Skip the placeholder at index 0.
+            None => jef_thread(1 + (i % (JEF_THREADS.len() - 1))),
+        })
+        .collect();
+
+    // Keep the date from the file this pattern was read from, if there was one.
+    let datetime_str = pattern
+        .attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            PatternAttribute::Arbitary(key, value) if key == "datetime" && value.len() == 14 => Some(value.clone()),
+            _ => None,
+        })
+        .next()
+        .unwrap_or_else(|| chrono::Local::now().format("%Y%m%d%H%M%S").to_string());
+    let mut datetime = [0_u8; 14];
+    datetime.copy_from_slice(&datetime_str.as_bytes()[..14]);
+
+    let mut header = PatternHeader {
+        stitch_abs_offset: 0,
+        format_flags: FORMAT_FLAGS,
+        datetime,
+        number_of_colors: threads.len() as u32,
+        number_of_stitches: jef_stitches.len() as u32,
+        hoop,
+        bounds: (left as u32, top as u32, right as u32, bottom as u32),
+        rect_from_110x110: hoop_edge_distance((550, 550), (left, top, right, bottom)),
+        rect_from_50x50: hoop_edge_distance((250, 250), (left, top, right, bottom)),
+        rect_from_200x140: hoop_edge_distance((700, 1000), (left, top, right, bottom)),
+        rect_from_custom: hoop_edge_distance((630, 550), (left, top, right, bottom)),
+        threads,
+    };
+    // The thread types sit between the header and the stitches.
+    header.stitch_abs_offset = (header.header_len() + 4 * header.threads.len()) as u32;
+    header
+}
+
+/// The distance from each edge of the design to the edge of a hoop; or all `-1` when the design
+/// doesn't fit.
+fn hoop_edge_distance(
+    (half_hoop_width, half_hoop_height): (i32, i32),
+    (left, top, right, bottom): (i32, i32, i32, i32),
+) -> (u32, u32, u32, u32) {
+    let edges = (
+        half_hoop_width - left,
+        half_hoop_height - top,
+        half_hoop_width - right,
+        half_hoop_height - bottom,
+    );
+    if edges.0 < 0 || edges.1 < 0 || edges.2 < 0 || edges.3 < 0 {
+        (u32::MAX, u32::MAX, u32::MAX, u32::MAX)
+    } else {
+        (edges.0 as u32, edges.1 as u32, edges.2 as u32, edges.3 as u32)
+    }
+}
+
+fn into_jef_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            re.push(StitchInformation::Move(0, 0, StitchType::ColorChange));
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                re.append(&mut safe_jump_to(x - ox, y - oy));
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation::Move(0, 0, StitchType::Jump));
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok(re)
+}
+
+/// Split a move into as few jumps as possible; none at all if it doesn't go anywhere.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<StitchInformation> {
+    split_jump(delta_x, delta_y, MAX_JUMP)
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation::Move(dx as i8, dy as i8, StitchType::Jump))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embroidery_fmt_vp4::Vp4PatternReader;
+    use embroidery_lib::format::PatternReader;
+
+    #[test]
+    fn test_header() {
+        let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+        let pattern = Vp4PatternReader {}.read_pattern(&mut &data[..]).unwrap();
+        let mut data = vec![];
+        JefPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+
+        let header = PatternHeader::build(&mut &data[..]).unwrap();
+        assert_eq!(header.number_of_colors, 3);
+        assert_eq!(header.hoop, JefHoop::Hoop50x50);
+        assert_eq!(header.bounds, (77, 225, 77, 225));
+        assert_eq!(header.rect_from_50x50, (173, 25, 173, 25));
+        assert_eq!(header.rect_from_110x110, (473, 325, 473, 325));
+        assert_eq!(header.rect_from_200x140, (623, 775, 623, 775));
+        assert_eq!(header.rect_from_custom, (553, 325, 553, 325));
+    }
+
+    #[test]
+    fn test_safe_jump_to() {
+        assert_eq!(safe_jump_to(0, 0), vec![]);
+        assert_eq!(
+            safe_jump_to(-127, 127),
+            vec![StitchInformation::Move(-127, 127, StitchType::Jump)]
+        );
+        assert_eq!(
+            safe_jump_to(300, -10),
+            vec![
+                StitchInformation::Move(100, -3, StitchType::Jump),
+                StitchInformation::Move(100, -3, StitchType::Jump),
+                StitchInformation::Move(100, -4, StitchType::Jump),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hoop_edge_distance() {
+        assert_eq!(hoop_edge_distance((250, 250), (100, 150, 200, 0)), (150, 100, 50, 250));
+        assert_eq!(
+            hoop_edge_distance((250, 250), (100, 150, 300, 0)),
+            (u32::MAX, u32::MAX, u32::MAX, u32::MAX)
+        );
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(JefPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+/// Identifies the documents written by this crate; so other JSON files aren't mistaken for them.
+pub const FORMAT: &str = "embroidery-rust";
+/// The version of the schema; which changes whenever the data model does.
+pub const VERSION: u32 = 1;
+
+/// The fields every document starts with; which are checked before reading the rest.
+#[derive(Debug, Deserialize)]
+pub struct DocumentHeader {
+    pub format: String,
+    pub version: u32,
+}
+
+impl DocumentHeader {
+    pub fn check(&self) -> ReadResult<()> {
+        if self.format != FORMAT {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown document format {:?}; expected {:?}",
+                self.format, FORMAT
+            )));
+        }
+        if self.version == 0 || self.version > VERSION {
+            return Err(ReadError::invalid_format(format!(
+                "Unsupported version {}; the newest supported version is {}",
+                self.version, VERSION
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternDocument {
+    pub format: String,
+    pub version: u32,
+    pub pattern: Pattern,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CollectionDocument {
+    pub format: String,
+    pub version: u32,
+    pub collection: PatternCollection,
+}
+
+pub fn parse<'a, T: Deserialize<'a>>(data: &'a [u8]) -> ReadResult<T> {
+    serde_json::from_slice(data).map_err(|err| ReadError::invalid_format(format!("Invalid document: {}", err)))
+}
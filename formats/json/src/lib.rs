@@ -0,0 +1,50 @@
+mod document;
+mod read;
+mod write;
+
+use embroidery_lib::format::{
+    CollectionFormat, CollectionReader, CollectionWriter, PatternFormat, PatternReader, PatternWriter,
+};
+
+pub use self::document::VERSION;
+pub use self::read::{JsonCollectionReader, JsonPatternReader};
+pub use self::write::{JsonCollectionWriter, JsonPatternWriter};
+
+const NAME: &str = "json";
+const EXTENSIONS: [&str; 1] = ["json"];
+
+#[derive(Default)]
+pub struct JsonPatternFormat {}
+
+impl PatternFormat for JsonPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(JsonPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(JsonPatternWriter::default()))
+    }
+}
+
+#[derive(Default)]
+pub struct JsonCollectionFormat {}
+
+impl CollectionFormat for JsonCollectionFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn CollectionReader>> {
+        Some(Box::from(JsonCollectionReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn CollectionWriter>> {
+        Some(Box::from(JsonCollectionWriter::default()))
+    }
+}
@@ -0,0 +1,144 @@
+use std::io::Read;
+
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::{CollectionReader, PatternReader};
+use embroidery_lib::prelude::*;
+
+use crate::document::{parse, CollectionDocument, DocumentHeader, PatternDocument};
+
+// Used to check a document has the right content, without checking the content itself.
+#[derive(Deserialize)]
+struct PatternProbe {
+    #[serde(flatten)]
+    header: DocumentHeader,
+    #[allow(dead_code)]
+    pattern: IgnoredAny,
+}
+
+#[derive(Deserialize)]
+struct CollectionProbe {
+    #[serde(flatten)]
+    header: DocumentHeader,
+    #[allow(dead_code)]
+    collection: IgnoredAny,
+}
+
+fn read_all(item: &mut dyn Read) -> ReadResult<Vec<u8>> {
+    let mut data = Vec::new();
+    item.read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn check_loadable(result: ReadResult<()>) -> ReadResult<bool> {
+    match result {
+        Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+        Err(error) => Err(error),
+        Ok(()) => Ok(true),
+    }
+}
+
+#[derive(Default)]
+pub struct JsonPatternReader {}
+
+impl PatternReader for JsonPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> ReadResult<bool> {
+        // The whole document has to be read to find the fields, as they could be in any order.
+        let data = read_all(item)?;
+        check_loadable(parse::<PatternProbe>(&data).and_then(|probe| probe.header.check()))
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> ReadResult<Pattern> {
+        let data = read_all(item)?;
+        parse::<DocumentHeader>(&data)?.check()?;
+        let document: PatternDocument = parse(&data)?;
+        Ok(document.pattern)
+    }
+}
+
+#[derive(Default)]
+pub struct JsonCollectionReader {}
+
+impl CollectionReader for JsonCollectionReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> ReadResult<bool> {
+        let data = read_all(item)?;
+        check_loadable(parse::<CollectionProbe>(&data).and_then(|probe| probe.header.check()))
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> ReadResult<PatternCollection> {
+        let data = read_all(item)?;
+        parse::<DocumentHeader>(&data)?.check()?;
+        let document: CollectionDocument = parse(&data)?;
+        Ok(document.collection)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATTERN: &[u8] = br##"{
+        "format": "embroidery-rust",
+        "version": 1,
+        "pattern": {
+            "name": "Test",
+            "attributes": [{"Title": "Test"}, {"Arbitrary": ["key", "value"]}],
+            "color_groups": [{
+                "thread": {
+                    "color": {"red": 255, "green": 0, "blue": 0},
+                    "name": "Red",
+                    "code": "1147",
+                    "manufacturer": null,
+                    "attributes": {"weight": "40"}
+                },
+                "stitch_groups": [{
+                    "stitches": [{"x": 0.0, "y": 0.0}, {"x": 1.5, "y": -2.0}],
+                    "trim": true,
+                    "cut": false
+                }]
+            }]
+        }
+    }"##;
+
+    #[test]
+    fn test_read_pattern() {
+        assert!(JsonPatternReader::default().is_loadable(&mut &PATTERN[..]).unwrap());
+        assert!(!JsonCollectionReader::default().is_loadable(&mut &PATTERN[..]).unwrap());
+
+        let pattern = JsonPatternReader::default().read_pattern(&mut &PATTERN[..]).unwrap();
+        assert_eq!(pattern.name, "Test");
+        assert_eq!(
+            pattern.attributes,
+            vec![
+                PatternAttribute::Title("Test".to_string()),
+                PatternAttribute::Arbitary("key".to_string(), "value".to_string()),
+            ]
+        );
+        let mut thread = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"1147");
+        thread.attributes.insert("weight".to_string(), "40".to_string());
+        assert_eq!(
+            pattern.color_groups,
+            vec![ColorGroup {
+                thread: Some(thread),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.5, -2.0)]).with_trim(true)
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_not_loadable() {
+        let reader = JsonPatternReader::default();
+        assert!(!reader.is_loadable(&mut &b"LA:Not JSON"[..]).unwrap());
+        assert!(!reader.is_loadable(&mut &br#"{"pattern": {}}"#[..]).unwrap());
+        assert!(!reader
+            .is_loadable(&mut &br#"{"format": "other", "version": 1, "pattern": {}}"#[..])
+            .unwrap());
+        let future = br#"{"format": "embroidery-rust", "version": 2, "pattern": {}}"#;
+        assert!(!reader.is_loadable(&mut &future[..]).unwrap());
+        assert!(reader.read_pattern(&mut &future[..]).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+
+use embroidery_lib::errors::WriteResult;
+use embroidery_lib::format::{CollectionWriter, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use crate::document::{CollectionDocument, PatternDocument, FORMAT, VERSION};
+
+#[derive(Default)]
+pub struct JsonPatternWriter {}
+
+impl PatternWriter for JsonPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> WriteResult<()> {
+        let document = PatternDocument {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            pattern: pattern.clone(),
+        };
+        serde_json::to_writer_pretty(writer, &document).map_err(io::Error::from)?;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct JsonCollectionWriter {}
+
+impl CollectionWriter for JsonCollectionWriter {
+    fn write_pattern(&self, collection: &PatternCollection, writer: &mut dyn Write) -> WriteResult<()> {
+        let document = CollectionDocument {
+            format: FORMAT.to_string(),
+            version: VERSION,
+            collection: collection.clone(),
+        };
+        serde_json::to_writer_pretty(writer, &document).map_err(io::Error::from)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::{JsonCollectionReader, JsonPatternReader};
+    use embroidery_lib::format::{CollectionReader, PatternReader};
+
+    fn pattern() -> Pattern {
+        let mut thread = Thread::new_str(Color::rgb(0, 0x43, 0xb1), &"Soldier Blue", &"2527");
+        thread.manufacturer = Some("Robison-Anton".to_string());
+        thread.attributes.insert("weight".to_string(), "40".to_string());
+        Pattern {
+            name: "Ünïcode".to_string(),
+            attributes: vec![
+                PatternAttribute::Title("Ünïcode".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Copyright("2020".to_string()),
+                PatternAttribute::Arbitary("notes".to_string(), "Line 1\nLine 2".to_string()),
+            ],
+            color_groups: vec![
+                ColorGroup {
+                    thread: Some(thread),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0.1, 0.2), Stitch::new(-1.25, 1e-3)])
+                            .with_trim(true)
+                            .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(30.0, -1.0)]),
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(1.0 / 3.0, 2.0 / 3.0)]).with_trim(true)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_collection_roundtrip() {
+        let collection: PatternCollection = vec![("a".to_string(), pattern()), ("b".to_string(), pattern())]
+            .into_iter()
+            .collect();
+        let mut data = vec![];
+        JsonCollectionWriter::default()
+            .write_pattern(&collection, &mut data)
+            .unwrap();
+        assert!(JsonCollectionReader::default().is_loadable(&mut &data[..]).unwrap());
+        assert!(!JsonPatternReader::default().is_loadable(&mut &data[..]).unwrap());
+        let read = JsonCollectionReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(read, collection);
+    }
+}
@@ -0,0 +1,22 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_json::{JsonPatternReader, JsonPatternWriter};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip() {
+    let pattern = load_sample();
+    let mut data = Vec::new();
+    JsonPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+    let loader = JsonPatternReader::default();
+    assert!(loader.is_loadable(&mut Cursor::new(&data)).unwrap());
+    assert_eq!(loader.read_pattern(&mut Cursor::new(&data)).unwrap(), pattern);
+}
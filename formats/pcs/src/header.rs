@@ -0,0 +1,83 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
+
+use crate::hoops::PcsHoop;
+
+pub const VERSION: u8 = 0x32;
+pub const MAX_COLORS: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternHeader {
+    pub hoop: PcsHoop,
+    /// Each color is stored as `red, green, blue, 0x00`.
+    pub colors: Vec<Color>,
+    pub number_of_stitches: u16,
+}
+
+impl PatternHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let version = read_int!(file, u8)?;
+        if version != VERSION {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown version 0x{:02X}; expected 0x{:02X}",
+                version, VERSION
+            )));
+        }
+        let hoop = PcsHoop::from_byte(read_int!(file, u8)?);
+        let number_of_colors = usize::from(read_int!(file, u16, LittleEndian)?);
+        if number_of_colors > MAX_COLORS {
+            return Err(ReadError::invalid_format(format!(
+                "Too many colors({}); the format supports at most {}",
+                number_of_colors, MAX_COLORS
+            )));
+        }
+        let mut colors = Vec::with_capacity(number_of_colors);
+        for _ in 0..number_of_colors {
+            let [red, green, blue, _] = read_exact!(file, [_; 4])?;
+            colors.push(Color::rgb(red, green, blue));
+        }
+        let number_of_stitches = read_int!(file, u16, LittleEndian)?;
+        Ok(PatternHeader {
+            hoop,
+            colors,
+            number_of_stitches,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_all(&[VERSION, self.hoop.to_byte()])?;
+        file.write_u16::<LittleEndian>(self.colors.len() as u16)?;
+        for color in &self.colors {
+            file.write_all(&[color.red, color.green, color.blue, 0x00])?;
+        }
+        file.write_u16::<LittleEndian>(self.number_of_stitches)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = PatternHeader {
+            hoop: PcsHoop::Hoop115x120,
+            colors: vec![Color::rgb(1, 2, 3), Color::rgb(4, 5, 6)],
+            number_of_stitches: 0x1234,
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(data, vec![0x32, 0x03, 0x02, 0x00, 1, 2, 3, 0, 4, 5, 6, 0, 0x34, 0x12]);
+        assert_eq!(PatternHeader::build(&mut &data[..]).unwrap(), header);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(PatternHeader::build(&mut &[0x31, 0x02, 0x00, 0x00, 0x00, 0x00][..]).is_err());
+        assert!(PatternHeader::build(&mut &[0x32, 0x02, 0x11, 0x00, 0x00, 0x00][..]).is_err());
+    }
+}
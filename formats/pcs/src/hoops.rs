@@ -0,0 +1,69 @@
+const HOOP_80X80: u8 = 2;
+const HOOP_115X120: u8 = 3;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PcsHoop {
+    Hoop80x80,
+    Hoop115x120,
+    Other(u8),
+}
+
+impl PcsHoop {
+    pub fn from_byte(hoop_code: u8) -> Self {
+        match hoop_code {
+            HOOP_80X80 => PcsHoop::Hoop80x80,
+            HOOP_115X120 => PcsHoop::Hoop115x120,
+            other => PcsHoop::Other(other),
+        }
+    }
+    pub fn to_byte(self) -> u8 {
+        match self {
+            PcsHoop::Hoop80x80 => HOOP_80X80,
+            PcsHoop::Hoop115x120 => HOOP_115X120,
+            PcsHoop::Other(code) => code,
+        }
+    }
+    pub fn hoop_size(self) -> Option<(f64, f64)> {
+        match self {
+            PcsHoop::Hoop80x80 => Some((80.0, 80.0)),
+            PcsHoop::Hoop115x120 => Some((115.0, 120.0)),
+            _ => None,
+        }
+    }
+    pub fn name(self) -> String {
+        match self.hoop_size() {
+            Some((width, height)) => format!("{}x{}", width, height),
+            None => format!("Unknown({})", self.to_byte()),
+        }
+    }
+
+    /// The smaller hoop if a design of `width` by `height` mm will fit in it; otherwise the larger
+    /// one.
+    pub fn smallest_fitting(width: f64, height: f64) -> Self {
+        if width <= 80. && height <= 80. {
+            PcsHoop::Hoop80x80
+        } else {
+            PcsHoop::Hoop115x120
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_byte_roundtrip() {
+        for code in 0..5 {
+            assert_eq!(PcsHoop::from_byte(code).to_byte(), code);
+        }
+    }
+
+    #[test]
+    fn test_smallest_fitting() {
+        assert_eq!(PcsHoop::smallest_fitting(0., 0.), PcsHoop::Hoop80x80);
+        assert_eq!(PcsHoop::smallest_fitting(80., 80.), PcsHoop::Hoop80x80);
+        assert_eq!(PcsHoop::smallest_fitting(80.1, 10.), PcsHoop::Hoop115x120);
+        assert_eq!(PcsHoop::smallest_fitting(500., 500.), PcsHoop::Hoop115x120);
+    }
+}
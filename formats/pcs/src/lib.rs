@@ -0,0 +1,32 @@
+mod header;
+mod hoops;
+mod read;
+mod stitch_info;
+mod write;
+
+pub use hoops::PcsHoop;
+pub use read::PcsPatternReader;
+pub use write::PcsPatternWriter;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+const NAME: &str = "pcs";
+const EXTENSIONS: [&str; 1] = ["pcs"];
+
+#[derive(Default)]
+pub struct PcsPatternFormat {}
+
+impl PatternFormat for PcsPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(PcsPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(PcsPatternWriter::default()))
+    }
+}
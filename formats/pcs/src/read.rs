@@ -0,0 +1,155 @@
+use std::io::Read;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::ReadByteIterator;
+use embroidery_lib::utils::StitchGrouper;
+
+use crate::header::PatternHeader;
+use crate::stitch_info::{read_stitch, StitchInformation, StitchType};
+
+/// The file stores coordinates in sixths of a millimetre.
+pub const UNITS_PER_MM: f64 = 6.;
+
+#[derive(Default)]
+pub struct PcsPatternReader {}
+
+impl PatternReader for PcsPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        match PatternHeader::build(item) {
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let header = PatternHeader::build(item)?;
+        let threads: Vec<Thread> = header
+            .colors
+            .iter()
+            .map(|&color| Thread::new(color, color.to_string(), String::new()))
+            .collect();
+
+        let mut iter = ReadByteIterator::new(item);
+        let color_groups = read_stitches(&threads, usize::from(header.number_of_stitches), &mut iter)?;
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![PatternAttribute::Arbitary("hoop".to_string(), header.hoop.name())],
+            color_groups,
+        })
+    }
+}
+
+fn to_stitch(x: i32, y: i32) -> Stitch {
+    Stitch::new(f64::from(x) / UNITS_PER_MM, -f64::from(y) / UNITS_PER_MM)
+}
+
+fn read_stitches(
+    threads: &[Thread],
+    number_of_stitches: usize,
+    item: &mut dyn Iterator<Item = u8>,
+) -> ReadResult<Vec<ColorGroup>> {
+    let mut thread_iter = threads.iter();
+    let mut grouper = StitchGrouper::new();
+
+    for _ in 0..number_of_stitches {
+        let StitchInformation(x, y, stitch_type) = match read_stitch(item)? {
+            Some(stitch) => stitch,
+            None => break,
+        };
+        match stitch_type {
+            StitchType::Regular => grouper.stitch(to_stitch(x, y)),
+            StitchType::Jump => grouper.jump(to_stitch(x, y)),
+            StitchType::ColorChange => {
+                // Each color change moves onto the next thread, even if nothing was sewn with the
+                // current one.
+                grouper.end_color(thread_iter.next().cloned());
+                grouper.jump(to_stitch(x, y));
+            },
+        }
+    }
+    Ok(grouper.finish(thread_iter.next().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stitch_bytes(stitches: &[StitchInformation]) -> Vec<u8> {
+        stitches.iter().flat_map(|st| st.to_bytes().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"#FF0000", &"");
+        let blue = Thread::new_str(Color::rgb(0, 0, 240), &"#0000F0", &"");
+        let data = stitch_bytes(&[
+            StitchInformation(6, 0, StitchType::Regular),
+            StitchInformation(6, -6, StitchType::Regular),
+            StitchInformation(18, -6, StitchType::Jump),
+            StitchInformation(18, 0, StitchType::Regular),
+            StitchInformation(12, 0, StitchType::ColorChange),
+            StitchInformation(18, -6, StitchType::Regular),
+            // Past the end of the stitch count.
+            StitchInformation(0, 0, StitchType::Regular),
+        ]);
+        let color_groups = read_stitches(&[red.clone(), blue.clone()], 6, &mut data.into_iter()).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(blue),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_pattern() {
+        let mut data = vec![0x32, 0x02, 0x01, 0x00, 0x12, 0x34, 0x56, 0x00, 0x01, 0x00];
+        data.extend(stitch_bytes(&[StitchInformation(6, 0, StitchType::Regular)]));
+
+        let pattern = PcsPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(
+            pattern.attributes,
+            vec![PatternAttribute::Arbitary("hoop".to_string(), "80x80".to_string())]
+        );
+        assert_eq!(pattern.color_groups.len(), 1);
+        let thread = pattern.color_groups[0].thread.as_ref().unwrap();
+        assert_eq!(thread.color, Color::rgb(0x12, 0x34, 0x56));
+        assert_eq!(thread.name, "#123456");
+        assert_eq!(
+            pattern.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = PcsPatternReader::default();
+        assert!(!reader
+            .is_loadable(&mut &b"LA:crown FS 40     \rST:   4562\r"[..])
+            .unwrap());
+    }
+}
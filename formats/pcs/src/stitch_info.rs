@@ -0,0 +1,132 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// Every stitch is 9 bytes: `0x00, x, 0x00, y, flags`; where `x` and `y` are absolute 24 bit
+// signed little endian values, with `y` increasing downwards.
+pub const STITCH_LEN: usize = 9;
+pub const COLOR_CHANGE: u8 = 0x01;
+pub const JUMP: u8 = 0x04;
+
+pub const MAX_COORD: i32 = (1 << 23) - 1;
+pub const MIN_COORD: i32 = -(1 << 23);
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    ColorChange,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StitchInformation(pub i32, pub i32, pub StitchType);
+
+pub fn fits_coord(value: i32) -> bool {
+    (MIN_COORD..=MAX_COORD).contains(&value)
+}
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        let StitchInformation(x, y, stitch_type) = self;
+        if !fits_coord(x) || !fits_coord(y) {
+            return None;
+        }
+        let flags = match stitch_type {
+            StitchType::Regular => 0x00,
+            StitchType::Jump => JUMP,
+            StitchType::ColorChange => COLOR_CHANGE,
+        };
+        let mut bytes = Vec::with_capacity(STITCH_LEN);
+        bytes.push(0x00);
+        bytes.extend_from_slice(&x.to_le_bytes()[..3]);
+        bytes.push(0x00);
+        bytes.extend_from_slice(&y.to_le_bytes()[..3]);
+        bytes.push(flags);
+        Some(bytes)
+    }
+}
+
+fn from_i24(bytes: &[u8]) -> i32 {
+    // Shift the value into the top of an i32 so that the sign gets extended on the way back down.
+    i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8
+}
+
+pub fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let bytes: Vec<u8> = in_bytes.take(STITCH_LEN).collect();
+    if bytes.is_empty() {
+        return Ok(None);
+    }
+    if bytes.len() < STITCH_LEN {
+        return Err(ReadError::invalid_format("Stitch data ends part way through a stitch"));
+    }
+    let (x, y) = (from_i24(&bytes[1..4]), from_i24(&bytes[5..8]));
+    let stitch_type = match bytes[8] {
+        0x00 => StitchType::Regular,
+        flags if flags & COLOR_CHANGE != 0 => StitchType::ColorChange,
+        flags if flags & JUMP != 0 => StitchType::Jump,
+        flags => {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown stitch flags 0x{:02X}",
+                flags
+            )))
+        },
+    };
+    Ok(Some(StitchInformation(x, y, stitch_type)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        assert_eq!(
+            StitchInformation(1, -2, StitchType::Regular).to_bytes(),
+            Some(vec![0x00, 0x01, 0x00, 0x00, 0x00, 0xFE, 0xFF, 0xFF, 0x00])
+        );
+        assert_eq!(
+            StitchInformation(0x123456, 0, StitchType::Jump).to_bytes(),
+            Some(vec![0x00, 0x56, 0x34, 0x12, 0x00, 0x00, 0x00, 0x00, 0x04])
+        );
+        assert_eq!(
+            StitchInformation(MAX_COORD + 1, 0, StitchType::Regular).to_bytes(),
+            None
+        );
+        assert_eq!(
+            StitchInformation(0, MIN_COORD - 1, StitchType::Regular).to_bytes(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(read_stitch(to_u8_iter!(b"")).unwrap(), None);
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x00, 0x01, 0x00, 0x00, 0x00, 0xFE, 0xFF, 0xFF, 0x00])).unwrap(),
+            Some(StitchInformation(1, -2, StitchType::Regular))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x00, 0x00, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00, 0x01])).unwrap(),
+            Some(StitchInformation(MIN_COORD, 0, StitchType::ColorChange))
+        );
+        assert!(read_stitch(to_u8_iter!([0x00, 0x01, 0x00])).is_err());
+        assert!(read_stitch(to_u8_iter!([0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10])).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for &stitch in &[
+            StitchInformation(MAX_COORD, MIN_COORD, StitchType::Regular),
+            StitchInformation(-1, 1, StitchType::Jump),
+            StitchInformation(0, 0, StitchType::ColorChange),
+        ] {
+            let data = stitch.to_bytes().unwrap();
+            assert_eq!(read_stitch(to_u8_iter!(data)).unwrap(), Some(stitch));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::io::Write;
+
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::thread_colors;
+
+use crate::header::{PatternHeader, MAX_COLORS};
+use crate::hoops::PcsHoop;
+use crate::read::UNITS_PER_MM;
+use crate::stitch_info::{fits_coord, StitchInformation, StitchType};
+
+#[derive(Default)]
+pub struct PcsPatternWriter {}
+
+impl PatternWriter for PcsPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        if pattern.color_groups.len() > MAX_COLORS {
+            return Err(WriteError::too_many_colors(pattern.color_groups.len(), MAX_COLORS));
+        }
+        let stitches = into_pcs_stitches(pattern)?;
+        if stitches.len() > usize::from(u16::MAX) {
+            return Err(WriteError::unsupported_stitch_msg(
+                Stitch::zero(),
+                Some(stitches.len()),
+                format!("The format only supports {} stitches", u16::MAX),
+            ));
+        }
+
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        let header = PatternHeader {
+            hoop: PcsHoop::smallest_fitting(max_x - min_x, max_y - min_y),
+            colors: thread_colors(pattern),
+            number_of_stitches: stitches.len() as u16,
+        };
+        header.write(writer)?;
+        for &st in &stitches {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&st.to_bytes().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+fn to_pcs_coord(value: f64) -> i32 {
+    (value * UNITS_PER_MM).round() as i32
+}
+
+/// The format has no way to cut the thread; so cuts are dropped, leaving plain jumps between the
+/// stitch groups.
+fn into_pcs_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            re.push(StitchInformation(ox, oy, StitchType::ColorChange));
+        }
+        for sg in &cg.stitch_groups {
+            for (j, s) in sg.stitches.iter().enumerate() {
+                // The y axis points down in the file.
+                let (x, y) = (to_pcs_coord(s.x), -to_pcs_coord(s.y));
+                if !fits_coord(x) || !fits_coord(y) {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch is too far from the origin for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                if j == 0 {
+                    re.push(StitchInformation(x, y, StitchType::Jump));
+                } else {
+                    re.push(StitchInformation(x, y, StitchType::Regular));
+                    idx += 1;
+                }
+            }
+        }
+    }
+    Ok(re)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_pcs_stitches() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.5)]).with_cut(true)
+                    ],
+                },
+                ColorGroup {
+                    thread: None,
+                    stitch_groups: vec![StitchGroup::new(vec![Stitch::new(30.0, -1.0), Stitch::new(30.5, -2.5)])],
+                },
+            ],
+        };
+        assert_eq!(
+            into_pcs_stitches(&pattern).unwrap(),
+            vec![
+                StitchInformation(0, 0, StitchType::Jump),
+                StitchInformation(6, -3, StitchType::Regular),
+                StitchInformation(6, -3, StitchType::ColorChange),
+                StitchInformation(180, 6, StitchType::Jump),
+                StitchInformation(183, 15, StitchType::Regular),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_too_many_colors() {
+        let color_group = ColorGroup {
+            thread: None,
+            stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0)])],
+        };
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![color_group; MAX_COLORS + 1],
+        };
+        match PcsPatternWriter::default().write_pattern(&pattern, &mut vec![]) {
+            Err(WriteError::TooManyColors { count, max, ctx: _ }) => {
+                assert_eq!((count, max), (MAX_COLORS + 1, MAX_COLORS))
+            },
+            res => panic!("Expected too many colors; got {:?}", res),
+        }
+    }
+}
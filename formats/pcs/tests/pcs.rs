@@ -0,0 +1,40 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_pcs::{PcsPatternReader, PcsPatternWriter};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip() {
+    let pattern = load_sample();
+    let mut data = Vec::new();
+    PcsPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+    let loader = PcsPatternReader::default();
+    assert!(loader.is_loadable(&mut Cursor::new(&data)).unwrap());
+    let read = loader.read_pattern(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(read.color_groups.len(), 3);
+    for (read_cg, cg) in read.color_groups.iter().zip(&pattern.color_groups) {
+        assert_eq!(
+            read_cg.thread.as_ref().unwrap().color,
+            cg.thread.as_ref().unwrap().color
+        );
+        assert_eq!(read_cg.stitch_groups.len(), cg.stitch_groups.len());
+        for (read_sg, sg) in read_cg.stitch_groups.iter().zip(&cg.stitch_groups) {
+            // Cuts are dropped.
+            assert!(read_sg.trim && !read_sg.cut);
+            assert_eq!(read_sg.stitches.len(), sg.stitches.len());
+            // Stitches are stored in 1/6mm; so they move by up to 1/12mm.
+            for (read_s, s) in read_sg.stitches.iter().zip(&sg.stitches) {
+                assert!((read_s.x - s.x).abs() <= 1. / 12. && (read_s.y - s.y).abs() <= 1. / 12.);
+            }
+        }
+    }
+}
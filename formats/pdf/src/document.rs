@@ -0,0 +1,281 @@
+//! Just enough of PDF to draw lines, boxes and text; using the fonts every PDF reader has.
+
+use std::io::{self, Write};
+
+use embroidery_lib::prelude::*;
+
+pub const POINTS_PER_MM: f64 = 72. / 25.4;
+
+/// Format a number with at most 3 decimal places; without trailing zeros.
+pub fn num(value: f64) -> String {
+    let s = format!("{:.3}", value);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// A PDF string; in the WinAnsi encoding the standard fonts use, with `?` for anything else.
+pub fn pdf_string(text: &str) -> Vec<u8> {
+    let mut re = vec![b'('];
+    for c in text.chars() {
+        let byte = match c as u32 {
+            0x20..=0x7E | 0xA0..=0xFF => c as u32 as u8,
+            _ => b'?',
+        };
+        if byte == b'(' || byte == b')' || byte == b'\\' {
+            re.push(b'\\');
+        }
+        re.push(byte);
+    }
+    re.push(b')');
+    re
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "/F1",
+            Font::Bold => "/F2",
+        }
+    }
+}
+
+/// A page being drawn on; all positions are in mm from the bottom left corner.
+pub struct Page {
+    pub width: f64,
+    pub height: f64,
+    content: Vec<u8>,
+}
+
+impl Page {
+    pub fn new(width: f64, height: f64) -> Self {
+        let mut content = Vec::new();
+        // Draw in mm rather than points.
+        writeln!(content, "{} 0 0 {} 0 0 cm", num(POINTS_PER_MM), num(POINTS_PER_MM)).unwrap();
+        Page { width, height, content }
+    }
+
+    fn op(&mut self, op: &str) {
+        self.content.extend_from_slice(op.as_bytes());
+        self.content.push(b'\n');
+    }
+
+    /// `size` is in points, as fonts usually are.
+    pub fn text(&mut self, x: f64, y: f64, size: f64, font: Font, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.op(&format!(
+            "BT {} {} Tf {} {} Td",
+            font.resource(),
+            num(size / POINTS_PER_MM),
+            num(x),
+            num(y)
+        ));
+        self.content.extend_from_slice(&pdf_string(text));
+        self.op(" Tj ET");
+    }
+
+    pub fn fill_color(&mut self, color: Color) {
+        self.op(&format!(
+            "{} {} {} rg",
+            num(f64::from(color.red) / 255.),
+            num(f64::from(color.green) / 255.),
+            num(f64::from(color.blue) / 255.)
+        ));
+    }
+
+    pub fn stroke_color(&mut self, color: Color) {
+        self.op(&format!(
+            "{} {} {} RG",
+            num(f64::from(color.red) / 255.),
+            num(f64::from(color.green) / 255.),
+            num(f64::from(color.blue) / 255.)
+        ));
+    }
+
+    /// Set the line width, and make the lines solid or dashed.
+    pub fn line_style(&mut self, width: f64, dash: Option<f64>) {
+        match dash {
+            Some(dash) => self.op(&format!("{} w 1 J 1 j [{} {}] 0 d", num(width), num(dash), num(dash))),
+            None => self.op(&format!("{} w 1 J 1 j [] 0 d", num(width))),
+        }
+    }
+
+    pub fn rect(&mut self, x: f64, y: f64, width: f64, height: f64, fill: bool, stroke: bool) {
+        let paint = match (fill, stroke) {
+            (true, true) => "B",
+            (true, false) => "f",
+            (false, true) => "S",
+            (false, false) => "n",
+        };
+        self.op(&format!(
+            "{} {} {} {} re {}",
+            num(x),
+            num(y),
+            num(width),
+            num(height),
+            paint
+        ));
+    }
+
+    /// Outline a rectangle with rounded corners.
+    pub fn rounded_rect(&mut self, x: f64, y: f64, width: f64, height: f64, radius: f64) {
+        let r = radius.min(width / 2.).min(height / 2.);
+        // How far along the tangent the bezier control points go for a quarter circle.
+        let k = r * 0.552_284_75;
+        let (x1, y1) = (x + width, y + height);
+        self.op(&format!("{} {} m", num(x + r), num(y)));
+        self.op(&format!("{} {} l", num(x1 - r), num(y)));
+        self.op(&format!(
+            "{} {} {} {} {} {} c",
+            num(x1 - r + k),
+            num(y),
+            num(x1),
+            num(y + r - k),
+            num(x1),
+            num(y + r)
+        ));
+        self.op(&format!("{} {} l", num(x1), num(y1 - r)));
+        self.op(&format!(
+            "{} {} {} {} {} {} c",
+            num(x1),
+            num(y1 - r + k),
+            num(x1 - r + k),
+            num(y1),
+            num(x1 - r),
+            num(y1)
+        ));
+        self.op(&format!("{} {} l", num(x + r), num(y1)));
+        self.op(&format!(
+            "{} {} {} {} {} {} c",
+            num(x + r - k),
+            num(y1),
+            num(x),
+            num(y1 - r + k),
+            num(x),
+            num(y1 - r)
+        ));
+        self.op(&format!("{} {} l", num(x), num(y + r)));
+        self.op(&format!(
+            "{} {} {} {} {} {} c",
+            num(x),
+            num(y + r - k),
+            num(x + r - k),
+            num(y),
+            num(x + r),
+            num(y)
+        ));
+        self.op("S");
+    }
+
+    /// Stroke a line through the points.
+    pub fn polyline(&mut self, points: &[(f64, f64)]) {
+        let mut iter = points.iter();
+        if let Some((x, y)) = iter.next() {
+            self.op(&format!("{} {} m", num(*x), num(*y)));
+            if points.len() == 1 {
+                // A zero length line still shows up, thanks to the round ends.
+                self.op(&format!("{} {} l", num(*x), num(*y)));
+            }
+            for (x, y) in iter {
+                self.op(&format!("{} {} l", num(*x), num(*y)));
+            }
+            self.op("S");
+        }
+    }
+}
+
+/// Write the pages as a PDF, with `title` shown in the reader's title bar.
+pub fn write_document(pages: &[Page], title: &str, writer: &mut dyn Write) -> io::Result<()> {
+    // Objects 1 to 5 are fixed; then each page has its own page object and content stream.
+    let mut objects: Vec<Vec<u8>> = Vec::new();
+    let kids: Vec<String> = (0..pages.len()).map(|idx| format!("{} 0 R", 6 + idx * 2)).collect();
+    objects.push(b"<< /Type /Catalog /Pages 2 0 R >>".to_vec());
+    objects.push(format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), pages.len()).into_bytes());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec());
+    objects.push(b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec());
+    let mut info = b"<< /Producer (embroidery-rust) /Title ".to_vec();
+    info.extend_from_slice(&pdf_string(title));
+    info.extend_from_slice(b" >>");
+    objects.push(info);
+    for (idx, page) in pages.iter().enumerate() {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> >> /Contents {} 0 R >>",
+                num(page.width * POINTS_PER_MM),
+                num(page.height * POINTS_PER_MM),
+                7 + idx * 2
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} >>\nstream\n", page.content.len()).into_bytes();
+        stream.extend_from_slice(&page.content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    // The binary comment tells tools the file isn't plain text.
+    let mut data = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (idx, object) in objects.iter().enumerate() {
+        offsets.push(data.len());
+        writeln!(data, "{} 0 obj", idx + 1)?;
+        data.extend_from_slice(object);
+        data.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = data.len();
+    write!(data, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1)?;
+    for offset in offsets {
+        writeln!(data, "{:010} 00000 n ", offset)?;
+    }
+    write!(
+        data,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 5 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    )?;
+    writer.write_all(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pdf_string() {
+        assert_eq!(pdf_string("a (b) \\"), b"(a \\(b\\) \\\\)".to_vec());
+        assert_eq!(pdf_string("Ünï✓"), b"(\xDCn\xEF?)".to_vec());
+    }
+
+    #[test]
+    fn test_xref() {
+        let mut page = Page::new(210., 297.);
+        page.text(10., 10., 12., Font::Bold, "Hello");
+        let mut data = vec![];
+        write_document(&[page, Page::new(100., 100.)], "Test", &mut data).unwrap();
+        // The binary comment isn't UTF-8; so only look at the text after it.
+        let header = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n";
+        assert!(data.starts_with(header));
+        let text = String::from_utf8(data[header.len()..].to_vec()).unwrap();
+        let at = |offset: usize| &text[offset - header.len()..];
+        assert!(text.contains("/Count 2"));
+        assert!(text.contains("(Hello) Tj ET"));
+
+        let start: usize = text.lines().rev().nth(1).unwrap().parse().unwrap();
+        assert!(at(start).starts_with("xref\n0 10\n"));
+        for (idx, line) in at(start).lines().skip(3).take(9).enumerate() {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(at(offset).starts_with(&format!("{} 0 obj\n", idx + 1)));
+        }
+    }
+}
@@ -0,0 +1,27 @@
+mod document;
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use self::write::{PdfPatternWriter, PdfWriterOptions, HOOPS};
+
+const NAME: &str = "pdf";
+const EXTENSIONS: [&str; 1] = ["pdf"];
+
+#[derive(Default)]
+pub struct PdfPatternFormat {}
+
+impl PatternFormat for PdfPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        None
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(PdfPatternWriter::default()))
+    }
+}
@@ -0,0 +1,340 @@
+use std::io::Write;
+
+use embroidery_lib::errors::WriteResult as Result;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{char_truncate, thread_colors};
+
+use crate::document::{num, write_document, Font, Page};
+
+/// Common hoop sizes as (width, height) in mm; the worksheet uses the smallest that fits.
+pub const HOOPS: [(f64, f64); 6] = [
+    (100., 100.),
+    (130., 180.),
+    (150., 240.),
+    (200., 200.),
+    (200., 300.),
+    (360., 200.),
+];
+
+const A4: (f64, f64) = (210., 297.);
+const MARGIN: f64 = 15.;
+const ROW_HEIGHT: f64 = 7.;
+const LINE_HEIGHT: f64 = 6.;
+const HOOP_CORNER_RADIUS: f64 = 10.;
+/// The length of the line used to check the preview was printed at the right size.
+const SCALE_BAR: f64 = 50.;
+const GUIDE_COLOR: Color = Color::rgb(0x80, 0x80, 0x80);
+const TEXT_COLOR: Color = Color::rgb(0, 0, 0);
+
+/// Controls the worksheet; sizes are in mm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfWriterOptions {
+    /// The hoop drawn around the pattern; or `None` to use the smallest of `HOOPS` that fits.
+    pub hoop: Option<(f64, f64)>,
+    /// How fast the machine sews; for working out how long the pattern takes.
+    pub stitches_per_minute: f64,
+    /// The width of the line drawn for each stitch.
+    pub line_width: f64,
+}
+
+impl Default for PdfWriterOptions {
+    fn default() -> Self {
+        PdfWriterOptions {
+            hoop: None,
+            stitches_per_minute: 800.,
+            line_width: 0.3,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PdfPatternWriter {
+    options: PdfWriterOptions,
+}
+
+impl PdfPatternWriter {
+    pub fn new(options: PdfWriterOptions) -> Self {
+        PdfPatternWriter { options }
+    }
+}
+
+impl PatternWriter for PdfPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+        let title = pattern_title(pattern);
+        let hoop = self.options.hoop.unwrap_or_else(|| pick_hoop(pattern));
+        let mut pages = worksheet_pages(pattern, &title, hoop, &self.options);
+        pages.push(preview_page(pattern, &title, hoop, &self.options));
+        write_document(&pages, &title, writer)?;
+        Ok(())
+    }
+}
+
+fn pattern_title(pattern: &Pattern) -> String {
+    pattern
+        .attributes
+        .iter()
+        .filter_map(|attr| match attr {
+            PatternAttribute::Title(title) => Some(title.clone()),
+            _ => None,
+        })
+        .next()
+        .unwrap_or_else(|| pattern.name.clone())
+}
+
+/// The smallest of the common hoops the pattern fits in; or a hoop just bigger than the pattern
+/// if none of them are big enough.
+fn pick_hoop(pattern: &Pattern) -> (f64, f64) {
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    HOOPS
+        .iter()
+        .copied()
+        .find(|&(hoop_width, hoop_height)| width <= hoop_width && height <= hoop_height)
+        .unwrap_or_else(|| {
+            let round_up = |length: f64| ((length + 20.) / 10.).ceil() * 10.;
+            (round_up(width), round_up(height))
+        })
+}
+
+fn format_duration(minutes: f64) -> String {
+    let seconds = (minutes * 60.).round() as u64;
+    if seconds >= 3600 {
+        format!("{} h {:02} min", seconds / 3600, (seconds % 3600) / 60)
+    } else {
+        format!("{} min {:02} s", seconds / 60, seconds % 60)
+    }
+}
+
+/// The summary and thread chart; spread over as many pages as the chart needs.
+fn worksheet_pages(pattern: &Pattern, title: &str, hoop: (f64, f64), options: &PdfWriterOptions) -> Vec<Page> {
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    let total_stitches = pattern.iter_stitches().count();
+    let trims = pattern
+        .color_groups
+        .iter()
+        .flat_map(|cg| &cg.stitch_groups)
+        .filter(|sg| sg.cut)
+        .count();
+    let colors = pattern.color_groups.len();
+
+    let mut page = Page::new(A4.0, A4.1);
+    let mut y = A4.1 - MARGIN - 5.;
+    page.fill_color(TEXT_COLOR);
+    page.text(MARGIN, y, 18., Font::Bold, title);
+    y -= LINE_HEIGHT * 1.5;
+    for attr in &pattern.attributes {
+        let line = match attr {
+            PatternAttribute::Author(author) => format!("Author: {}", author),
+            PatternAttribute::Copyright(copyright) => format!("Copyright: {}", copyright),
+            _ => continue,
+        };
+        page.text(MARGIN, y, 10., Font::Regular, &line);
+        y -= LINE_HEIGHT;
+    }
+    y -= LINE_HEIGHT / 2.;
+
+    let summary = [
+        ("Size", format!("{} x {} mm", num(max_x - min_x), num(max_y - min_y))),
+        ("Stitches", total_stitches.to_string()),
+        ("Colors", colors.to_string()),
+        ("Color changes", colors.saturating_sub(1).to_string()),
+        ("Trims", trims.to_string()),
+        (
+            "Sewing time",
+            format!(
+                "{} at {} stitches/min",
+                format_duration(total_stitches as f64 / options.stitches_per_minute),
+                num(options.stitches_per_minute)
+            ),
+        ),
+        ("Hoop", format!("{} x {} mm", num(hoop.0), num(hoop.1))),
+    ];
+    for (label, value) in &summary {
+        page.text(MARGIN, y, 10., Font::Bold, label);
+        page.text(MARGIN + 35., y, 10., Font::Regular, value);
+        y -= LINE_HEIGHT;
+    }
+    y -= LINE_HEIGHT;
+
+    let mut pages = Vec::new();
+    let chart_header = |page: &mut Page, y: f64, heading: &str| {
+        page.text(MARGIN, y, 12., Font::Bold, heading);
+        let y = y - LINE_HEIGHT * 1.5;
+        for (x, label) in &[
+            (0., "#"),
+            (25., "Name"),
+            (95., "Code"),
+            (125., "Manufacturer"),
+            (165., "Stitches"),
+        ] {
+            page.text(MARGIN + x, y, 9., Font::Bold, label);
+        }
+        y - ROW_HEIGHT
+    };
+    y = chart_header(&mut page, y, "Threads");
+    for (idx, (cg, color)) in pattern.color_groups.iter().zip(thread_colors(pattern)).enumerate() {
+        if y < MARGIN {
+            pages.push(page);
+            page = Page::new(A4.0, A4.1);
+            page.fill_color(TEXT_COLOR);
+            y = chart_header(&mut page, A4.1 - MARGIN - 5., "Threads (continued)");
+        }
+        let stitches: usize = cg.stitch_groups.iter().map(|sg| sg.stitches.len()).sum();
+        let (name, code, manufacturer) = match &cg.thread {
+            Some(thread) => (
+                thread.name.clone(),
+                thread.code.clone(),
+                thread.manufacturer.clone().unwrap_or_default(),
+            ),
+            None => (format!("Any {}", color), String::new(), String::new()),
+        };
+        page.text(MARGIN, y, 9., Font::Regular, &(idx + 1).to_string());
+        page.fill_color(color);
+        page.stroke_color(TEXT_COLOR);
+        page.line_style(0.2, None);
+        page.rect(MARGIN + 8., y - 1., 12., 4.5, true, true);
+        page.fill_color(TEXT_COLOR);
+        page.text(MARGIN + 25., y, 9., Font::Regular, &char_truncate(&name, 40));
+        page.text(MARGIN + 95., y, 9., Font::Regular, &char_truncate(&code, 16));
+        page.text(MARGIN + 125., y, 9., Font::Regular, &char_truncate(&manufacturer, 24));
+        page.text(MARGIN + 165., y, 9., Font::Regular, &stitches.to_string());
+        y -= ROW_HEIGHT;
+    }
+    pages.push(page);
+    pages
+}
+
+/// The pattern at its real size; in the middle of the hoop, with lines through the middle to line
+/// it up on the fabric.
+fn preview_page(pattern: &Pattern, title: &str, hoop: (f64, f64), options: &PdfWriterOptions) -> Page {
+    // Room for the title above the hoop, and the scale below it.
+    let width = A4.0.max(hoop.0 + 2. * MARGIN);
+    let height = A4.1.max(hoop.1 + 2. * MARGIN + 20.);
+    let (cx, cy) = (width / 2., height / 2.);
+    let mut page = Page::new(width, height);
+
+    page.fill_color(TEXT_COLOR);
+    page.text(
+        MARGIN,
+        height - MARGIN - 5.,
+        12.,
+        Font::Bold,
+        &format!("{} (actual size)", title),
+    );
+
+    page.stroke_color(GUIDE_COLOR);
+    page.line_style(0.5, None);
+    page.rounded_rect(cx - hoop.0 / 2., cy - hoop.1 / 2., hoop.0, hoop.1, HOOP_CORNER_RADIUS);
+    page.line_style(0.2, Some(2.));
+    page.polyline(&[(cx - hoop.0 / 2., cy), (cx + hoop.0 / 2., cy)]);
+    page.polyline(&[(cx, cy - hoop.1 / 2.), (cx, cy + hoop.1 / 2.)]);
+
+    page.stroke_color(TEXT_COLOR);
+    page.line_style(0.3, None);
+    page.polyline(&[
+        (MARGIN, MARGIN + 2.),
+        (MARGIN, MARGIN),
+        (MARGIN + SCALE_BAR, MARGIN),
+        (MARGIN + SCALE_BAR, MARGIN + 2.),
+    ]);
+    page.fill_color(TEXT_COLOR);
+    page.text(
+        MARGIN + SCALE_BAR + 3.,
+        MARGIN,
+        9.,
+        Font::Regular,
+        &format!("{} mm; check this is the right length when printed", num(SCALE_BAR)),
+    );
+
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    let (ox, oy) = (cx - (min_x + max_x) / 2., cy - (min_y + max_y) / 2.);
+    page.line_style(options.line_width, None);
+    for (cg, color) in pattern.color_groups.iter().zip(thread_colors(pattern)) {
+        page.stroke_color(color);
+        for sg in &cg.stitch_groups {
+            let points: Vec<_> = sg
+                .stitches
+                .iter()
+                .filter(|s| s.is_valid())
+                .map(|s| (s.x + ox, s.y + oy))
+                .collect();
+            page.polyline(&points);
+        }
+    }
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(colors: usize) -> Pattern {
+        let mut thread = Thread::new_str(Color::rgb(255, 0, 0), &"Red (Bright)", &"1234");
+        thread.manufacturer = Some("Madeira".to_string());
+        let mut color_groups = vec![ColorGroup {
+            thread: Some(thread),
+            stitch_groups: vec![
+                StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(120., 50.)]).with_cut(true),
+                StitchGroup::new(vec![Stitch::new(10., 10.), Stitch::new(11., 10.)]).with_trim(true),
+            ],
+        }];
+        for _ in 1..colors {
+            color_groups.push(ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(5., 5.)])],
+            });
+        }
+        Pattern {
+            name: "Sample".to_string(),
+            attributes: vec![PatternAttribute::Author("Someone".to_string())],
+            color_groups,
+        }
+    }
+
+    fn write(pattern: &Pattern) -> String {
+        let mut data = vec![];
+        PdfPatternWriter::default().write_pattern(pattern, &mut data).unwrap();
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    #[test]
+    fn test_pick_hoop() {
+        assert_eq!(pick_hoop(&pattern(1)), (130., 180.));
+        let mut big = pattern(1);
+        big.color_groups[0].stitch_groups[0].stitches[1] = Stitch::new(370., 42.);
+        assert_eq!(pick_hoop(&big), (390., 70.));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.5), "0 min 30 s");
+        assert_eq!(format_duration(75.25), "1 h 15 min");
+    }
+
+    #[test]
+    fn test_worksheet() {
+        let pdf = write(&pattern(2));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(Sample) Tj"));
+        assert!(pdf.contains("(Author: Someone) Tj"));
+        assert!(pdf.contains("(120 x 50 mm) Tj"));
+        assert!(pdf.contains("(5) Tj"));
+        assert!(pdf.contains("(Color changes) Tj"));
+        assert!(pdf.contains("(0 min 00 s at 800 stitches/min) Tj"));
+        assert!(pdf.contains("(Red \\(Bright\\)) Tj"));
+        assert!(pdf.contains("(Madeira) Tj"));
+        assert!(pdf.contains("(130 x 180 mm) Tj"));
+        // The pattern is drawn at its real size, in the middle of the hoop on an A4 page.
+        assert!(pdf.contains("/MediaBox [0 0 595.276 841.89]"));
+        assert!(pdf.contains("45 123.5 m\n165 173.5 l\nS\n"));
+    }
+
+    #[test]
+    fn test_long_thread_chart() {
+        let pdf = write(&pattern(60));
+        assert!(pdf.contains("/Count 3"));
+        assert!(pdf.contains("(Threads \\(continued\\)) Tj"));
+        assert!(pdf.contains("(60) Tj"));
+    }
+}
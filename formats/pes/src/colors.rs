@@ -0,0 +1,106 @@
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::closest_color;
+
+// These colors are taken from pyembroidery; the code is the index into the palette.
+pub const PEC_THREADS: [(Color, &str, &str); 65] = [
+    (Color::rgb(0, 0, 0), "Unknown", "0"),
+    (Color::rgb(14, 31, 124), "Prussian Blue", "1"),
+    (Color::rgb(10, 85, 163), "Blue", "2"),
+    (Color::rgb(0, 135, 119), "Teal Green", "3"),
+    (Color::rgb(75, 107, 175), "Cornflower Blue", "4"),
+    (Color::rgb(237, 23, 31), "Red", "5"),
+    (Color::rgb(209, 92, 0), "Reddish Brown", "6"),
+    (Color::rgb(145, 54, 151), "Magenta", "7"),
+    (Color::rgb(228, 154, 203), "Light Lilac", "8"),
+    (Color::rgb(145, 95, 172), "Lilac", "9"),
+    (Color::rgb(158, 214, 125), "Mint Green", "10"),
+    (Color::rgb(232, 169, 0), "Deep Gold", "11"),
+    (Color::rgb(254, 186, 53), "Orange", "12"),
+    (Color::rgb(255, 255, 0), "Yellow", "13"),
+    (Color::rgb(112, 188, 31), "Lime Green", "14"),
+    (Color::rgb(186, 152, 0), "Brass", "15"),
+    (Color::rgb(168, 168, 168), "Silver", "16"),
+    (Color::rgb(125, 111, 0), "Russet Brown", "17"),
+    (Color::rgb(255, 255, 179), "Cream Brown", "18"),
+    (Color::rgb(79, 85, 86), "Pewter", "19"),
+    (Color::rgb(0, 0, 0), "Black", "20"),
+    (Color::rgb(11, 61, 145), "Ultramarine", "21"),
+    (Color::rgb(119, 1, 118), "Royal Purple", "22"),
+    (Color::rgb(41, 49, 51), "Dark Gray", "23"),
+    (Color::rgb(42, 19, 1), "Dark Brown", "24"),
+    (Color::rgb(246, 74, 138), "Deep Rose", "25"),
+    (Color::rgb(178, 118, 36), "Light Brown", "26"),
+    (Color::rgb(252, 187, 197), "Salmon Pink", "27"),
+    (Color::rgb(254, 55, 15), "Vermilion", "28"),
+    (Color::rgb(240, 240, 240), "White", "29"),
+    (Color::rgb(106, 28, 138), "Violet", "30"),
+    (Color::rgb(168, 221, 196), "Seacrest", "31"),
+    (Color::rgb(37, 132, 187), "Sky Blue", "32"),
+    (Color::rgb(254, 179, 67), "Pumpkin", "33"),
+    (Color::rgb(255, 243, 107), "Cream Yellow", "34"),
+    (Color::rgb(208, 166, 96), "Khaki", "35"),
+    (Color::rgb(209, 84, 0), "Clay Brown", "36"),
+    (Color::rgb(102, 186, 73), "Leaf Green", "37"),
+    (Color::rgb(19, 74, 70), "Peacock Blue", "38"),
+    (Color::rgb(135, 135, 135), "Gray", "39"),
+    (Color::rgb(216, 204, 198), "Warm Gray", "40"),
+    (Color::rgb(67, 86, 7), "Dark Olive", "41"),
+    (Color::rgb(253, 217, 222), "Flesh Pink", "42"),
+    (Color::rgb(249, 147, 188), "Pink", "43"),
+    (Color::rgb(0, 56, 34), "Deep Green", "44"),
+    (Color::rgb(178, 175, 212), "Lavender", "45"),
+    (Color::rgb(104, 106, 176), "Wisteria Violet", "46"),
+    (Color::rgb(239, 227, 185), "Beige", "47"),
+    (Color::rgb(247, 56, 102), "Carmine", "48"),
+    (Color::rgb(181, 75, 100), "Amber Red", "49"),
+    (Color::rgb(19, 43, 26), "Olive Green", "50"),
+    (Color::rgb(199, 1, 86), "Dark Fuchsia", "51"),
+    (Color::rgb(254, 158, 50), "Tangerine", "52"),
+    (Color::rgb(168, 222, 235), "Light Blue", "53"),
+    (Color::rgb(0, 103, 62), "Emerald Green", "54"),
+    (Color::rgb(78, 41, 144), "Purple", "55"),
+    (Color::rgb(47, 126, 32), "Moss Green", "56"),
+    (Color::rgb(255, 204, 204), "Flesh Pink", "57"),
+    (Color::rgb(255, 217, 17), "Harvest Gold", "58"),
+    (Color::rgb(9, 91, 166), "Electric Blue", "59"),
+    (Color::rgb(240, 249, 112), "Lemon Yellow", "60"),
+    (Color::rgb(227, 243, 91), "Fresh Green", "61"),
+    (Color::rgb(255, 153, 0), "Orange", "62"),
+    (Color::rgb(255, 240, 141), "Cream Yellow", "63"),
+    (Color::rgb(255, 200, 200), "Applique", "64"),
+];
+
+pub fn pec_thread(idx: u8) -> Thread {
+    let (color, name, code) = PEC_THREADS[(idx as usize) % PEC_THREADS.len()];
+    Thread::new_str(color, &name, &code)
+}
+
+/// Find the palette index of the PEC thread closest in color to `thread`.
+pub fn closest_pec_thread(thread: &Thread) -> u8 {
+    // Index 0 is a placeholder for an unknown thread; so never pick it.
+    (closest_color(thread.color, &PEC_THREADS[1..]) + 1) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_pec_thread() {
+        let exact = Thread::new_str(Color::rgb(237, 23, 31), &"Red", &"");
+        assert_eq!(closest_pec_thread(&exact), 5);
+        let near = Thread::new_str(Color::rgb(250, 250, 250), &"Not quite white", &"");
+        assert_eq!(closest_pec_thread(&near), 29);
+        let black = Thread::new_str(Color::rgb(0, 0, 0), &"Black", &"");
+        assert_eq!(closest_pec_thread(&black), 20);
+    }
+
+    #[test]
+    fn test_pec_thread() {
+        assert_eq!(
+            pec_thread(13),
+            Thread::new_str(Color::rgb(255, 255, 0), &"Yellow", &"13")
+        );
+        assert_eq!(pec_thread(65).name, "Unknown");
+    }
+}
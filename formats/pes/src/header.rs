@@ -0,0 +1,296 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::char_truncate;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+
+pub const PES_MAGIC: &[u8; 4] = b"#PES";
+
+// The hoops that version 1 files can choose between; in mm.
+pub const SMALL_HOOP: (u16, u16) = (100, 100);
+pub const LARGE_HOOP: (u16, u16) = (130, 180);
+// Thread types that Brother's software writes for a user defined color.
+const CUSTOM_THREAD_TYPE: u32 = 0x0A;
+const THREAD_CHART: &str = "chart";
+
+/// The descriptive strings stored by version 4 and later.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PesMetadata {
+    pub name: String,
+    pub category: String,
+    pub author: String,
+    pub keywords: String,
+    pub comments: String,
+}
+
+/// The PES specific part of the file; everything a machine needs is in the PEC block it points to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PesHeader {
+    /// The version as written in the file; so `#PES0060` is `60`.
+    pub version: u16,
+    pub pec_offset: u32,
+    /// The width and height of the hoop in mm; when the version records it.
+    pub hoop: Option<(u16, u16)>,
+    pub metadata: PesMetadata,
+    /// The threads used by the design; only stored by version 5 and later.
+    pub threads: Vec<Thread>,
+}
+
+fn read_string(file: &mut dyn Read) -> ReadResult<String> {
+    let len = read_int!(file, u8)?;
+    let data = read_exact!(file, vec![_; usize::from(len)])?;
+    Ok(String::from_utf8_lossy(&data).trim().to_string())
+}
+
+fn write_string(file: &mut dyn Write, value: &str) -> Result<()> {
+    let value = char_truncate(value, u8::MAX as usize);
+    file.write_u8(value.len() as u8)?;
+    file.write_all(value.as_bytes())
+}
+
+fn read_thread(file: &mut dyn Read) -> ReadResult<Thread> {
+    let code = read_string(file)?;
+    let [red, green, blue] = read_exact!(file, [_; 3])?;
+    let _unknown = read_int!(file, u8)?;
+    let _thread_type = read_int!(file, u32, LittleEndian)?;
+    let name = read_string(file)?;
+    let manufacturer = read_string(file)?;
+    let chart = read_string(file)?;
+
+    let mut thread = Thread::new(Color::rgb(red, green, blue), name, code);
+    if !manufacturer.is_empty() {
+        thread.manufacturer = Some(manufacturer);
+    }
+    if !chart.is_empty() {
+        thread.attributes.insert(THREAD_CHART.to_string(), chart);
+    }
+    Ok(thread)
+}
+
+fn write_thread(file: &mut dyn Write, thread: &Thread) -> Result<()> {
+    write_string(file, &thread.code)?;
+    file.write_all(&[thread.color.red, thread.color.green, thread.color.blue])?;
+    file.write_u8(0)?;
+    file.write_u32::<LittleEndian>(CUSTOM_THREAD_TYPE)?;
+    write_string(file, &thread.name)?;
+    write_string(file, thread.manufacturer.as_ref().map_or("", |m| m.as_str()))?;
+    write_string(file, thread.attributes.get(THREAD_CHART).map_or("", |c| c.as_str()))
+}
+
+impl PesMetadata {
+    fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        Ok(PesMetadata {
+            name: read_string(file)?,
+            category: read_string(file)?,
+            author: read_string(file)?,
+            keywords: read_string(file)?,
+            comments: read_string(file)?,
+        })
+    }
+
+    fn write(&self, file: &mut dyn Write) -> Result<()> {
+        write_string(file, &self.name)?;
+        write_string(file, &self.category)?;
+        write_string(file, &self.author)?;
+        write_string(file, &self.keywords)?;
+        write_string(file, &self.comments)
+    }
+}
+
+impl PesHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let magic = read_exact!(file, [_; 4])?;
+        if &magic != PES_MAGIC {
+            return Err(ReadError::invalid_format("File doesn't start with #PES"));
+        }
+        let version_str = read_exact!(file, [_; 4])?;
+        let version = std::str::from_utf8(&version_str)
+            .ok()
+            .and_then(|v| v.parse::<u16>().ok())
+            .ok_or_else(|| ReadError::invalid_format("The PES version isn't a number"))?;
+        let pec_offset = read_int!(file, u32, LittleEndian)?;
+
+        let mut header = PesHeader {
+            version,
+            pec_offset,
+            hoop: None,
+            metadata: PesMetadata::default(),
+            threads: Vec::new(),
+        };
+        match version {
+            1 => {
+                let _scale_to_fit = read_int!(file, u16, LittleEndian)?;
+                header.hoop = Some(match read_int!(file, u16, LittleEndian)? {
+                    0 => SMALL_HOOP,
+                    _ => LARGE_HOOP,
+                });
+            },
+            40 => {
+                read_exact!(file, [_; 4])?;
+                header.metadata = PesMetadata::build(file)?;
+            },
+            50 | 55 | 56 | 60 => {
+                read_exact!(file, [_; 4])?;
+                header.metadata = PesMetadata::build(file)?;
+                let _optimize_hoop_change = read_int!(file, u16, LittleEndian)?;
+                let _custom_page = read_int!(file, u16, LittleEndian)?;
+                header.hoop = Some((read_int!(file, u16, LittleEndian)?, read_int!(file, u16, LittleEndian)?));
+                // Version 6 has 6 more settings for the design page.
+                let settings_len = if version == 60 { 28 } else { 16 };
+                read_exact!(file, vec![_; settings_len])?;
+                let _image_file = read_string(file)?;
+                // The transform applied to the image.
+                read_exact!(file, [_; 24])?;
+
+                let fill_patterns = read_int!(file, u16, LittleEndian)?;
+                let motif_patterns = read_int!(file, u16, LittleEndian)?;
+                let feather_patterns = read_int!(file, u16, LittleEndian)?;
+                // The threads come after these; so they can't be found without knowing the layout
+                // of each pattern.
+                if fill_patterns == 0 && motif_patterns == 0 && feather_patterns == 0 {
+                    let thread_count = read_int!(file, u16, LittleEndian)?;
+                    for _ in 0..thread_count {
+                        header.threads.push(read_thread(file)?);
+                    }
+                }
+            },
+            // The layout of the version 2 & 3 headers isn't documented; but the PEC block has
+            // everything needed to sew the design.
+            20 | 22 | 30 => {},
+            _ => {
+                return Err(ReadError::invalid_format(format!(
+                    "PES version {:04} isn't supported; only versions 1 to 6 are",
+                    version
+                )))
+            },
+        }
+        Ok(header)
+    }
+
+    /// Write the header for version 1 or version 6; which are the only versions supported.
+    ///
+    /// # Errors
+    /// For any other version.
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_all(PES_MAGIC)?;
+        write!(file, "{:04}", self.version)?;
+        file.write_u32::<LittleEndian>(self.pec_offset)?;
+        let hoop = self.hoop.unwrap_or(SMALL_HOOP);
+        match self.version {
+            1 => {
+                // Scale to fit, the hoop and the number of objects that follow.
+                file.write_u16::<LittleEndian>(1)?;
+                file.write_u16::<LittleEndian>(if hoop == SMALL_HOOP { 0 } else { 1 })?;
+                file.write_u16::<LittleEndian>(0)?;
+            },
+            60 => {
+                file.write_u16::<LittleEndian>(1)?;
+                file.write_all(b"02")?;
+                self.metadata.write(file)?;
+                file.write_u16::<LittleEndian>(0)?;
+                file.write_u16::<LittleEndian>(0)?;
+                file.write_u16::<LittleEndian>(hoop.0)?;
+                file.write_u16::<LittleEndian>(hoop.1)?;
+                // The design page settings; copied from files written by Brother's software.
+                for &setting in &[0, 200, 200, 100, 100, 100, 7, 19, 1, 1, 0, 100, 1, 0] {
+                    file.write_u16::<LittleEndian>(setting)?;
+                }
+                // No image, with an identity transform.
+                file.write_u8(0)?;
+                for &value in &[1., 0., 0., 1., 0., 0.] {
+                    file.write_f32::<LittleEndian>(value)?;
+                }
+                // No fill, motif or feather patterns.
+                file.write_all(&[0; 6])?;
+                file.write_u16::<LittleEndian>(self.threads.len() as u16)?;
+                for thread in &self.threads {
+                    write_thread(file, thread)?;
+                }
+                // No objects.
+                file.write_u16::<LittleEndian>(0)?;
+            },
+            version => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    format!("Writing version {:04} PES headers isn't supported", version),
+                ))
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_header(version: u16) -> PesHeader {
+        let mut thread = Thread::new_str(Color::rgb(1, 2, 3), &"Dark", &"123");
+        thread.manufacturer = Some("Brother".to_string());
+        thread.attributes.insert("chart".to_string(), "Embroidery".to_string());
+        PesHeader {
+            version,
+            pec_offset: 0,
+            hoop: Some((130, 180)),
+            metadata: PesMetadata {
+                name: "Flower".to_string(),
+                category: "".to_string(),
+                author: "Someone".to_string(),
+                keywords: "flower, garden".to_string(),
+                comments: "Comment".to_string(),
+            },
+            threads: vec![thread, Thread::new_str(Color::rgb(255, 255, 255), &"", &"")],
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_v1() {
+        let header = sample_header(1);
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(&data[..8], b"#PES0001");
+        assert_eq!(data.len(), 18);
+        assert_eq!(
+            PesHeader::build(&mut &data[..]).unwrap(),
+            PesHeader {
+                metadata: PesMetadata::default(),
+                threads: vec![],
+                ..header
+            }
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_v6() {
+        let header = sample_header(60);
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(&data[..8], b"#PES0060");
+        assert_eq!(PesHeader::build(&mut &data[..]).unwrap(), header);
+    }
+
+    #[test]
+    fn test_undocumented_version() {
+        let mut data = b"#PES0020".to_vec();
+        data.extend_from_slice(&[0x10, 0x00, 0x00, 0x00]);
+        let header = PesHeader::build(&mut &data[..]).unwrap();
+        assert_eq!(header.version, 20);
+        assert_eq!(header.pec_offset, 16);
+        assert_eq!(header.hoop, None);
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let mut data = b"#PES0100".to_vec();
+        data.extend_from_slice(&[0x10, 0x00, 0x00, 0x00]);
+        assert!(PesHeader::build(&mut &data[..]).is_err());
+        assert!(sample_header(40).write(&mut vec![]).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(PesHeader::build(&mut &b"#PEC0001\0\0\0\0"[..]).is_err());
+        assert!(PesHeader::build(&mut &b"#PESv1.0\0\0\0\0"[..]).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+mod colors;
+mod header;
+mod pec;
+mod read;
+mod stitch_info;
+mod thumbnail;
+mod write;
+
+pub use read::PesPatternReader;
+pub use write::{PesPatternWriter, PesVersion};
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+const NAME: &str = "pes";
+const EXTENSIONS: [&str; 2] = ["pes", "pec"];
+
+#[derive(Default)]
+pub struct PesPatternFormat {}
+
+impl PatternFormat for PesPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(PesPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(PesPatternWriter::default()))
+    }
+}
@@ -0,0 +1,209 @@
+use std::io::Write;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{c_trim, char_truncate};
+
+use crate::stitch_info::{read_stitch, StitchInformation};
+use crate::thumbnail::{Thumbnail, THUMBNAIL_HEIGHT, THUMBNAIL_LEN, THUMBNAIL_STRIDE};
+
+pub const PEC_MAGIC: &[u8; 8] = b"#PEC0001";
+
+const LABEL_LEN: usize = 16;
+// Offsets from the start of the PEC block.
+const THUMBNAIL_SIZE_OFFSET: usize = 0x22;
+const COLOR_COUNT_OFFSET: usize = 0x30;
+const STITCH_BLOCK_OFFSET: usize = 0x200;
+// Offset from the start of the stitch block.
+const STITCHES_OFFSET: usize = 0x10;
+
+/// The most colors the header can count; a count byte of 0xFF means there aren't any.
+pub const MAX_COLORS: usize = 255;
+
+/// The part of a PES file that machines actually read; it's also found on its own in PEC files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PecBlock {
+    pub label: String,
+    /// An index into `PEC_THREADS` for each color.
+    pub colors: Vec<u8>,
+    pub stitches: Vec<StitchInformation>,
+    /// A thumbnail of the whole design, followed by one for each color.
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+fn read_u24(data: &[u8]) -> usize {
+    usize::from(data[0]) | usize::from(data[1]) << 8 | usize::from(data[2]) << 16
+}
+
+impl PecBlock {
+    pub fn read(data: &[u8]) -> ReadResult<Self> {
+        if data.len() < STITCH_BLOCK_OFFSET + STITCHES_OFFSET {
+            return Err(ReadError::invalid_format("The PEC block is too short"));
+        }
+        if &data[..3] != b"LA:" {
+            return Err(ReadError::invalid_format("The PEC block doesn't start with a label"));
+        }
+        let label = c_trim(&String::from_utf8_lossy(&data[3..3 + LABEL_LEN]));
+
+        // The number of colors is stored minus one; with 0xFF meaning none at all.
+        let color_count = match data[COLOR_COUNT_OFFSET] {
+            0xFF => 0,
+            count => usize::from(count) + 1,
+        };
+        let colors = data[COLOR_COUNT_OFFSET + 1..COLOR_COUNT_OFFSET + 1 + color_count].to_vec();
+
+        let stitch_block = &data[STITCH_BLOCK_OFFSET..];
+        let thumbnail_offset = read_u24(&stitch_block[2..5]);
+        let stitch_data = if STITCHES_OFFSET < thumbnail_offset && thumbnail_offset <= stitch_block.len() {
+            &stitch_block[STITCHES_OFFSET..thumbnail_offset]
+        } else {
+            &stitch_block[STITCHES_OFFSET..]
+        };
+        let mut iter = stitch_data.iter().cloned();
+        let mut stitches = Vec::new();
+        while let Some(stitch) = read_stitch(&mut iter)? {
+            stitches.push(stitch);
+        }
+
+        // Some writers leave the thumbnails out, so take whatever is there.
+        let (stride, height) = (
+            usize::from(data[THUMBNAIL_SIZE_OFFSET]),
+            usize::from(data[THUMBNAIL_SIZE_OFFSET + 1]),
+        );
+        let mut thumbnails = Vec::new();
+        if stride == THUMBNAIL_STRIDE && height == THUMBNAIL_HEIGHT && thumbnail_offset <= stitch_block.len() {
+            thumbnails = stitch_block[thumbnail_offset..]
+                .chunks(THUMBNAIL_LEN)
+                .take(color_count + 1)
+                .filter_map(Thumbnail::from_bytes)
+                .collect();
+        }
+
+        Ok(PecBlock {
+            label,
+            colors,
+            stitches,
+            thumbnails,
+        })
+    }
+
+    /// Write the block; `size` is the width and height of the design in 0.1mm.
+    pub fn write(&self, size: (u16, u16), writer: &mut dyn Write) -> Result<(), WriteError> {
+        writer.write_all(b"LA:")?;
+        let label = char_truncate(&self.label, LABEL_LEN);
+        writer.write_all(label.as_bytes())?;
+        writer.write_all(&vec![b' '; LABEL_LEN - label.len()])?;
+        writer.write_all(b"\r")?;
+        writer.write_all(&[b' '; 12])?;
+        writer.write_all(&[0xFF, 0x00])?;
+        writer.write_all(&[THUMBNAIL_STRIDE as u8, THUMBNAIL_HEIGHT as u8])?;
+        // Copied from files written by Brother's software; what it means is unknown.
+        writer.write_all(b"    \x64 \x00 \x00   ")?;
+
+        if self.colors.len() > MAX_COLORS {
+            return Err(WriteError::too_many_colors(self.colors.len(), MAX_COLORS));
+        }
+        if self.colors.is_empty() {
+            writer.write_u8(0xFF)?;
+        } else {
+            writer.write_u8((self.colors.len() - 1) as u8)?;
+            writer.write_all(&self.colors)?;
+        }
+        let padding = STITCH_BLOCK_OFFSET - COLOR_COUNT_OFFSET - 1 - self.colors.len();
+        writer.write_all(&vec![b' '; padding])?;
+
+        let stitch_data: Vec<u8> = self
+            .stitches
+            .iter()
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            .flat_map(|st| st.to_bytes().unwrap())
+            .collect();
+        let thumbnail_offset = (STITCHES_OFFSET + stitch_data.len()) as u32;
+        writer.write_all(&[0x00, 0x00])?;
+        writer.write_all(&thumbnail_offset.to_le_bytes()[..3])?;
+        writer.write_all(&[0x31, 0xFF, 0xF0])?;
+        writer.write_u16::<LittleEndian>(size.0)?;
+        writer.write_u16::<LittleEndian>(size.1)?;
+        writer.write_u16::<LittleEndian>(0x1E0)?;
+        writer.write_u16::<LittleEndian>(0x1B0)?;
+        writer.write_all(&stitch_data)?;
+
+        for thumbnail in &self.thumbnails {
+            writer.write_all(&thumbnail.data)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stitch_info::StitchType;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut block = PecBlock {
+            label: "Flower".to_string(),
+            colors: vec![5, 13],
+            stitches: vec![
+                StitchInformation::Move(100, -100, StitchType::Jump),
+                StitchInformation::Move(10, 10, StitchType::Regular),
+                StitchInformation::ColorChange(2),
+                StitchInformation::Move(-10, 0, StitchType::Regular),
+                StitchInformation::End,
+            ],
+            thumbnails: vec![Thumbnail::default(); 3],
+        };
+        let mut data = Vec::new();
+        block.write((100, 200), &mut data).unwrap();
+        assert_eq!(&data[..20], b"LA:Flower          \r");
+        assert_eq!(data[0x30..0x33], [1, 5, 13]);
+        assert_eq!(data[0x202..0x205], [0x1C, 0, 0]);
+        assert_eq!(data[0x208..0x20C], [100, 0, 200, 0]);
+        assert_eq!(data.len(), 0x21C + 3 * THUMBNAIL_LEN);
+
+        // Reading stops at the end, rather than including it.
+        block.stitches.pop();
+        assert_eq!(PecBlock::read(&data).unwrap(), block);
+    }
+
+    #[test]
+    fn test_read_without_colors_or_thumbnails() {
+        let block = PecBlock {
+            label: "".to_string(),
+            colors: vec![],
+            stitches: vec![StitchInformation::End],
+            thumbnails: vec![],
+        };
+        let mut data = Vec::new();
+        block.write((0, 0), &mut data).unwrap();
+        assert_eq!(data[0x30], 0xFF);
+        let read = PecBlock::read(&data).unwrap();
+        assert!(read.colors.is_empty());
+        assert!(read.stitches.is_empty());
+        assert!(read.thumbnails.is_empty());
+    }
+
+    #[test]
+    fn test_most_colors() {
+        let mut block = PecBlock {
+            label: "".to_string(),
+            colors: vec![1; MAX_COLORS],
+            stitches: vec![StitchInformation::End],
+            thumbnails: vec![],
+        };
+        let mut data = Vec::new();
+        block.write((0, 0), &mut data).unwrap();
+        assert_eq!(PecBlock::read(&data).unwrap().colors.len(), MAX_COLORS);
+
+        block.colors.push(1);
+        assert!(block.write((0, 0), &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        assert!(PecBlock::read(b"LA:").is_err());
+        assert!(PecBlock::read(&[0; 0x300]).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::io::Read;
+
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+
+use crate::colors::pec_thread;
+use crate::header::{PesHeader, PesMetadata, PES_MAGIC};
+use crate::pec::{PecBlock, PEC_MAGIC};
+use crate::stitch_info::{StitchInformation, StitchType};
+
+#[derive(Default)]
+pub struct PesPatternReader {}
+
+impl PatternReader for PesPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        let mut buf = [0_u8; 8];
+        item.read_exact(&mut buf)?;
+        Ok(&buf == PEC_MAGIC || buf.starts_with(PES_MAGIC))
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut data = Vec::new();
+        item.read_to_end(&mut data)?;
+
+        let (header, pec) = if data.starts_with(PEC_MAGIC) {
+            (None, PecBlock::read(&data[PEC_MAGIC.len()..])?)
+        } else {
+            let header = PesHeader::build(&mut &data[..])?;
+            let offset = header.pec_offset as usize;
+            if offset >= data.len() {
+                return Err(ReadError::invalid_format(format!(
+                    "The PEC block's offset({}) is past the end of the file",
+                    offset
+                )));
+            }
+            let pec = PecBlock::read(&data[offset..])?;
+            (Some(header), pec)
+        };
+
+        // The PES header has the full details of each thread, but only the PEC block is
+        // guaranteed to have them.
+        let threads: Vec<Thread> = match header {
+            Some(ref header) if header.threads.len() == pec.colors.len() => header.threads.clone(),
+            _ => pec.colors.iter().map(|&idx| pec_thread(idx)).collect(),
+        };
+        let color_groups = read_stitches(&threads, &pec.stitches);
+
+        let metadata = header.as_ref().map(|h| h.metadata.clone()).unwrap_or_default();
+        let mut attributes = metadata_to_attributes(&metadata);
+        if let Some((width, height)) = header.as_ref().and_then(|h| h.hoop) {
+            attributes.push(PatternAttribute::Arbitary(
+                "hoop".to_string(),
+                format!("{}x{}", width, height),
+            ));
+        }
+        let name = if !metadata.name.is_empty() {
+            metadata.name
+        } else if !pec.label.is_empty() {
+            pec.label
+        } else {
+            "Untitled".to_string()
+        };
+
+        Ok(Pattern {
+            name,
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn metadata_to_attributes(metadata: &PesMetadata) -> Vec<PatternAttribute> {
+    let mut attributes = Vec::new();
+    if !metadata.name.is_empty() {
+        attributes.push(PatternAttribute::Title(metadata.name.clone()));
+    }
+    if !metadata.author.is_empty() {
+        attributes.push(PatternAttribute::Author(metadata.author.clone()));
+    }
+    for &(key, value) in &[
+        ("category", &metadata.category),
+        ("keywords", &metadata.keywords),
+        ("comments", &metadata.comments),
+    ] {
+        if !value.is_empty() {
+            attributes.push(PatternAttribute::Arbitary(key.to_string(), value.clone()));
+        }
+    }
+    attributes
+}
+
+fn read_stitches(threads: &[Thread], pec_stitches: &[StitchInformation]) -> Vec<ColorGroup> {
+    let mut thread_iter = threads.iter();
+    let mut grouper = StitchGrouper::new();
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    for &stitch in pec_stitches {
+        match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => {
+                cx += i32::from(dx);
+                cy += i32::from(dy);
+                // PEC's y axis points down the page; so flip it to match the rest of the library.
+                let to = from_tenths(cx, -cy);
+                match stitch_type {
+                    StitchType::Regular => grouper.stitch(to),
+                    StitchType::Trim => {
+                        grouper.trim();
+                        grouper.jump(to);
+                    },
+                    StitchType::Jump => grouper.jump(to),
+                }
+            },
+            // Each color change moves onto the next thread, even if nothing was sewn with the
+            // current one.
+            StitchInformation::ColorChange(_) => grouper.end_color(thread_iter.next().cloned()),
+            StitchInformation::End => break,
+        }
+    }
+    grouper.finish(thread_iter.next().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_stitches() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let blue = Thread::new_str(Color::rgb(0, 0, 240), &"Blue", &"");
+        let stitches = [
+            StitchInformation::Move(10, 0, StitchType::Regular),
+            StitchInformation::Move(0, -10, StitchType::Regular),
+            StitchInformation::Move(0, 0, StitchType::Trim),
+            StitchInformation::Move(20, 0, StitchType::Jump),
+            StitchInformation::Move(0, 10, StitchType::Regular),
+            StitchInformation::ColorChange(2),
+            StitchInformation::Move(-10, 0, StitchType::Jump),
+            StitchInformation::Move(10, -10, StitchType::Regular),
+            StitchInformation::End,
+            StitchInformation::Move(10, 10, StitchType::Regular),
+        ];
+        assert_eq!(
+            read_stitches(&[red.clone(), blue.clone()], &stitches),
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(blue),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_pec_file() {
+        let block = PecBlock {
+            label: "Square".to_string(),
+            colors: vec![13],
+            stitches: vec![
+                StitchInformation::Move(10, 0, StitchType::Regular),
+                StitchInformation::Move(0, 10, StitchType::Regular),
+                StitchInformation::End,
+            ],
+            thumbnails: vec![],
+        };
+        let mut data = PEC_MAGIC.to_vec();
+        block.write((10, 10), &mut data).unwrap();
+
+        let reader = PesPatternReader::default();
+        assert!(reader.is_loadable(&mut &data[..]).unwrap());
+        let pattern = reader.read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(pattern.name, "Square");
+        assert_eq!(pattern.attributes, vec![]);
+        assert_eq!(pattern.color_groups.len(), 1);
+        assert_eq!(pattern.color_groups[0].thread, Some(pec_thread(13)));
+        assert_eq!(
+            pattern.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0), Stitch::new(1.0, -1.0)]
+        );
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let reader = PesPatternReader::default();
+        assert!(!reader.is_loadable(&mut &b"LA:crown FS 40     \r"[..]).unwrap());
+        match reader.read_pattern(&mut &b"#PES0001\xFF\0\0\0\0\0\0\0"[..]) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            result => panic!("Expected an invalid format error; got {:?}", result),
+        }
+        match reader.read_pattern(&mut &b"<svg></svg>"[..]) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            result => panic!("Expected an invalid format error; got {:?}", result),
+        }
+    }
+}
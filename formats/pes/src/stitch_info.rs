@@ -0,0 +1,189 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// Every stitch is a pair of `(dx, dy)` in 0.1mm increments. Each is either a 7 bit signed number
+// in a single byte, or when the top bit is set; a 12 bit signed number in 2 big endian bytes where
+// bits 4 and 5 of the first byte flag a jump or a trim.
+const LONG_FORM: u8 = 0x80;
+const JUMP_FLAG: u8 = 0x10;
+const TRIM_FLAG: u8 = 0x20;
+// `COLOR_CHANGE` is followed by a byte which alternates between 2 and 1.
+pub const COLOR_CHANGE: [u8; 2] = [0xFE, 0xB0];
+pub const END: u8 = 0xFF;
+
+pub const MAX_SHORT: i16 = 63;
+pub const MIN_SHORT: i16 = -64;
+pub const MAX_LONG: i16 = 2047;
+pub const MIN_LONG: i16 = -2048;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    Trim,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchInformation {
+    Move(i16, i16, StitchType),
+    ColorChange(u8),
+    End,
+}
+
+fn is_short(value: i16) -> bool {
+    (MIN_SHORT..=MAX_SHORT).contains(&value)
+}
+
+fn is_long(value: i16) -> bool {
+    (MIN_LONG..=MAX_LONG).contains(&value)
+}
+
+fn encode_long(value: i16, flags: u8) -> [u8; 2] {
+    let value = (value as u16) & 0x0FFF;
+    [LONG_FORM | flags | (value >> 8) as u8, value as u8]
+}
+
+fn decode_long(high: u8, low: u8) -> i16 {
+    let value = (i16::from(high & 0x0F) << 8) | i16::from(low);
+    if value > MAX_LONG {
+        value - 0x1000
+    } else {
+        value
+    }
+}
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            StitchInformation::Move(dx, dy, _) if !is_long(dx) || !is_long(dy) => None,
+            StitchInformation::Move(dx, dy, StitchType::Regular) if is_short(dx) && is_short(dy) => {
+                Some(vec![(dx as u8) & 0x7F, (dy as u8) & 0x7F])
+            },
+            StitchInformation::Move(dx, dy, stitch_type) => {
+                let flags = match stitch_type {
+                    StitchType::Regular => 0,
+                    StitchType::Jump => JUMP_FLAG,
+                    StitchType::Trim => TRIM_FLAG,
+                };
+                let mut bytes = encode_long(dx, flags).to_vec();
+                bytes.extend_from_slice(&encode_long(dy, flags));
+                Some(bytes)
+            },
+            StitchInformation::ColorChange(marker) => Some(vec![COLOR_CHANGE[0], COLOR_CHANGE[1], marker]),
+            StitchInformation::End => Some(vec![END]),
+        }
+    }
+}
+
+fn decode_short(value: u8) -> i16 {
+    // Sign extend the 7 bit value.
+    i16::from(((value << 1) as i8) >> 1)
+}
+
+fn next_byte(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<u8> {
+    in_bytes
+        .next()
+        .ok_or_else(|| ReadError::invalid_format("Stitch data ends part way through a stitch"))
+}
+
+pub fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let first = match in_bytes.next() {
+        Some(END) | None => return Ok(None),
+        Some(first) => first,
+    };
+    let second = next_byte(in_bytes)?;
+    if [first, second] == COLOR_CHANGE {
+        return Ok(Some(StitchInformation::ColorChange(next_byte(in_bytes)?)));
+    }
+    let mut flags = 0;
+    let (dx, next) = if first & LONG_FORM == 0 {
+        (decode_short(first), second)
+    } else {
+        flags |= first;
+        (decode_long(first, second), next_byte(in_bytes)?)
+    };
+    let dy = if next & LONG_FORM == 0 {
+        decode_short(next)
+    } else {
+        flags |= next;
+        decode_long(next, next_byte(in_bytes)?)
+    };
+    let stitch_type = if flags & TRIM_FLAG != 0 {
+        StitchType::Trim
+    } else if flags & JUMP_FLAG != 0 {
+        StitchType::Jump
+    } else {
+        StitchType::Regular
+    };
+    Ok(Some(StitchInformation::Move(dx, dy, stitch_type)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(data: &[u8]) -> ReadResult<Vec<StitchInformation>> {
+        let mut iter = data.iter().cloned();
+        let mut stitches = Vec::new();
+        while let Some(stitch) = read_stitch(&mut iter)? {
+            stitches.push(stitch);
+        }
+        Ok(stitches)
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let to_bytes = |dx, dy, st| StitchInformation::Move(dx, dy, st).to_bytes();
+        assert_eq!(to_bytes(1, -1, StitchType::Regular), Some(vec![0x01, 0x7F]));
+        assert_eq!(to_bytes(63, -64, StitchType::Regular), Some(vec![0x3F, 0x40]));
+        assert_eq!(to_bytes(64, 0, StitchType::Regular), Some(vec![0x80, 0x40, 0x80, 0x00]));
+        assert_eq!(to_bytes(-1, 2047, StitchType::Jump), Some(vec![0x9F, 0xFF, 0x97, 0xFF]));
+        assert_eq!(to_bytes(-2048, 0, StitchType::Trim), Some(vec![0xA8, 0x00, 0xA0, 0x00]));
+        assert_eq!(to_bytes(2048, 0, StitchType::Regular), None);
+        assert_eq!(
+            StitchInformation::ColorChange(2).to_bytes(),
+            Some(vec![0xFE, 0xB0, 0x02])
+        );
+        assert_eq!(StitchInformation::End.to_bytes(), Some(vec![0xFF]));
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(
+            read_all(&[
+                0x01, 0x7F, // Stitch (1, -1)
+                0x80, 0x40, 0x05, // Stitch (64, 5)
+                0x9F, 0xFF, 0x97, 0xFF, // Jump (-1, 2047)
+                0xFE, 0xB0, 0x02, // Color change
+                0xA8, 0x00, 0x00, // Trim (-2048, 0)
+                0xFF, 0x00, // End
+                0x01, 0x01, // Ignored
+            ])
+            .unwrap(),
+            vec![
+                StitchInformation::Move(1, -1, StitchType::Regular),
+                StitchInformation::Move(64, 5, StitchType::Regular),
+                StitchInformation::Move(-1, 2047, StitchType::Jump),
+                StitchInformation::ColorChange(2),
+                StitchInformation::Move(-2048, 0, StitchType::Trim),
+            ]
+        );
+        assert!(read_all(&[0x01]).is_err());
+        assert!(read_all(&[0x80, 0x01]).is_err());
+        assert!(read_all(&[0xFE, 0xB0]).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let stitches = [
+            StitchInformation::Move(0, 0, StitchType::Regular),
+            StitchInformation::Move(-64, 63, StitchType::Regular),
+            StitchInformation::Move(-65, 0, StitchType::Regular),
+            StitchInformation::Move(300, -300, StitchType::Jump),
+            StitchInformation::Move(0, 0, StitchType::Trim),
+            StitchInformation::ColorChange(1),
+        ];
+        let data: Vec<u8> = stitches.iter().flat_map(|s| s.to_bytes().unwrap()).collect();
+        assert_eq!(read_all(&data).unwrap(), stitches.to_vec());
+    }
+}
@@ -0,0 +1,123 @@
+use embroidery_lib::prelude::*;
+
+pub const THUMBNAIL_WIDTH: usize = 48;
+pub const THUMBNAIL_HEIGHT: usize = 38;
+/// The number of bytes in each row; each byte holds 8 pixels with the leftmost in the lowest bit.
+pub const THUMBNAIL_STRIDE: usize = THUMBNAIL_WIDTH / 8;
+pub const THUMBNAIL_LEN: usize = THUMBNAIL_STRIDE * THUMBNAIL_HEIGHT;
+
+// The stitches are drawn inside the rounded border, leaving a gap around them.
+const BORDER: usize = 5;
+
+/// One of the monochrome images in a PEC block that machines show when picking a design.
+#[derive(Clone, PartialEq)]
+pub struct Thumbnail {
+    pub data: [u8; THUMBNAIL_LEN],
+}
+
+impl std::fmt::Debug for Thumbnail {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Thumbnail(")?;
+        for y in 0..THUMBNAIL_HEIGHT {
+            let row: String = (0..THUMBNAIL_WIDTH)
+                .map(|x| if self.get(x, y) { '#' } else { '.' })
+                .collect();
+            writeln!(f, "  {}", row)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Default for Thumbnail {
+    /// An empty image with a rounded border.
+    fn default() -> Self {
+        let mut thumbnail = Thumbnail {
+            data: [0; THUMBNAIL_LEN],
+        };
+        let (right, bottom) = (THUMBNAIL_WIDTH - 1, THUMBNAIL_HEIGHT - 1);
+        for x in 4..=(right - 4) {
+            thumbnail.set(x, 1);
+            thumbnail.set(x, bottom - 1);
+        }
+        for &(x, y) in &[(3, 2), (2, 3)] {
+            thumbnail.set(x, y);
+            thumbnail.set(right - x, y);
+            thumbnail.set(x, bottom - y);
+            thumbnail.set(right - x, bottom - y);
+        }
+        for y in 4..=(bottom - 4) {
+            thumbnail.set(1, y);
+            thumbnail.set(right - 1, y);
+        }
+        thumbnail
+    }
+}
+
+impl Thumbnail {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != THUMBNAIL_LEN {
+            return None;
+        }
+        let mut data = [0; THUMBNAIL_LEN];
+        data.copy_from_slice(bytes);
+        Some(Thumbnail { data })
+    }
+
+    /// Draw the needle points of `stitches` scaled so that `bounds` fills the image.
+    pub fn draw<'a>(stitches: impl Iterator<Item = &'a Stitch>, bounds: (f64, f64, f64, f64)) -> Self {
+        let mut thumbnail = Thumbnail::default();
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let (inner_width, inner_height) = (THUMBNAIL_WIDTH - 2 * BORDER, THUMBNAIL_HEIGHT - 2 * BORDER);
+        let scale = f64::min(
+            (inner_width - 1) as f64 / (max_x - min_x).max(1e-3),
+            (inner_height - 1) as f64 / (max_y - min_y).max(1e-3),
+        );
+        for stitch in stitches {
+            // The image's y axis points down the page.
+            let x = ((stitch.x - min_x) * scale).round() as usize;
+            let y = ((max_y - stitch.y) * scale).round() as usize;
+            thumbnail.set(BORDER + x.min(inner_width - 1), BORDER + y.min(inner_height - 1));
+        }
+        thumbnail
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.data[y * THUMBNAIL_STRIDE + x / 8] & (1 << (x % 8)) != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize) {
+        self.data[y * THUMBNAIL_STRIDE + x / 8] |= 1 << (x % 8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_blank_border() {
+        let thumbnail = Thumbnail::default();
+        assert_eq!(&thumbnail.data[..THUMBNAIL_STRIDE], &[0x00; THUMBNAIL_STRIDE]);
+        assert_eq!(&thumbnail.data[6..12], &[0xF0, 0xFF, 0xFF, 0xFF, 0xFF, 0x0F]);
+        assert_eq!(&thumbnail.data[12..18], &[0x08, 0x00, 0x00, 0x00, 0x00, 0x10]);
+        assert_eq!(&thumbnail.data[18..24], &[0x04, 0x00, 0x00, 0x00, 0x00, 0x20]);
+        assert_eq!(&thumbnail.data[24..30], &[0x02, 0x00, 0x00, 0x00, 0x00, 0x40]);
+        assert_eq!(
+            &thumbnail.data[THUMBNAIL_LEN - 12..THUMBNAIL_LEN - 6],
+            &thumbnail.data[6..12]
+        );
+    }
+
+    #[test]
+    fn test_draw() {
+        let stitches = [Stitch::new(-10., 5.), Stitch::new(10., -5.)];
+        let thumbnail = Thumbnail::draw(stitches.iter(), (-10., -5., 10., 5.));
+        // The top left and bottom right corners of the area inside the border.
+        assert!(thumbnail.get(5, 5));
+        assert!(thumbnail.get(42, 24));
+        let set = thumbnail.data.iter().map(|b| b.count_ones()).sum::<u32>();
+        let border = Thumbnail::default().data.iter().map(|b| b.count_ones()).sum::<u32>();
+        assert_eq!(set, border + 2);
+        assert_eq!(Thumbnail::from_bytes(&thumbnail.data), Some(thumbnail));
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::Write;
+
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, to_tenths};
+
+use crate::colors::{closest_pec_thread, pec_thread, PEC_THREADS};
+use crate::header::{PesHeader, PesMetadata, LARGE_HOOP, SMALL_HOOP};
+use crate::pec::{PecBlock, MAX_COLORS};
+use crate::stitch_info::{StitchInformation, StitchType, MAX_LONG, MIN_LONG};
+use crate::thumbnail::Thumbnail;
+
+/// The versions of the PES header that can be written.
+///
+/// Versions 2 to 5 can be read but not written; their headers aren't fully documented, and every
+/// machine that reads them also reads version 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PesVersion {
+    /// Only the hoop size; which every Brother machine can read.
+    V1,
+    /// Includes the threads and the name, author and other metadata of the design.
+    V6,
+}
+
+impl PesVersion {
+    fn number(self) -> u16 {
+        match self {
+            PesVersion::V1 => 1,
+            PesVersion::V6 => 60,
+        }
+    }
+}
+
+pub struct PesPatternWriter {
+    version: PesVersion,
+}
+
+impl Default for PesPatternWriter {
+    fn default() -> Self {
+        PesPatternWriter::new(PesVersion::V6)
+    }
+}
+
+impl PesPatternWriter {
+    pub fn new(version: PesVersion) -> Self {
+        PesPatternWriter { version }
+    }
+}
+
+impl PatternWriter for PesPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        if pattern.color_groups.len() > MAX_COLORS {
+            return Err(WriteError::too_many_colors(pattern.color_groups.len(), MAX_COLORS));
+        }
+        let threads: Vec<Thread> = pattern
+            .color_groups
+            .iter()
+            .enumerate()
+            .map(|(i, cg)| match &cg.thread {
+                Some(thread) => thread.clone(),
+                // Skip the placeholder at index 0.
+                None => pec_thread(1 + (i % (PEC_THREADS.len() - 1)) as u8),
+            })
+            .collect();
+
+        let bounds = pattern.get_bounds();
+        let (min_x, min_y, max_x, max_y) = bounds;
+        let (width, height) = (max_x - min_x, max_y - min_y);
+        let mut thumbnails = vec![Thumbnail::draw(pattern.iter_stitches(), bounds)];
+        thumbnails.extend(
+            pattern
+                .color_groups
+                .iter()
+                .map(|cg| Thumbnail::draw(cg.iter_stitches(), bounds)),
+        );
+        let pec = PecBlock {
+            label: pattern.name.clone(),
+            colors: threads.iter().map(closest_pec_thread).collect(),
+            stitches: into_pec_stitches(pattern)?,
+            thumbnails,
+        };
+
+        let hoop = if width <= f64::from(SMALL_HOOP.0) && height <= f64::from(SMALL_HOOP.1) {
+            SMALL_HOOP
+        } else {
+            LARGE_HOOP
+        };
+        let mut header = PesHeader {
+            version: self.version.number(),
+            pec_offset: 0,
+            hoop: Some(hoop),
+            metadata: attributes_to_metadata(pattern),
+            threads,
+        };
+        // The header's length doesn't depend on the offset; so write it once to find out.
+        let mut header_data = Vec::new();
+        header.write(&mut header_data)?;
+        header.pec_offset = header_data.len() as u32;
+        header.write(writer)?;
+        pec.write((to_tenths(width) as u16, to_tenths(height) as u16), writer)?;
+        Ok(())
+    }
+}
+
+fn attributes_to_metadata(pattern: &Pattern) -> PesMetadata {
+    let mut metadata = PesMetadata {
+        name: pattern.name.clone(),
+        ..PesMetadata::default()
+    };
+    for attribute in &pattern.attributes {
+        match attribute {
+            PatternAttribute::Title(title) => metadata.name = title.clone(),
+            PatternAttribute::Author(author) => metadata.author = author.clone(),
+            PatternAttribute::Arbitary(key, value) if key == "category" => metadata.category = value.clone(),
+            PatternAttribute::Arbitary(key, value) if key == "keywords" => metadata.keywords = value.clone(),
+            PatternAttribute::Arbitary(key, value) if key == "comments" => metadata.comments = value.clone(),
+            _ => {},
+        }
+    }
+    metadata
+}
+
+fn fits_long(value: i32) -> bool {
+    (i32::from(MIN_LONG)..=i32::from(MAX_LONG)).contains(&value)
+}
+
+fn into_pec_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            // The byte after a color change alternates between 2 and 1.
+            re.push(StitchInformation::ColorChange(if i % 2 == 1 { 2 } else { 1 }));
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                // PEC's y axis points down the page.
+                let (x, y) = (to_tenths(s.x), -to_tenths(s.y));
+                re.append(&mut safe_jump_to(x - ox, y - oy));
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), -to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if !fits_long(dx) || !fits_long(dy) {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation::Move(dx as i16, dy as i16, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation::Move(0, 0, StitchType::Trim));
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok(re)
+}
+
+/// Split a move into as few jumps as possible; none at all if it doesn't go anywhere.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<StitchInformation> {
+    split_jump(delta_x, delta_y, i32::from(MAX_LONG))
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation::Move(dx as i16, dy as i16, StitchType::Jump))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_jump_to() {
+        assert_eq!(safe_jump_to(0, 0), vec![]);
+        assert_eq!(
+            safe_jump_to(-2047, 2047),
+            vec![StitchInformation::Move(-2047, 2047, StitchType::Jump)]
+        );
+        assert_eq!(
+            safe_jump_to(5000, -10),
+            vec![
+                StitchInformation::Move(1666, -3, StitchType::Jump),
+                StitchInformation::Move(1667, -3, StitchType::Jump),
+                StitchInformation::Move(1667, -4, StitchType::Jump),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(204.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(PesPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_pes::{PesPatternReader, PesPatternWriter, PesVersion};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip_v6() {
+    let mut pattern = load_sample();
+    pattern.name = "Hedera".to_string();
+    pattern.attributes = vec![
+        PatternAttribute::Title("Hedera".to_string()),
+        PatternAttribute::Author("Someone".to_string()),
+        PatternAttribute::Arbitary("keywords".to_string(), "ivy".to_string()),
+        // The smallest hoop that the design fits in.
+        PatternAttribute::Arbitary("hoop".to_string(), "100x100".to_string()),
+    ];
+    let mut data = Vec::new();
+    PesPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+    assert_eq!(&data[..8], b"#PES0060");
+    let loader = PesPatternReader::default();
+    assert!(loader.is_loadable(&mut Cursor::new(&data)).unwrap());
+    let read = loader.read_pattern(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(read.name, pattern.name);
+    assert_eq!(read.attributes, pattern.attributes);
+    assert_eq!(read.color_groups.len(), 3);
+    for (read_cg, cg) in read.color_groups.iter().zip(&pattern.color_groups) {
+        assert_eq!(read_cg.stitch_groups, cg.stitch_groups);
+        let (read_thread, thread) = (read_cg.thread.as_ref().unwrap(), cg.thread.as_ref().unwrap());
+        assert_eq!(read_thread.color, thread.color);
+        assert_eq!(read_thread.name, thread.name);
+        assert_eq!(read_thread.code, thread.code);
+        assert_eq!(read_thread.manufacturer, thread.manufacturer);
+    }
+}
+
+#[test]
+fn test_hedera_roundtrip_v1() {
+    let pattern = load_sample();
+    let mut data = Vec::new();
+    PesPatternWriter::new(PesVersion::V1)
+        .write_pattern(&pattern, &mut data)
+        .unwrap();
+    assert_eq!(&data[..8], b"#PES0001");
+    let read = PesPatternReader::default()
+        .read_pattern(&mut Cursor::new(&data))
+        .unwrap();
+
+    // The design fits in the smallest hoop.
+    assert_eq!(
+        read.attributes,
+        vec![PatternAttribute::Arbitary("hoop".to_string(), "100x100".to_string())]
+    );
+    assert_eq!(read.color_groups.len(), 3);
+    for (read_cg, cg) in read.color_groups.iter().zip(&pattern.color_groups) {
+        assert_eq!(read_cg.stitch_groups, cg.stitch_groups);
+    }
+    // Only the palette's colors survive.
+    let names: Vec<_> = read
+        .color_groups
+        .iter()
+        .map(|cg| cg.thread.as_ref().unwrap().name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Blue", "Moss Green", "Red"]);
+}
@@ -0,0 +1,26 @@
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use self::write::{PngPatternWriter, PngWriterOptions};
+
+const NAME: &str = "png";
+const EXTENSIONS: [&str; 1] = ["png"];
+
+#[derive(Default)]
+pub struct PngPatternFormat {}
+
+impl PatternFormat for PngPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        None
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(PngPatternWriter::default()))
+    }
+}
@@ -0,0 +1,169 @@
+use std::io::{self, Write};
+
+use embroidery_lib::errors::WriteResult as Result;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::render::{draw_pattern, draw_pattern_shaded, Canvas, RenderStyle, Viewport, MM_PER_INCH};
+
+/// Controls how a pattern is drawn; all sizes are in mm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PngWriterOptions {
+    /// Pixels per inch.
+    pub dpi: f64,
+    /// The width of the line drawn for each stitch.
+    pub line_width: f64,
+    /// The space left around the pattern.
+    pub margin: f64,
+    /// The color of the fabric; or `None` for a transparent background.
+    pub background: Option<Color>,
+    /// Flat lines, or thread shaded to look like the real thing.
+    pub style: RenderStyle,
+}
+
+impl Default for PngWriterOptions {
+    fn default() -> Self {
+        PngWriterOptions {
+            dpi: 96.,
+            line_width: 0.4,
+            margin: 2.,
+            background: None,
+            style: RenderStyle::Flat,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PngPatternWriter {
+    options: PngWriterOptions,
+}
+
+impl PngPatternWriter {
+    pub fn new(options: PngWriterOptions) -> Self {
+        PngPatternWriter { options }
+    }
+}
+
+impl PatternWriter for PngPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+        let viewport = Viewport::for_pattern(pattern, self.options.dpi, self.options.margin);
+        let mut canvas = viewport.new_canvas(self.options.background)?;
+        match self.options.style {
+            RenderStyle::Flat => draw_pattern(pattern, &viewport, self.options.line_width, &mut canvas),
+            RenderStyle::Shaded => draw_pattern_shaded(pattern, &viewport, self.options.line_width, &mut canvas),
+        }
+        write_png(&canvas, self.options.dpi, self.options.background, writer)
+    }
+}
+
+/// Encode the canvas; as RGB when there's a background, since every pixel is then opaque.
+pub fn write_png(canvas: &Canvas, dpi: f64, background: Option<Color>, writer: &mut dyn Write) -> Result<()> {
+    let mut encoder = png::Encoder::new(writer, canvas.width(), canvas.height());
+    encoder.set_depth(png::BitDepth::Eight);
+    let pixels_per_metre = (dpi / MM_PER_INCH * 1000.).round() as u32;
+    encoder.set_pixel_dims(Some(png::PixelDimensions {
+        xppu: pixels_per_metre,
+        yppu: pixels_per_metre,
+        unit: png::Unit::Meter,
+    }));
+    let data = match background {
+        Some(color) => {
+            encoder.set_color(png::ColorType::Rgb);
+            canvas.to_rgb8(color)
+        },
+        None => {
+            encoder.set_color(png::ColorType::Rgba);
+            canvas.to_rgba8()
+        },
+    };
+    let mut png_writer = encoder.write_header().map_err(io::Error::from)?;
+    png_writer.write_image_data(&data).map_err(io::Error::from)?;
+    png_writer.finish().map_err(io::Error::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern() -> Pattern {
+        Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"")),
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(10., 5.)])],
+            }],
+        }
+    }
+
+    fn decode(data: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let decoder = png::Decoder::new(data);
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        buf.truncate(info.buffer_size());
+        (info, buf)
+    }
+
+    #[test]
+    fn test_transparent() {
+        let mut data = vec![];
+        PngPatternWriter::default()
+            .write_pattern(&pattern(), &mut data)
+            .unwrap();
+        let (info, pixels) = decode(&data);
+        assert_eq!(info.color_type, png::ColorType::Rgba);
+        // 14mm x 9mm at 96 dpi.
+        assert_eq!((info.width, info.height), (53, 35));
+        assert_eq!(&pixels[..4], &[0, 0, 0, 0]);
+        assert!(pixels.chunks(4).any(|pixel| pixel == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_background() {
+        let mut data = vec![];
+        PngPatternWriter::new(PngWriterOptions {
+            dpi: 254.,
+            background: Some(Color::rgb(0, 0, 255)),
+            ..PngWriterOptions::default()
+        })
+        .write_pattern(&pattern(), &mut data)
+        .unwrap();
+        let (info, pixels) = decode(&data);
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!((info.width, info.height), (140, 90));
+        assert_eq!(&pixels[..3], &[0, 0, 255]);
+    }
+
+    #[test]
+    fn test_shaded() {
+        let mut data = vec![];
+        PngPatternWriter::new(PngWriterOptions {
+            dpi: 254.,
+            style: RenderStyle::Shaded,
+            ..PngWriterOptions::default()
+        })
+        .write_pattern(&pattern(), &mut data)
+        .unwrap();
+        let (_, pixels) = decode(&data);
+        let mut reds: Vec<_> = pixels
+            .chunks(4)
+            .filter(|pixel| pixel[3] == 255)
+            .map(|pixel| pixel[0])
+            .collect();
+        reds.sort();
+        reds.dedup();
+        // Lit from the side; so there's more than one shade of red.
+        assert!(reds.len() > 10);
+    }
+
+    #[test]
+    fn test_too_big() {
+        let mut pattern = pattern();
+        pattern.color_groups[0].stitch_groups[0]
+            .stitches
+            .push(Stitch::new(1e6, 1e6));
+        let mut data = vec![];
+        assert!(PngPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
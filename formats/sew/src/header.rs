@@ -0,0 +1,70 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
+
+use embroidery_janome::{closest_jef_thread, jef_thread};
+
+/// The stitches always start here; everything after the palette is padding.
+pub const STITCH_OFFSET: usize = 0x1D78;
+/// As many palette indexes as will fit between the color count and the stitches.
+pub const MAX_COLORS: usize = (STITCH_OFFSET - 2) / 2;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternHeader {
+    /// One thread for each color; stored as an index into the JEF palette.
+    pub threads: Vec<Thread>,
+}
+
+impl PatternHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let number_of_colors = usize::from(read_int!(file, u16, LittleEndian)?);
+        if number_of_colors > MAX_COLORS {
+            return Err(ReadError::invalid_format(format!(
+                "Too many colors({}) to fit in the header",
+                number_of_colors
+            )));
+        }
+        let mut threads = Vec::with_capacity(number_of_colors);
+        for _ in 0..number_of_colors {
+            threads.push(jef_thread(usize::from(read_int!(file, u16, LittleEndian)?)));
+        }
+        read_exact!(file, vec![_; STITCH_OFFSET - 2 - 2 * number_of_colors])?;
+        Ok(PatternHeader { threads })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_u16::<LittleEndian>(self.threads.len() as u16)?;
+        for thread in &self.threads {
+            file.write_u16::<LittleEndian>(closest_jef_thread(thread) as u16)?;
+        }
+        file.write_all(&vec![0; STITCH_OFFSET - 2 - 2 * self.threads.len()])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = PatternHeader {
+            threads: vec![jef_thread(10), jef_thread(2)],
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(data.len(), STITCH_OFFSET);
+        assert_eq!(data[..6], [0x02, 0x00, 0x0A, 0x00, 0x02, 0x00]);
+        assert_eq!(PatternHeader::build(&mut &data[..]).unwrap(), header);
+    }
+
+    #[test]
+    fn test_too_many_colors() {
+        let mut data = vec![0; STITCH_OFFSET];
+        data[0] = 0xFF;
+        data[1] = 0xFF;
+        assert!(PatternHeader::build(&mut &data[..]).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+mod header;
+mod read;
+mod stitch_info;
+mod write;
+
+pub use read::SewPatternReader;
+pub use write::SewPatternWriter;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+const NAME: &str = "sew";
+const EXTENSIONS: [&str; 1] = ["sew"];
+
+#[derive(Default)]
+pub struct SewPatternFormat {}
+
+impl PatternFormat for SewPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(SewPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(SewPatternWriter::default()))
+    }
+}
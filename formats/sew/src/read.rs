@@ -0,0 +1,153 @@
+use std::io::Read;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::ReadByteIterator;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+
+use crate::header::PatternHeader;
+use crate::stitch_info::{read_stitch, StitchInformation, StitchType};
+
+#[derive(Default)]
+pub struct SewPatternReader {}
+
+impl PatternReader for SewPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        match PatternHeader::build(item) {
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let header = PatternHeader::build(item)?;
+
+        let mut iter = ReadByteIterator::new(item);
+        let color_groups = read_stitches(&header.threads, &mut iter)?;
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups,
+        })
+    }
+}
+
+fn read_stitches(threads: &[Thread], item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<ColorGroup>> {
+    let mut thread_iter = threads.iter();
+    let mut grouper = StitchGrouper::new();
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(stitch) = read_stitch(item)? {
+        let (dx, dy, stitch_type) = match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => (i32::from(dx), i32::from(dy), stitch_type),
+            StitchInformation::End => break,
+        };
+        cx += dx;
+        cy += dy;
+        let to = from_tenths(cx, cy);
+        match stitch_type {
+            StitchType::Regular => grouper.stitch(to),
+            StitchType::Jump => {
+                if stitch.is_trim() {
+                    grouper.trim();
+                }
+                grouper.jump(to);
+            },
+            StitchType::ColorChange => {
+                // Each color change moves onto the next thread, even if nothing was sewn with the
+                // current one.
+                grouper.end_color(thread_iter.next().cloned());
+                grouper.jump(to);
+            },
+        }
+    }
+    Ok(grouper.finish(thread_iter.next().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::STITCH_OFFSET;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let blue = Thread::new_str(Color::rgb(0, 0, 240), &"Blue", &"");
+        let data = [
+            0x0A, 0x00, // Stitch to (1.0, 0.0)
+            0x00, 0x0A, // Stitch to (1.0, 1.0)
+            0x80, 0x04, 0x00, 0x00, // Trim
+            0x80, 0x04, 0x14, 0x00, // Jump to (3.0, 1.0)
+            0x00, 0xF6, // Stitch to (3.0, 0.0)
+            0x80, 0x01, 0xF6, 0x00, // Color change, moving to (2.0, 0.0)
+            0x0A, 0x0A, // Stitch to (3.0, 1.0)
+            0x80, 0x10, // End
+            0x0A, 0x0A, // Ignored
+        ];
+        let color_groups = read_stitches(&[red.clone(), blue.clone()], to_u8_iter!(data)).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(blue),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_pattern() {
+        let mut data = vec![0; STITCH_OFFSET];
+        // One color; palette index 10.
+        data[0] = 0x01;
+        data[2] = 0x0A;
+        data.extend_from_slice(&[0x0A, 0x00, 0x0A, 0x0A, 0x80, 0x10]);
+
+        let pattern = SewPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(pattern.color_groups.len(), 1);
+        let thread = pattern.color_groups[0].thread.as_ref().unwrap();
+        assert_eq!(thread.name, "Red");
+        assert_eq!(thread.color, Color::rgb(255, 0, 0));
+        assert_eq!(
+            pattern.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0), Stitch::new(2.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = SewPatternReader::default();
+        assert!(!reader
+            .is_loadable(&mut &b"LA:crown FS 40     \rST:   4562\r"[..])
+            .unwrap());
+    }
+}
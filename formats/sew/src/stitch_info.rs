@@ -0,0 +1,120 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// The same encoding as JEF: every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm
+// increments; except when the first byte is `ESCAPE`, in which case the second byte is a command
+// which is (usually) followed by a regular `(dx, dy)` pair.
+pub const ESCAPE: u8 = 0x80;
+pub const COLOR_CHANGE: u8 = 0x01;
+pub const JUMP: u8 = 0x04;
+// Some software writes JEF's jump command instead.
+const JUMP_ALT: u8 = 0x02;
+pub const END: u8 = 0x10;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    ColorChange,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchInformation {
+    Move(i8, i8, StitchType),
+    End,
+}
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            // -128 is reserved as it's the same byte as `ESCAPE`.
+            StitchInformation::Move(dx, dy, _) if dx == i8::MIN || dy == i8::MIN => None,
+            StitchInformation::Move(dx, dy, StitchType::Regular) => Some(vec![dx as u8, dy as u8]),
+            StitchInformation::Move(dx, dy, StitchType::Jump) => Some(vec![ESCAPE, JUMP, dx as u8, dy as u8]),
+            StitchInformation::Move(dx, dy, StitchType::ColorChange) => {
+                Some(vec![ESCAPE, COLOR_CHANGE, dx as u8, dy as u8])
+            },
+            StitchInformation::End => Some(vec![ESCAPE, END]),
+        }
+    }
+
+    /// A jump that doesn't go anywhere is a trim.
+    pub fn is_trim(self) -> bool {
+        self == StitchInformation::Move(0, 0, StitchType::Jump)
+    }
+}
+
+pub fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let (first, second) = match (in_bytes.next(), in_bytes.next()) {
+        (Some(first), Some(second)) => (first, second),
+        _ => return Ok(None),
+    };
+    if first != ESCAPE {
+        return Ok(Some(StitchInformation::Move(
+            first as i8,
+            second as i8,
+            StitchType::Regular,
+        )));
+    }
+    let stitch_type = match second {
+        END => return Ok(Some(StitchInformation::End)),
+        COLOR_CHANGE => StitchType::ColorChange,
+        JUMP | JUMP_ALT => StitchType::Jump,
+        cmd => {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown stitch command 0x{:02X}",
+                cmd
+            )))
+        },
+    };
+    match (in_bytes.next(), in_bytes.next()) {
+        (Some(dx), Some(dy)) => Ok(Some(StitchInformation::Move(dx as i8, dy as i8, stitch_type))),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(read_stitch(to_u8_iter!(b"")).unwrap(), None);
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x01, 0xFF])).unwrap(),
+            Some(StitchInformation::Move(1, -1, StitchType::Regular))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x04, 0x00, 0x0A])).unwrap(),
+            Some(StitchInformation::Move(0, 10, StitchType::Jump))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x02, 0x0A, 0x00])).unwrap(),
+            Some(StitchInformation::Move(10, 0, StitchType::Jump))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x01, 0x00, 0x00])).unwrap(),
+            Some(StitchInformation::Move(0, 0, StitchType::ColorChange))
+        );
+        assert_eq!(
+            read_stitch(to_u8_iter!([0x80, 0x10])).unwrap(),
+            Some(StitchInformation::End)
+        );
+        assert!(read_stitch(to_u8_iter!([0x80, 0x42, 0x00, 0x00])).is_err());
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let to_bytes = |dx, dy, st| StitchInformation::Move(dx, dy, st).to_bytes();
+        assert_eq!(to_bytes(1, -1, StitchType::Regular), Some(vec![0x01, 0xFF]));
+        assert_eq!(to_bytes(-128, 0, StitchType::Regular), None);
+        assert_eq!(to_bytes(0, 10, StitchType::Jump), Some(vec![0x80, 0x04, 0x00, 0x0A]));
+        assert_eq!(StitchInformation::End.to_bytes(), Some(vec![0x80, 0x10]));
+    }
+}
@@ -0,0 +1,109 @@
+use std::io::Write;
+
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, to_tenths};
+
+use embroidery_janome::{jef_thread, JEF_THREADS};
+
+use crate::header::{PatternHeader, MAX_COLORS};
+use crate::stitch_info::{StitchInformation, StitchType};
+
+const MAX_JUMP: i32 = 127;
+
+#[derive(Default)]
+pub struct SewPatternWriter {}
+
+impl PatternWriter for SewPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        if pattern.color_groups.len() > MAX_COLORS {
+            return Err(WriteError::too_many_colors(pattern.color_groups.len(), MAX_COLORS));
+        }
+        let stitches = into_sew_stitches(pattern)?;
+        let threads = pattern
+            .color_groups
+            .iter()
+            .enumerate()
+            .map(|(i, cg)| match &cg.thread {
+                Some(thread) => thread.clone(),
+                // Skip the placeholder at index 0.
+                None => jef_thread(1 + (i % (JEF_THREADS.len() - 1))),
+            })
+            .collect();
+        PatternHeader { threads }.write(writer)?;
+        for &st in &stitches {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&st.to_bytes().unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+fn into_sew_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            re.push(StitchInformation::Move(0, 0, StitchType::ColorChange));
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                re.append(&mut safe_jump_to(x - ox, y - oy));
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation::Move(0, 0, StitchType::Jump));
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok(re)
+}
+
+/// Split a move into as few jumps as possible; none at all if it doesn't go anywhere.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<StitchInformation> {
+    split_jump(delta_x, delta_y, MAX_JUMP)
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation::Move(dx as i8, dy as i8, StitchType::Jump))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(SewPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+use std::io::Write;
+
+use embroidery_lib::errors::WriteResult as Result;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::render::{sewout_steps, SewoutOptions, SewoutStepKind, JUMP_COLOR, TRIM_COLOR};
+
+use crate::write::escape;
+
+/// Controls how the sewing is animated; sizes are in mm.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AnimatedSvgWriterOptions {
+    pub sewout: SewoutOptions,
+    pub line_width: f64,
+    /// The space left around the pattern.
+    pub margin: f64,
+}
+
+/// Writes an SVG that sews the pattern out using SMIL animations; so it plays in a browser
+/// without any scripts.
+pub struct AnimatedSvgPatternWriter {
+    options: AnimatedSvgWriterOptions,
+}
+
+impl Default for AnimatedSvgPatternWriter {
+    fn default() -> Self {
+        AnimatedSvgPatternWriter {
+            options: AnimatedSvgWriterOptions {
+                sewout: SewoutOptions::default(),
+                line_width: 0.2,
+                margin: 10.,
+            },
+        }
+    }
+}
+
+impl AnimatedSvgPatternWriter {
+    pub fn new(options: AnimatedSvgWriterOptions) -> Self {
+        AnimatedSvgPatternWriter { options }
+    }
+}
+
+impl PatternWriter for AnimatedSvgPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+        write_pattern(pattern, &self.options, writer)
+    }
+}
+
+/// Show an element `start` seconds in, and keep it there.
+fn write_appear(writer: &mut dyn Write, start: f64) -> Result<()> {
+    writeln!(
+        writer,
+        "      <set attributeName='visibility' to='visible' begin='{:.3}s' fill='freeze' />",
+        start
+    )?;
+    Ok(())
+}
+
+/// Flash an element in and out again; starting `start` seconds in.
+fn write_flash(writer: &mut dyn Write, start: f64, duration: f64) -> Result<()> {
+    writeln!(
+        writer,
+        "      <animate attributeName='opacity' values='0;1;0' begin='{:.3}s' dur='{:.3}s' fill='freeze' />",
+        start, duration
+    )?;
+    Ok(())
+}
+
+fn write_pattern(pattern: &Pattern, options: &AnimatedSvgWriterOptions, writer: &mut dyn Write) -> Result<()> {
+    let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+    let width = max_x - min_x + 2. * options.margin;
+    let height = max_y - min_y + 2. * options.margin;
+
+    writeln!(writer, "<?xml version='1.0' encoding='UTF-8' standalone='no'?>")?;
+    writeln!(writer, "<svg")?;
+    writeln!(writer, " xmlns=\"http://www.w3.org/2000/svg\"")?;
+    writeln!(writer, " version=\"1.1\"")?;
+    writeln!(writer, " preserveAspectRatio=\"xMidYMid meet\"")?;
+    writeln!(writer, " width=\"{}mm\"", width)?;
+    writeln!(writer, " height=\"{}mm\"", height)?;
+    // The y axis is flipped so +ve y moves up, which puts the top of the pattern at -max_y.
+    writeln!(
+        writer,
+        " viewBox=\"{} {} {} {}\"",
+        min_x - options.margin,
+        -max_y - options.margin,
+        width,
+        height
+    )?;
+    writeln!(writer, ">")?;
+    for attribute in &pattern.attributes {
+        if let PatternAttribute::Title(title) = attribute {
+            writeln!(writer, "  <title>{}</title>", escape(title))?;
+        }
+    }
+    writeln!(
+        writer,
+        "  <g fill='none' stroke-width='{}' stroke-linecap='round'>",
+        options.line_width
+    )?;
+
+    let dashes = options.line_width * 5.;
+    for step in sewout_steps(pattern, &options.sewout) {
+        let (from, to) = (step.from, step.to);
+        match step.kind {
+            SewoutStepKind::Stitch(color) => {
+                writeln!(
+                    writer,
+                    "    <line x1='{}' y1='{}' x2='{}' y2='{}' stroke='{}' visibility='hidden'>",
+                    from.x, -from.y, to.x, -to.y, color
+                )?;
+                write_appear(writer, step.start)?;
+                writeln!(writer, "    </line>")?;
+            },
+            SewoutStepKind::Jump => {
+                writeln!(
+                    writer,
+                    "    <line class='emb_jump' x1='{}' y1='{}' x2='{}' y2='{}' stroke='{}' stroke-dasharray='{} {}' opacity='0'>",
+                    from.x, -from.y, to.x, -to.y, JUMP_COLOR, dashes, dashes
+                )?;
+                write_flash(writer, step.start, step.duration)?;
+                writeln!(writer, "    </line>")?;
+            },
+            SewoutStepKind::Trim => {
+                writeln!(
+                    writer,
+                    "    <circle class='emb_trim' cx='{}' cy='{}' r='{}' stroke='none' fill='{}' opacity='0'>",
+                    to.x,
+                    -to.y,
+                    options.line_width * 4.,
+                    TRIM_COLOR
+                )?;
+                write_flash(writer, step.start, step.duration)?;
+                writeln!(writer, "    </circle>")?;
+            },
+            // Nothing to draw; the pause is enough.
+            SewoutStepKind::ColorChange(_) => {},
+        }
+    }
+
+    writeln!(writer, "  </g>")?;
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animation() {
+        let pattern = Pattern {
+            name: "Sample".to_string(),
+            attributes: vec![PatternAttribute::Title("Fish & Chips".to_string())],
+            color_groups: vec![ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"1234")),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(5., -15.)]).with_cut(true),
+                    StitchGroup::new(vec![Stitch::new(0., -10.), Stitch::new(1., -10.)]),
+                ],
+            }],
+        };
+        let mut data = Vec::new();
+        AnimatedSvgPatternWriter::default()
+            .write_pattern(&pattern, &mut data)
+            .unwrap();
+        let svg = String::from_utf8(data).unwrap();
+        assert!(svg.contains("<title>Fish &amp; Chips</title>"));
+        assert!(svg.contains(
+            "    <line x1='0' y1='-0' x2='5' y2='15' stroke='#FF0000' visibility='hidden'>\n      <set attributeName='visibility' to='visible' begin='0.000s' fill='freeze' />\n"
+        ));
+        assert!(svg.contains(
+            "<animate attributeName='opacity' values='0;1;0' begin='0.010s' dur='0.250s' fill='freeze' />\n    </circle>"
+        ));
+        assert!(svg.contains("<line class='emb_jump' x1='5' y1='15' x2='0' y2='10'"));
+        assert!(svg.contains("begin='0.510s' fill='freeze'"));
+    }
+}
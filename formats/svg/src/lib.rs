@@ -1,8 +1,13 @@
+mod animate;
+mod path;
+mod read;
 mod write;
 
 use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
 
-pub use self::write::SvgPatternWriter;
+pub use self::animate::{AnimatedSvgPatternWriter, AnimatedSvgWriterOptions};
+pub use self::read::{SvgPatternReader, DEFAULT_STITCH_LENGTH};
+pub use self::write::{SvgPatternWriter, SvgWriterOptions};
 
 const NAME: &str = "svg";
 const EXTENSIONS: [&str; 1] = ["svg"];
@@ -18,7 +23,7 @@ impl PatternFormat for SvgPatternFormat {
         &EXTENSIONS
     }
     fn reader(&self) -> Option<Box<dyn PatternReader>> {
-        None
+        Some(Box::from(SvgPatternReader::default()))
     }
     fn writer(&self) -> Option<Box<dyn PatternWriter>> {
         Some(Box::from(SvgPatternWriter::default()))
@@ -0,0 +1,451 @@
+use std::f64::consts::PI;
+
+use svgtypes::{PathParser, PathSegment};
+
+pub type Point = (f64, f64);
+
+// Curves are approximated by this many straight lines before being resampled into stitches.
+const CURVE_STEPS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Line(Point),
+    /// Points along a curve, excluding the starting point.
+    Curve(Vec<Point>),
+}
+
+impl Segment {
+    fn end(&self) -> Option<Point> {
+        match self {
+            Segment::Line(p) => Some(*p),
+            Segment::Curve(points) => points.last().cloned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubPath {
+    pub start: Point,
+    pub segments: Vec<Segment>,
+}
+
+impl SubPath {
+    pub fn new(start: Point) -> Self {
+        SubPath {
+            start,
+            segments: Vec::new(),
+        }
+    }
+
+    pub fn polygon(points: &[Point], closed: bool) -> Option<Self> {
+        let (&start, rest) = points.split_first()?;
+        let mut path = SubPath::new(start);
+        path.segments.extend(rest.iter().map(|&p| Segment::Line(p)));
+        if closed {
+            path.close();
+        }
+        Some(path)
+    }
+
+    pub fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> Self {
+        let mut path = SubPath::new((cx + rx, cy));
+        let points = (1..=CURVE_STEPS)
+            .map(|i| {
+                let t = 2. * PI * (i as f64) / (CURVE_STEPS as f64);
+                (cx + rx * t.cos(), cy + ry * t.sin())
+            })
+            .collect();
+        path.segments.push(Segment::Curve(points));
+        path
+    }
+
+    pub fn end(&self) -> Point {
+        self.segments
+            .iter()
+            .rev()
+            .filter_map(Segment::end)
+            .next()
+            .unwrap_or(self.start)
+    }
+
+    pub fn close(&mut self) {
+        if self.end() != self.start {
+            self.segments.push(Segment::Line(self.start));
+        }
+    }
+
+    /// Convert into stitches no longer than `stitch_length`, after mapping each point with
+    /// `transform`. Straight lines keep their end points; curves are resampled evenly along their
+    /// length.
+    pub fn to_points(&self, transform: impl Fn(Point) -> Point, stitch_length: f64) -> Vec<Point> {
+        let mut current = transform(self.start);
+        let mut points = vec![current];
+        for segment in &self.segments {
+            match segment {
+                Segment::Line(p) => {
+                    let end = transform(*p);
+                    split_line(current, end, stitch_length, &mut points);
+                    current = end;
+                },
+                Segment::Curve(curve) => {
+                    let curve: Vec<_> = curve.iter().map(|&p| transform(p)).collect();
+                    resample_curve(current, &curve, stitch_length, &mut points);
+                    current = *curve.last().unwrap_or(&current);
+                },
+            }
+        }
+        points
+    }
+}
+
+fn distance((x1, y1): Point, (x2, y2): Point) -> f64 {
+    (x2 - x1).hypot(y2 - y1)
+}
+
+fn lerp((x1, y1): Point, (x2, y2): Point, t: f64) -> Point {
+    (x1 + (x2 - x1) * t, y1 + (y2 - y1) * t)
+}
+
+fn split_line(start: Point, end: Point, stitch_length: f64, out: &mut Vec<Point>) {
+    let steps = (distance(start, end) / stitch_length).ceil().max(1.) as usize;
+    for i in 1..steps {
+        out.push(lerp(start, end, (i as f64) / (steps as f64)));
+    }
+    out.push(end);
+}
+
+fn resample_curve(start: Point, curve: &[Point], stitch_length: f64, out: &mut Vec<Point>) {
+    let end = match curve.last() {
+        Some(&end) => end,
+        None => return,
+    };
+    let mut prev = start;
+    let total: f64 = curve
+        .iter()
+        .map(|&p| {
+            let d = distance(prev, p);
+            prev = p;
+            d
+        })
+        .sum();
+    let steps = (total / stitch_length).ceil().max(1.) as usize;
+    let step = total / (steps as f64);
+
+    // Place the points between the ends of the curve, then finish exactly on the end.
+    let mut travelled = 0.;
+    let mut placed = 1;
+    let mut prev = start;
+    for &p in curve {
+        let d = distance(prev, p);
+        while placed < steps && (placed as f64) * step <= travelled + d {
+            out.push(lerp(prev, p, ((placed as f64) * step - travelled) / d));
+            placed += 1;
+        }
+        travelled += d;
+        prev = p;
+    }
+    out.push(end);
+}
+
+fn cubic(p0: Point, p1: Point, p2: Point, p3: Point) -> Segment {
+    Segment::Curve(
+        (1..=CURVE_STEPS)
+            .map(|i| {
+                let t = (i as f64) / (CURVE_STEPS as f64);
+                let mt = 1. - t;
+                let a = mt * mt * mt;
+                let b = 3. * mt * mt * t;
+                let c = 3. * mt * t * t;
+                let d = t * t * t;
+                (
+                    a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                    a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+                )
+            })
+            .collect(),
+    )
+}
+
+fn quadratic(p0: Point, p1: Point, p2: Point) -> Segment {
+    Segment::Curve(
+        (1..=CURVE_STEPS)
+            .map(|i| {
+                let t = (i as f64) / (CURVE_STEPS as f64);
+                let mt = 1. - t;
+                let a = mt * mt;
+                let b = 2. * mt * t;
+                let c = t * t;
+                (a * p0.0 + b * p1.0 + c * p2.0, a * p0.1 + b * p1.1 + c * p2.1)
+            })
+            .collect(),
+    )
+}
+
+fn angle((ux, uy): Point, (vx, vy): Point) -> f64 {
+    let a = (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    if a.is_nan() {
+        0.
+    } else {
+        a
+    }
+}
+
+// See https://www.w3.org/TR/SVG11/implnote.html#ArcConversionEndpointToCenter
+#[allow(clippy::too_many_arguments)]
+fn arc(from: Point, rx: f64, ry: f64, x_axis_rotation: f64, large_arc: bool, sweep: bool, to: Point) -> Segment {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if from == to {
+        return Segment::Curve(Vec::new());
+    }
+    if rx == 0. || ry == 0. {
+        return Segment::Line(to);
+    }
+    let (sin_phi, cos_phi) = x_axis_rotation.to_radians().sin_cos();
+    let dx = (from.0 - to.0) / 2.;
+    let dy = (from.1 - to.1) / 2.;
+    let x1 = cos_phi * dx + sin_phi * dy;
+    let y1 = -sin_phi * dx + cos_phi * dy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1. {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = (num / den).max(0.).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let cx = cos_phi * cx1 - sin_phi * cy1 + (from.0 + to.0) / 2.;
+    let cy = sin_phi * cx1 + cos_phi * cy1 + (from.1 + to.1) / 2.;
+
+    let start_vec = ((x1 - cx1) / rx, (y1 - cy1) / ry);
+    let end_vec = ((-x1 - cx1) / rx, (-y1 - cy1) / ry);
+    let start_angle = angle((1., 0.), start_vec);
+    let mut sweep_angle = angle(start_vec, end_vec);
+    if !sweep && sweep_angle > 0. {
+        sweep_angle -= 2. * PI;
+    } else if sweep && sweep_angle < 0. {
+        sweep_angle += 2. * PI;
+    }
+
+    let steps = ((sweep_angle.abs() / (2. * PI)) * (CURVE_STEPS as f64)).ceil().max(1.) as usize;
+    let mut points: Vec<_> = (1..steps)
+        .map(|i| {
+            let t = start_angle + sweep_angle * (i as f64) / (steps as f64);
+            let (sin_t, cos_t) = t.sin_cos();
+            (
+                cx + rx * cos_phi * cos_t - ry * sin_phi * sin_t,
+                cy + rx * sin_phi * cos_t + ry * cos_phi * sin_t,
+            )
+        })
+        .collect();
+    points.push(to);
+    Segment::Curve(points)
+}
+
+/// Parse the `d` attribute of a `<path>` into its sub-paths. Parsing stops at the first error,
+/// which matches how browsers render a broken path.
+pub fn parse_path(data: &str) -> Vec<SubPath> {
+    let mut paths: Vec<SubPath> = Vec::new();
+    let mut current = (0., 0.);
+    // The second control point of the previous curve, for the smooth curve commands.
+    let mut last_cubic: Option<Point> = None;
+    let mut last_quad: Option<Point> = None;
+
+    let reflect = |control: Option<Point>, (x, y): Point| match control {
+        Some((cx, cy)) => (2. * x - cx, 2. * y - cy),
+        None => (x, y),
+    };
+
+    for segment in PathParser::from(data) {
+        let offset = |x: f64, y: f64, abs: bool| if abs { (x, y) } else { (current.0 + x, current.1 + y) };
+        let mut next_cubic = None;
+        let mut next_quad = None;
+        let new_segment = match segment {
+            PathSegment::MoveTo { abs, x, y } => {
+                current = offset(x, y, abs);
+                paths.push(SubPath::new(current));
+                None
+            },
+            PathSegment::LineTo { abs, x, y } => Some(Segment::Line(offset(x, y, abs))),
+            PathSegment::HorizontalLineTo { abs, x } => Some(Segment::Line(if abs {
+                (x, current.1)
+            } else {
+                (current.0 + x, current.1)
+            })),
+            PathSegment::VerticalLineTo { abs, y } => Some(Segment::Line(if abs {
+                (current.0, y)
+            } else {
+                (current.0, current.1 + y)
+            })),
+            PathSegment::CurveTo {
+                abs,
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                let c2 = offset(x2, y2, abs);
+                next_cubic = Some(c2);
+                Some(cubic(current, offset(x1, y1, abs), c2, offset(x, y, abs)))
+            },
+            PathSegment::SmoothCurveTo { abs, x2, y2, x, y } => {
+                let c2 = offset(x2, y2, abs);
+                next_cubic = Some(c2);
+                Some(cubic(current, reflect(last_cubic, current), c2, offset(x, y, abs)))
+            },
+            PathSegment::Quadratic { abs, x1, y1, x, y } => {
+                let c = offset(x1, y1, abs);
+                next_quad = Some(c);
+                Some(quadratic(current, c, offset(x, y, abs)))
+            },
+            PathSegment::SmoothQuadratic { abs, x, y } => {
+                let c = reflect(last_quad, current);
+                next_quad = Some(c);
+                Some(quadratic(current, c, offset(x, y, abs)))
+            },
+            PathSegment::EllipticalArc {
+                abs,
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            } => Some(arc(
+                current,
+                rx,
+                ry,
+                x_axis_rotation,
+                large_arc,
+                sweep,
+                offset(x, y, abs),
+            )),
+            PathSegment::ClosePath { .. } => {
+                if let Some(path) = paths.last_mut() {
+                    path.close();
+                    current = path.start;
+                    // Drawing after a close starts a new sub-path at the same point.
+                    paths.push(SubPath::new(current));
+                }
+                None
+            },
+        };
+        last_cubic = next_cubic;
+        last_quad = next_quad;
+        if let Some(new_segment) = new_segment {
+            if paths.is_empty() {
+                paths.push(SubPath::new(current));
+            }
+            if let Some(end) = new_segment.end() {
+                current = end;
+            }
+            paths.last_mut().unwrap().segments.push(new_segment);
+        }
+    }
+    paths.retain(|path| !path.segments.is_empty());
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Point, b: Point) {
+        assert!(distance(a, b) < 1e-6, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(
+            parse_path("M 1 2 L 3 4 h 2 v -1 m 5 5 l 1 1"),
+            vec![
+                SubPath {
+                    start: (1., 2.),
+                    segments: vec![
+                        Segment::Line((3., 4.)),
+                        Segment::Line((5., 4.)),
+                        Segment::Line((5., 3.))
+                    ],
+                },
+                SubPath {
+                    start: (10., 8.),
+                    segments: vec![Segment::Line((11., 9.))],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_close() {
+        assert_eq!(
+            parse_path("M0 0 H 5 V 5 Z l 1 1"),
+            vec![
+                SubPath {
+                    start: (0., 0.),
+                    segments: vec![
+                        Segment::Line((5., 0.)),
+                        Segment::Line((5., 5.)),
+                        Segment::Line((0., 0.))
+                    ],
+                },
+                SubPath {
+                    start: (0., 0.),
+                    segments: vec![Segment::Line((1., 1.))],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_curves_end_on_target() {
+        let paths = parse_path("M0 0 C 0 5 5 5 5 0 S 10 -5 10 0 Q 15 5 20 0 T 30 0 A 5 5 0 0 1 40 0");
+        assert_eq!(paths.len(), 1);
+        let ends: Vec<_> = paths[0].segments.iter().map(|s| s.end().unwrap()).collect();
+        assert_eq!(ends, vec![(5., 0.), (10., 0.), (20., 0.), (30., 0.), (40., 0.)]);
+    }
+
+    #[test]
+    fn test_arc_is_semicircle() {
+        if let Segment::Curve(points) = arc((0., 0.), 5., 5., 0., false, true, (10., 0.)) {
+            assert_close(points[CURVE_STEPS / 4 - 1], (5., -5.));
+            for &p in &points {
+                assert!((distance(p, (5., 0.)) - 5.).abs() < 1e-6);
+            }
+        } else {
+            panic!("Expected a curve");
+        }
+    }
+
+    #[test]
+    fn test_to_points_splits_lines() {
+        let path = SubPath::polygon(&[(0., 0.), (10., 0.), (10., 1.)], false).unwrap();
+        let points = path.to_points(|p| p, 4.);
+        let expected = [(0., 0.), (10. / 3., 0.), (20. / 3., 0.), (10., 0.), (10., 1.)];
+        assert_eq!(points.len(), expected.len());
+        for (&a, &b) in points.iter().zip(expected.iter()) {
+            assert_close(a, b);
+        }
+    }
+
+    #[test]
+    fn test_to_points_resamples_curves() {
+        let radius = 10.;
+        let points = SubPath::ellipse(0., 0., radius, radius).to_points(|p| p, 2.);
+        // The circumference is ~62.8mm, so 32 stitches of just under 2mm.
+        assert_eq!(points.len(), 33);
+        assert_close(points[0], (radius, 0.));
+        assert_close(*points.last().unwrap(), (radius, 0.));
+        for pair in points.windows(2) {
+            let d = distance(pair[0], pair[1]);
+            assert!(d <= 2. && d > 1.9, "{}", d);
+        }
+    }
+}
@@ -0,0 +1,475 @@
+use std::io::Read;
+use std::str::FromStr;
+
+use roxmltree::{Document, Node};
+use svgtypes::{Length, LengthUnit, PointsParser, Transform, ViewBox};
+
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+
+use crate::path::{parse_path, SubPath};
+
+/// The length of each stitch when converting lines and curves into running stitches; in mm.
+pub const DEFAULT_STITCH_LENGTH: f64 = 2.5;
+
+// SVG user units are CSS pixels, of which there are 96 to the inch.
+const MM_PER_PX: f64 = 25.4 / 96.;
+
+/// Reads the stroked outlines of an SVG as running stitches; fills are ignored.
+pub struct SvgPatternReader {
+    stitch_length: f64,
+}
+
+impl Default for SvgPatternReader {
+    fn default() -> Self {
+        SvgPatternReader {
+            stitch_length: DEFAULT_STITCH_LENGTH,
+        }
+    }
+}
+
+impl SvgPatternReader {
+    pub fn with_stitch_length(self, stitch_length: f64) -> Self {
+        Self { stitch_length }
+    }
+}
+
+impl PatternReader for SvgPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        let mut data = Vec::with_capacity(1024);
+        item.take(1024).read_to_end(&mut data)?;
+        Ok(String::from_utf8_lossy(&data).contains("<svg"))
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        if self.stitch_length.is_nan() || self.stitch_length <= 0. {
+            return Err(ReadError::invalid_format("The stitch length must be greater than 0"));
+        }
+        let mut data = String::new();
+        if item.read_to_string(&mut data).is_err() {
+            return Err(ReadError::invalid_format("SVG files must be valid UTF-8"));
+        }
+        let document =
+            Document::parse(&data).map_err(|e| ReadError::invalid_format(format!("Invalid SVG document: {}", e)))?;
+        let root = document.root_element();
+        if root.tag_name().name() != "svg" {
+            return Err(ReadError::invalid_format("The root element is not an <svg>"));
+        }
+
+        let mut builder = PatternBuilder {
+            stitch_length: self.stitch_length,
+            color_groups: Vec::new(),
+        };
+        let style = Style {
+            transform: root_transform(root),
+            stroke: None,
+        };
+        for child in root.children().filter(Node::is_element) {
+            builder.visit(child, &style);
+        }
+
+        let mut attributes = read_metadata(root);
+        let title = attributes.iter().find_map(|a| match a {
+            PatternAttribute::Title(title) => Some(title.clone()),
+            _ => None,
+        });
+        let title = match title {
+            Some(title) => Some(title),
+            None => {
+                let title = root
+                    .children()
+                    .find(|n| n.has_tag_name("title"))
+                    .and_then(|n| n.text())
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .map(str::to_string);
+                if let Some(ref title) = title {
+                    attributes.insert(0, PatternAttribute::Title(title.clone()));
+                }
+                title
+            },
+        };
+        Ok(Pattern {
+            name: title.unwrap_or_else(|| "Untitled".to_string()),
+            attributes,
+            color_groups: builder.color_groups,
+        })
+    }
+}
+
+fn length_to_mm(length: Length) -> Option<f64> {
+    let scale = match length.unit {
+        LengthUnit::None | LengthUnit::Px => MM_PER_PX,
+        LengthUnit::Mm => 1.,
+        LengthUnit::Cm => 10.,
+        LengthUnit::In => 25.4,
+        LengthUnit::Pt => 25.4 / 72.,
+        LengthUnit::Pc => 25.4 / 6.,
+        LengthUnit::Em | LengthUnit::Ex | LengthUnit::Percent => return None,
+    };
+    Some(length.num * scale)
+}
+
+/// Read the Dublin Core metadata that Inkscape, and `SvgPatternWriter`, store in the `<metadata>`.
+fn read_metadata(root: Node) -> Vec<PatternAttribute> {
+    let text = |node: Node| {
+        node.descendants()
+            .filter(|n| n.is_text())
+            .filter_map(|n| n.text())
+            .collect::<String>()
+            .trim()
+            .to_string()
+    };
+    let work = root
+        .children()
+        .filter(|n| n.has_tag_name("metadata"))
+        .flat_map(|n| n.descendants())
+        .find(|n| n.has_tag_name("Work"));
+    let mut attributes = Vec::new();
+    for node in work.iter().flat_map(|work| work.children()) {
+        let value = text(node);
+        if value.is_empty() {
+            continue;
+        }
+        match node.tag_name().name() {
+            "title" => attributes.push(PatternAttribute::Title(value)),
+            "creator" => attributes.push(PatternAttribute::Author(value)),
+            "rights" => attributes.push(PatternAttribute::Copyright(value)),
+            "description" => {
+                for line in value.lines() {
+                    let mut parts = line.splitn(2, ": ");
+                    if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                        attributes.push(PatternAttribute::Arbitary(key.to_string(), value.to_string()));
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    attributes
+}
+
+/// Maps the user units of the root element into mm. The origin of the view box is ignored, so
+/// that the pattern keeps the position it was drawn at.
+fn root_transform(root: Node) -> Transform {
+    let size = |name| {
+        root.attribute(name)
+            .and_then(|v| Length::from_str(v).ok())
+            .and_then(length_to_mm)
+    };
+    let view_box = root
+        .attribute("viewBox")
+        .and_then(|v| ViewBox::from_str(v).ok())
+        .filter(|vb| vb.w > 0. && vb.h > 0.);
+    match view_box {
+        Some(vb) => {
+            let sx = size("width").map(|w| w / vb.w);
+            let sy = size("height").map(|h| h / vb.h);
+            let sx = sx.or(sy).unwrap_or(MM_PER_PX);
+            let sy = sy.unwrap_or(sx);
+            Transform::new(sx, 0., 0., sy, 0., 0.)
+        },
+        None => Transform::new(MM_PER_PX, 0., 0., MM_PER_PX, 0., 0.),
+    }
+}
+
+#[derive(Clone)]
+struct Style {
+    transform: Transform,
+    // `None` when the stroke is not drawn.
+    stroke: Option<Color>,
+}
+
+/// Get a presentation attribute; preferring the value from the `style` attribute.
+fn property<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    let from_style = node.attribute("style").and_then(|style| {
+        style
+            .split(';')
+            .filter_map(|decl| {
+                let mut parts = decl.splitn(2, ':');
+                match (parts.next(), parts.next()) {
+                    (Some(key), Some(value)) if key.trim() == name => Some(value.trim()),
+                    _ => None,
+                }
+            })
+            .next_back()
+    });
+    from_style.or_else(|| node.attribute(name).map(str::trim))
+}
+
+fn number(node: Node, name: &str) -> f64 {
+    node.attribute(name)
+        .and_then(|v| Length::from_str(v).ok())
+        .map(|l| l.num)
+        .unwrap_or(0.)
+}
+
+impl Style {
+    fn for_node(&self, node: Node) -> Self {
+        let mut style = self.clone();
+        if let Some(transform) = node.attribute("transform").and_then(|t| Transform::from_str(t).ok()) {
+            style.transform.append(&transform);
+        }
+        match property(node, "stroke") {
+            None | Some("inherit") => {},
+            Some("none") => style.stroke = None,
+            // Gradients and other paints are drawn in black, as there's no single thread color.
+            Some(value) => {
+                style.stroke = Some(svgtypes::Color::from_str(value).map_or(Color::rgb(0, 0, 0), into_color))
+            },
+        }
+        style
+    }
+}
+
+fn into_color(c: svgtypes::Color) -> Color {
+    Color::rgb(c.red, c.green, c.blue)
+}
+
+fn shape_paths(node: Node) -> Vec<SubPath> {
+    match node.tag_name().name() {
+        "path" => node.attribute("d").map(parse_path).unwrap_or_default(),
+        "line" => vec![SubPath::polygon(
+            &[
+                (number(node, "x1"), number(node, "y1")),
+                (number(node, "x2"), number(node, "y2")),
+            ],
+            false,
+        )
+        .unwrap()],
+        "polyline" | "polygon" => {
+            let points: Vec<_> = node
+                .attribute("points")
+                .map(|p| PointsParser::from(p).collect())
+                .unwrap_or_default();
+            SubPath::polygon(&points, node.has_tag_name("polygon"))
+                .into_iter()
+                .collect()
+        },
+        "rect" => {
+            let (x, y) = (number(node, "x"), number(node, "y"));
+            let (w, h) = (number(node, "width"), number(node, "height"));
+            if w <= 0. || h <= 0. {
+                return Vec::new();
+            }
+            vec![SubPath::polygon(&[(x, y), (x + w, y), (x + w, y + h), (x, y + h)], true).unwrap()]
+        },
+        "circle" => {
+            let r = number(node, "r");
+            if r <= 0. {
+                return Vec::new();
+            }
+            vec![SubPath::ellipse(number(node, "cx"), number(node, "cy"), r, r)]
+        },
+        "ellipse" => {
+            let (rx, ry) = (number(node, "rx"), number(node, "ry"));
+            if rx <= 0. || ry <= 0. {
+                return Vec::new();
+            }
+            vec![SubPath::ellipse(number(node, "cx"), number(node, "cy"), rx, ry)]
+        },
+        _ => Vec::new(),
+    }
+}
+
+struct PatternBuilder {
+    stitch_length: f64,
+    color_groups: Vec<ColorGroup>,
+}
+
+impl PatternBuilder {
+    fn visit(&mut self, node: Node, parent: &Style) {
+        // Needle points and jumps drawn by `SvgPatternWriter` aren't part of the design.
+        let ignored = node
+            .attribute("class")
+            .is_some_and(|c| c.split_whitespace().any(|c| c == "emb_ignore"));
+        if ignored || property(node, "display") == Some("none") {
+            return;
+        }
+        let style = parent.for_node(node);
+        match node.tag_name().name() {
+            "g" | "a" | "switch" | "svg" => {
+                for child in node.children().filter(Node::is_element) {
+                    self.visit(child, &style);
+                }
+            },
+            _ => {
+                if let Some(color) = style.stroke {
+                    for path in shape_paths(node) {
+                        self.add_path(color, &path, &style.transform);
+                    }
+                }
+            },
+        }
+    }
+
+    fn add_path(&mut self, color: Color, path: &SubPath, transform: &Transform) {
+        let stitches: Vec<_> = path
+            .to_points(|(x, y)| transform.apply(x, y), self.stitch_length)
+            .into_iter()
+            // The y axis points down the page.
+            .map(|(x, y)| Stitch::new(x, -y))
+            .collect();
+        let stitch_group = StitchGroup::new(stitches).with_trim(true);
+
+        // Consecutive shapes of the same color are sewn together.
+        match self.color_groups.last_mut() {
+            Some(ColorGroup {
+                thread: Some(thread),
+                stitch_groups,
+            }) if thread.color == color => stitch_groups.push(stitch_group),
+            _ => self.color_groups.push(ColorGroup {
+                thread: Some(Thread::new_str(color, &color, &"")),
+                stitch_groups: vec![stitch_group],
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn read(svg: &str) -> Pattern {
+        SvgPatternReader::default()
+            .with_stitch_length(100.)
+            .read_pattern(&mut Cursor::new(svg))
+            .unwrap()
+    }
+
+    fn stitches(sg: &StitchGroup) -> Vec<(f64, f64)> {
+        sg.stitches.iter().map(|s| (s.x, s.y)).collect()
+    }
+
+    fn assert_stitches_close(sg: &StitchGroup, expected: &[(f64, f64)]) {
+        assert_eq!(sg.stitches.len(), expected.len());
+        for (s, &(x, y)) in sg.stitches.iter().zip(expected.iter()) {
+            assert!(
+                (s.x - x).abs() < 1e-9 && (s.y - y).abs() < 1e-9,
+                "{:?} != {:?}",
+                s,
+                (x, y)
+            );
+        }
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = SvgPatternReader::default();
+        assert!(reader
+            .is_loadable(&mut Cursor::new(
+                "<?xml version='1.0'?>\n<svg xmlns='http://www.w3.org/2000/svg'/>"
+            ))
+            .unwrap());
+        assert!(!reader.is_loadable(&mut Cursor::new("#,\"Title:\",\"Hi\"")).unwrap());
+    }
+
+    #[test]
+    fn test_read_shapes_and_colors() {
+        let pattern = read(
+            "<svg xmlns='http://www.w3.org/2000/svg' width='100mm' height='100mm' viewBox='0 0 100 100'>
+              <title>Shapes</title>
+              <g stroke='red'>
+                <line x1='0' y1='0' x2='10' y2='0' />
+                <polyline points='0,10 10,10 10,20' />
+                <rect x='1' y='1' width='2' height='3' fill='blue' stroke='none' />
+              </g>
+              <polygon points='0,0 1,0 1,1' style='stroke: #00ff00; fill: none' />
+              <path d='M 5 5 l 1 0' stroke='#00ff00' transform='translate(1, 2)' />
+              <g stroke='red' display='none'><line x2='1' /></g>
+              <defs><line stroke='red' x2='1' /></defs>
+              <g class='layer emb_ignore'><line stroke='red' x2='1' /></g>
+              <line x2='1' />
+            </svg>",
+        );
+        assert_eq!(pattern.name, "Shapes");
+        assert_eq!(pattern.attributes, vec![PatternAttribute::Title("Shapes".to_string())]);
+        assert_eq!(pattern.color_groups.len(), 2);
+
+        let red = &pattern.color_groups[0];
+        assert_eq!(
+            red.thread,
+            Some(Thread::new_str(Color::rgb(255, 0, 0), &"#FF0000", &""))
+        );
+        assert_eq!(red.stitch_groups.len(), 2);
+        assert!(red.stitch_groups[0].trim);
+        assert_eq!(stitches(&red.stitch_groups[0]), vec![(0., 0.), (10., 0.)]);
+        assert_eq!(
+            stitches(&red.stitch_groups[1]),
+            vec![(0., -10.), (10., -10.), (10., -20.)]
+        );
+
+        let green = &pattern.color_groups[1];
+        assert_eq!(green.thread.as_ref().unwrap().color, Color::rgb(0, 255, 0));
+        assert_eq!(
+            stitches(&green.stitch_groups[0]),
+            vec![(0., 0.), (1., 0.), (1., -1.), (0., 0.)]
+        );
+        assert_eq!(stitches(&green.stitch_groups[1]), vec![(6., -7.), (7., -7.)]);
+    }
+
+    #[test]
+    fn test_read_units() {
+        let pattern = read(
+            "<svg xmlns='http://www.w3.org/2000/svg' width='2in' height='2in' viewBox='10 10 192 192'>
+              <line x1='0' y1='0' x2='96' y2='0' stroke='black' />
+            </svg>",
+        );
+        assert_stitches_close(&pattern.color_groups[0].stitch_groups[0], &[(0., 0.), (25.4, 0.)]);
+
+        let pattern = read("<svg xmlns='http://www.w3.org/2000/svg'><line x2='96' stroke='black' /></svg>");
+        assert_stitches_close(&pattern.color_groups[0].stitch_groups[0], &[(0., 0.), (25.4, 0.)]);
+    }
+
+    #[test]
+    fn test_read_splits_into_stitch_length() {
+        let svg = "<svg xmlns='http://www.w3.org/2000/svg' viewBox='0 0 20 20' width='20mm' height='20mm'>
+              <line x1='0' y1='0' x2='10' y2='0' stroke='black' />
+              <circle cx='10' cy='10' r='5' stroke='black' />
+            </svg>";
+        let pattern = SvgPatternReader::default().read_pattern(&mut Cursor::new(svg)).unwrap();
+        let sgs = &pattern.color_groups[0].stitch_groups;
+        assert_eq!(sgs[0].stitches.len(), 5);
+        // A circumference of ~31.4mm.
+        assert_eq!(sgs[1].stitches.len(), 14);
+    }
+
+    #[test]
+    fn test_read_metadata() {
+        let pattern = read(
+            "<svg xmlns='http://www.w3.org/2000/svg' xmlns:rdf='http://www.w3.org/1999/02/22-rdf-syntax-ns#'
+              xmlns:cc='http://creativecommons.org/ns#' xmlns:dc='http://purl.org/dc/elements/1.1/'>
+              <title>Ignored</title>
+              <metadata><rdf:RDF><cc:Work rdf:about=''>
+                <dc:format>image/svg+xml</dc:format>
+                <dc:title>Flower</dc:title>
+                <dc:creator><cc:Agent><dc:title>Someone</dc:title></cc:Agent></dc:creator>
+                <dc:rights><cc:Agent><dc:title>CC-BY</dc:title></cc:Agent></dc:rights>
+                <dc:description>hoop: 100x100\nnot an attribute</dc:description>
+              </cc:Work></rdf:RDF></metadata>
+            </svg>",
+        );
+        assert_eq!(pattern.name, "Flower");
+        assert_eq!(
+            pattern.attributes,
+            vec![
+                PatternAttribute::Title("Flower".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Copyright("CC-BY".to_string()),
+                PatternAttribute::Arbitary("hoop".to_string(), "100x100".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let reader = SvgPatternReader::default();
+        assert!(reader.read_pattern(&mut Cursor::new("<svg")).is_err());
+        assert!(reader.read_pattern(&mut Cursor::new("<html></html>")).is_err());
+        match reader.read_pattern(&mut Cursor::new(b"\x80\x03<svg />")) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            other => panic!("Expected an invalid format error, got {:?}", other),
+        }
+    }
+}
@@ -1,124 +1,298 @@
 use std::io::Write;
 
-use palette::{Lch, Srgb};
 use svgtypes::{PathBuilder, WriteBuffer, WriteOptions};
 
 use embroidery_lib::errors::WriteResult as Result;
 use embroidery_lib::format::PatternWriter;
 use embroidery_lib::prelude::*;
+use embroidery_lib::utils::thread_colors;
 
-const LINE_WIDTH: f64 = 0.2;
-const STITCH_DIAMETER: f64 = 0.4;
-
-pub struct SvgPatternWriter {}
+/// Controls how a pattern is drawn; all sizes are in mm.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgWriterOptions {
+    /// The width of the line drawn for each stitch.
+    pub line_width: f64,
+    /// Draw a dot where the needle goes through the fabric.
+    pub show_needle_points: bool,
+    pub needle_point_diameter: f64,
+    /// Draw a dashed line for each jump and trim between groups of stitches.
+    pub show_jumps: bool,
+    /// The space left around the pattern.
+    pub margin: f64,
+}
 
-impl Default for SvgPatternWriter {
+impl Default for SvgWriterOptions {
     fn default() -> Self {
-        SvgPatternWriter {}
+        SvgWriterOptions {
+            line_width: 0.2,
+            show_needle_points: true,
+            needle_point_diameter: 0.4,
+            show_jumps: false,
+            margin: 10.,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SvgPatternWriter {
+    options: SvgWriterOptions,
+}
+
+impl SvgPatternWriter {
+    pub fn new(options: SvgWriterOptions) -> Self {
+        SvgPatternWriter { options }
     }
 }
 
 impl PatternWriter for SvgPatternWriter {
     fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
-        write_pattern(pattern, writer)
+        write_pattern(pattern, &self.options, writer)
     }
 }
 
-fn generate_color(idx: usize, total: usize) -> Srgb {
-    Lch::new(50., 100., (idx as f32) * 360.0 / (total as f32)).into()
+/// Escape text for use in XML; dropping the control characters that XML doesn't allow at all.
+pub(crate) fn escape(s: &str) -> String {
+    s.chars()
+        .filter(|&c| !c.is_control() || c == '\t' || c == '\n' || c == '\r')
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\'', "&apos;")
+        .replace('"', "&quot;")
 }
 
-fn write_pattern(pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+fn write_metadata(pattern: &Pattern, writer: &mut dyn Write) -> Result<()> {
+    let mut description = Vec::new();
+    writeln!(writer, "  <metadata>")?;
+    writeln!(writer, "    <rdf:RDF>")?;
+    writeln!(writer, "      <cc:Work rdf:about=''>")?;
+    writeln!(writer, "        <dc:format>image/svg+xml</dc:format>")?;
+    writeln!(
+        writer,
+        "        <dc:type rdf:resource='http://purl.org/dc/dcmitype/StillImage' />"
+    )?;
+    for attribute in &pattern.attributes {
+        match attribute {
+            PatternAttribute::Title(title) => writeln!(writer, "        <dc:title>{}</dc:title>", escape(title))?,
+            PatternAttribute::Author(author) => writeln!(
+                writer,
+                "        <dc:creator><cc:Agent><dc:title>{}</dc:title></cc:Agent></dc:creator>",
+                escape(author)
+            )?,
+            PatternAttribute::Copyright(copyright) => writeln!(
+                writer,
+                "        <dc:rights><cc:Agent><dc:title>{}</dc:title></cc:Agent></dc:rights>",
+                escape(copyright)
+            )?,
+            PatternAttribute::Arbitary(key, value) => description.push(format!("{}: {}", key, value)),
+        }
+    }
+    if !description.is_empty() {
+        writeln!(
+            writer,
+            "        <dc:description>{}</dc:description>",
+            escape(&description.join("\n"))
+        )?;
+    }
+    writeln!(writer, "      </cc:Work>")?;
+    writeln!(writer, "    </rdf:RDF>")?;
+    writeln!(writer, "  </metadata>")?;
+    Ok(())
+}
+
+fn write_pattern(pattern: &Pattern, options: &SvgWriterOptions, writer: &mut dyn Write) -> Result<()> {
     let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
-    let width = max_x - min_x;
-    let height = max_y - min_y;
+    let width = max_x - min_x + 2. * options.margin;
+    let height = max_y - min_y + 2. * options.margin;
 
     writeln!(writer, "<?xml version='1.0' encoding='UTF-8' standalone='no'?>")?;
     writeln!(writer, "<svg")?;
     writeln!(writer, " xmlns:svg=\"http://www.w3.org/2000/svg\"")?;
     writeln!(writer, " xmlns=\"http://www.w3.org/2000/svg\"")?;
+    writeln!(
+        writer,
+        " xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\""
+    )?;
+    writeln!(writer, " xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\"")?;
+    writeln!(writer, " xmlns:cc=\"http://creativecommons.org/ns#\"")?;
+    writeln!(writer, " xmlns:dc=\"http://purl.org/dc/elements/1.1/\"")?;
     writeln!(writer, " version=\"1.1\"")?;
     writeln!(writer, " preserveAspectRatio=\"xMidYMid meet\"")?;
     writeln!(writer, " shape-rendering='geometricPrecision'")?;
     writeln!(writer, " text-rendering='geometricPrecision'")?;
     writeln!(writer, " image-rendering='optimizeQuality'")?;
-    writeln!(writer, " width=\"{}mm\"", width + 20.)?;
-    writeln!(writer, " height=\"{}mm\"", height + 20.)?;
+    writeln!(writer, " width=\"{}mm\"", width)?;
+    writeln!(writer, " height=\"{}mm\"", height)?;
+    // The y axis is flipped so +ve y moves up, which puts the top of the pattern at -max_y.
     writeln!(
         writer,
         " viewBox=\"{} {} {} {}\"",
-        min_x - 10.,
-        -10.,
-        width + 20.,
-        height + 20.
+        min_x - options.margin,
+        -max_y - options.margin,
+        width,
+        height
     )?;
     writeln!(writer, ">")?;
 
-    // TODO: Write out metadata
-    // writeln!(writer, "  <metadata>")?;
-    // writeln!(writer, "    <rdf:RDF>")?;
-    // writeln!(writer, "      <cc:Work rdf:about=''>")?;
-    // writeln!(writer, "        <dc:format>image/svg+xml</dc:format>")?;
-    // writeln!(writer, "        <dc:type rdf:resource='http://purl.org/dc/dcmitype/StillImage' />")?;
-    // writeln!(writer, "      </cc:Work>")?;
-    // writeln!(writer, "    </rdf:RDF>")?;
-    // writeln!(writer, "  </metadata>")?;
-
-    let total_colors = pattern.color_groups.iter().filter(|cg| cg.thread == None).count();
-    let mut used_random_colors: usize = 0;
+    for attribute in &pattern.attributes {
+        if let PatternAttribute::Title(title) = attribute {
+            writeln!(writer, "  <title>{}</title>", escape(title))?;
+        }
+    }
+    write_metadata(pattern, writer)?;
+
     let opt = WriteOptions {
         remove_leading_zero: true,
         use_compact_path_notation: true,
         join_arc_to_flags: true,
         ..WriteOptions::default()
     };
+    let mut last_stitch: Option<(Stitch, bool)> = None;
 
-    for cg in pattern.color_groups.iter() {
-        // TODO: Write out stitch metadata.
-        let color: Color = if let Some(ref thread) = cg.thread {
-            // Need clone to use the color later.
-            thread.color
+    let colors = thread_colors(pattern);
+    for (idx, (cg, &color)) in pattern.color_groups.iter().zip(&colors).enumerate() {
+        let label = if let Some(ref thread) = cg.thread {
+            match (thread.name.is_empty(), thread.code.is_empty()) {
+                (true, true) => format!("{}", thread.color),
+                (false, true) => thread.name.clone(),
+                (true, false) => thread.code.clone(),
+                (false, false) => format!("{} ({})", thread.name, thread.code),
+            }
         } else {
-            used_random_colors += 1;
-            generate_color(used_random_colors - 1, total_colors).into()
+            format!("Color {}", idx + 1)
         };
-        writeln!(writer, "    <g")?;
-        writeln!(writer, "     fill='none'")?;
-        writeln!(writer, "     stroke='{}'", color)?;
-        writeln!(writer, "     stroke-width='{}'", LINE_WIDTH)?;
-        writeln!(writer, "     stroke-linecap='round'")?;
-        writeln!(writer, "     stroke-linejoin='round'")?;
-        writeln!(writer, "    >")?;
+        writeln!(writer, "  <g")?;
+        writeln!(writer, "   id='layer{}'", idx + 1)?;
+        writeln!(writer, "   inkscape:groupmode='layer'")?;
+        writeln!(writer, "   inkscape:label='{}'", escape(&label))?;
+        writeln!(writer, "   fill='none'")?;
+        writeln!(writer, "   stroke='{}'", color)?;
+        writeln!(writer, "   stroke-width='{}'", options.line_width)?;
+        writeln!(writer, "   stroke-linecap='round'")?;
+        writeln!(writer, "   stroke-linejoin='round'")?;
+        writeln!(writer, "  >")?;
 
         for sg in cg.stitch_groups.iter() {
+            if let (Some((from, trimmed)), Some(to)) = (last_stitch, sg.stitches.first()) {
+                if options.show_jumps {
+                    let dashes = options.line_width * if trimmed { 2. } else { 5. };
+                    writeln!(
+                        writer,
+                        "    <path class='emb_ignore {}' stroke-dasharray='{} {}' d='M{} {} {} {}' />",
+                        if trimmed { "emb_trim" } else { "emb_jump" },
+                        dashes,
+                        dashes,
+                        from.x,
+                        -from.y,
+                        to.x,
+                        -to.y
+                    )?;
+                }
+            }
+            if let Some(&stitch) = sg.stitches.last() {
+                last_stitch = Some((stitch, sg.trim || sg.cut));
+            }
+
             let mut path = PathBuilder::with_capacity(sg.stitches.len() + 2);
-            if let Some(stitch) = sg.stitches.get(0) {
-                path = path.move_to(stitch.x, max_y - stitch.y);
+            if let Some(stitch) = sg.stitches.first() {
+                path = path.move_to(stitch.x, -stitch.y);
+            }
+            for stitch in sg.stitches.iter().skip(1) {
+                path = path.line_to(stitch.x, -stitch.y);
             }
-            writeln!(writer, "      <g stroke='none' fill='{}' class='emb_ignore'>", color)?;
-            for (i, stitch) in sg.stitches.iter().enumerate() {
-                if i != 0 {
-                    // reverse y axis so +ve y moves up
-                    path = path.line_to(stitch.x, max_y - stitch.y);
+            if options.show_needle_points {
+                writeln!(writer, "    <g stroke='none' fill='{}' class='emb_ignore'>", color)?;
+                for stitch in sg.stitches.iter() {
+                    writeln!(
+                        writer,
+                        "      <circle cx='{}' cy='{}' r='{}' />",
+                        stitch.x,
+                        -stitch.y,
+                        options.needle_point_diameter / 2.
+                    )?;
                 }
-                writeln!(
-                    writer,
-                    "        <circle cx='{}' cy='{}' r='{}' />",
-                    stitch.x,
-                    max_y - stitch.y,
-                    STITCH_DIAMETER / 2.
-                )?;
+                writeln!(writer, "    </g>")?;
             }
-            writeln!(writer, "      </g>")?;
-            writeln!(
-                writer,
-                "      <path d='{}' />",
-                path.finalize().with_write_opt(&opt).to_string()
-            )?;
+            writeln!(writer, "    <path d='{}' />", path.finalize().with_write_opt(&opt))?;
         }
-        writeln!(writer, "    </g>")?;
+        writeln!(writer, "  </g>")?;
     }
 
     writeln!(writer, "</svg>")?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(pattern: &Pattern, options: SvgWriterOptions) -> String {
+        let mut data = Vec::new();
+        SvgPatternWriter::new(options)
+            .write_pattern(pattern, &mut data)
+            .unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    fn sample_pattern() -> Pattern {
+        Pattern {
+            name: "Sample".to_string(),
+            attributes: vec![
+                PatternAttribute::Title("Fish & Chips".to_string()),
+                PatternAttribute::Author("Someone".to_string()),
+                PatternAttribute::Arbitary("hoop".to_string(), "<100mm>\u{1a}".to_string()),
+            ],
+            color_groups: vec![ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"1234")),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(-5., -20.), Stitch::new(5., -15.)]).with_trim(true),
+                    StitchGroup::new(vec![Stitch::new(0., -10.), Stitch::new(1., -10.)]),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_view_box_contains_pattern() {
+        let svg = write(
+            &sample_pattern(),
+            SvgWriterOptions {
+                margin: 1.,
+                ..SvgWriterOptions::default()
+            },
+        );
+        assert!(svg.contains(" width=\"12mm\""));
+        assert!(svg.contains(" height=\"12mm\""));
+        assert!(svg.contains(" viewBox=\"-6 9 12 12\""));
+    }
+
+    #[test]
+    fn test_layers_and_metadata() {
+        let svg = write(&sample_pattern(), SvgWriterOptions::default());
+        assert!(svg.contains("inkscape:groupmode='layer'"));
+        assert!(svg.contains("inkscape:label='Red (1234)'"));
+        assert!(svg.contains("<title>Fish &amp; Chips</title>"));
+        assert!(svg.contains("<dc:title>Fish &amp; Chips</dc:title>"));
+        assert!(svg.contains("<dc:creator><cc:Agent><dc:title>Someone</dc:title></cc:Agent></dc:creator>"));
+        assert!(svg.contains("<dc:description>hoop: &lt;100mm&gt;</dc:description>"));
+    }
+
+    #[test]
+    fn test_optional_elements() {
+        let svg = write(&sample_pattern(), SvgWriterOptions::default());
+        assert!(svg.contains("<circle"));
+        assert!(!svg.contains("stroke-dasharray"));
+
+        let svg = write(
+            &sample_pattern(),
+            SvgWriterOptions {
+                show_needle_points: false,
+                show_jumps: true,
+                ..SvgWriterOptions::default()
+            },
+        );
+        assert!(!svg.contains("<circle"));
+        assert!(svg.contains("class='emb_ignore emb_trim' stroke-dasharray='0.4 0.4' d='M5 15 0 10'"));
+    }
+}
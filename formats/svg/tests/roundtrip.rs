@@ -0,0 +1,76 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_svg::{SvgPatternReader, SvgPatternWriter, SvgWriterOptions};
+
+fn sample_pattern() -> Pattern {
+    Pattern {
+        name: "Round & Trip".to_string(),
+        attributes: vec![
+            PatternAttribute::Title("Round & Trip".to_string()),
+            PatternAttribute::Copyright("<nobody>".to_string()),
+            PatternAttribute::Arbitary("hoop".to_string(), "100x100".to_string()),
+        ],
+        color_groups: vec![
+            ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(255, 0, 17), &"#FF0011", &"")),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![Stitch::new(0., 0.), Stitch::new(1.5, 2.), Stitch::new(3., -1.25)])
+                        .with_trim(true),
+                    StitchGroup::new(vec![Stitch::new(10., 10.), Stitch::new(11., 12.)]).with_trim(true),
+                ],
+            },
+            ColorGroup {
+                thread: Some(Thread::new_str(Color::rgb(0, 128, 255), &"#0080FF", &"")),
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(-5., 4.), Stitch::new(-4., 5.)]).with_trim(true)],
+            },
+        ],
+    }
+}
+
+fn assert_pattern_close(actual: &Pattern, expected: &Pattern) {
+    assert_eq!(actual.name, expected.name);
+    assert_eq!(actual.attributes, expected.attributes);
+    assert_eq!(actual.color_groups.len(), expected.color_groups.len());
+    for (a_cg, e_cg) in actual.color_groups.iter().zip(expected.color_groups.iter()) {
+        assert_eq!(a_cg.thread, e_cg.thread);
+        assert_eq!(a_cg.stitch_groups.len(), e_cg.stitch_groups.len());
+        for (a_sg, e_sg) in a_cg.stitch_groups.iter().zip(e_cg.stitch_groups.iter()) {
+            assert_eq!(a_sg.trim, e_sg.trim);
+            assert_eq!(a_sg.stitches.len(), e_sg.stitches.len());
+            for (a, e) in a_sg.stitches.iter().zip(e_sg.stitches.iter()) {
+                assert!(
+                    (a.x - e.x).abs() < 1e-6 && (a.y - e.y).abs() < 1e-6,
+                    "{:?} != {:?}",
+                    a,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_roundtrip() {
+    let pattern = sample_pattern();
+    let options = [
+        SvgWriterOptions::default(),
+        SvgWriterOptions {
+            show_needle_points: false,
+            show_jumps: true,
+            margin: 0.,
+            ..SvgWriterOptions::default()
+        },
+    ];
+    for options in options.iter() {
+        let mut data = Vec::new();
+        SvgPatternWriter::new(options.clone())
+            .write_pattern(&pattern, &mut data)
+            .unwrap();
+
+        let reader = SvgPatternReader::default().with_stitch_length(100.);
+        assert!(reader.is_loadable(&mut &data[..]).unwrap());
+        let read = reader.read_pattern(&mut &data[..]).unwrap();
+        assert_pattern_close(&read, &pattern);
+    }
+}
@@ -0,0 +1,94 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::read_exact;
+use std::io::{Read, Result, Write};
+
+/// The needle tables follow the 512 byte DST header; the stitches start straight after.
+pub const NEEDLE_TABLE_LEN: usize = NEEDLE_ORDER_LEN + NEEDLE_COLORS_LEN;
+// One needle per color; which limits the number of colors.
+const NEEDLE_ORDER_LEN: usize = 0x100;
+// Each needle's color is stored as `red, green, blue, 0x00`; counting from needle 1.
+const NEEDLE_COLORS_LEN: usize = 0x300;
+pub const MAX_COLORS: usize = NEEDLE_ORDER_LEN;
+pub const MAX_NEEDLE: u8 = 15;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeedleTable {
+    /// The needle used for each color; in the order they're sewn.
+    pub order: Vec<u8>,
+    /// The color of each needle; starting at needle 1.
+    pub colors: Vec<Color>,
+}
+
+impl NeedleTable {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let order_data = read_exact!(file, vec![_; NEEDLE_ORDER_LEN])?;
+        let order: Vec<u8> = order_data.into_iter().take_while(|&needle| needle != 0).collect();
+        if let Some(needle) = order.iter().find(|&&needle| needle > MAX_NEEDLE) {
+            return Err(ReadError::invalid_format(format!(
+                "Unknown needle {}; the format supports at most {}",
+                needle, MAX_NEEDLE
+            )));
+        }
+        let color_data = read_exact!(file, vec![_; NEEDLE_COLORS_LEN])?;
+        let colors = color_data
+            .chunks(4)
+            .take(usize::from(MAX_NEEDLE))
+            .map(|color| Color::rgb(color[0], color[1], color[2]))
+            .collect();
+        Ok(NeedleTable { order, colors })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        let mut order_data = self.order.clone();
+        order_data.resize(NEEDLE_ORDER_LEN, 0);
+        file.write_all(&order_data)?;
+
+        let mut color_data = Vec::with_capacity(NEEDLE_COLORS_LEN);
+        for color in &self.colors {
+            color_data.extend_from_slice(&[color.red, color.green, color.blue, 0x00]);
+        }
+        color_data.resize(NEEDLE_COLORS_LEN, 0);
+        file.write_all(&color_data)?;
+        Ok(())
+    }
+
+    /// The color of a needle; counting from 1.
+    pub fn needle_color(&self, needle: u8) -> Option<Color> {
+        self.colors.get(usize::from(needle).checked_sub(1)?).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let mut colors = vec![Color::rgb(0, 0, 0); usize::from(MAX_NEEDLE)];
+        colors[0] = Color::rgb(255, 0, 0);
+        colors[2] = Color::rgb(0, 0, 255);
+        let table = NeedleTable {
+            order: vec![1, 3, 1],
+            colors,
+        };
+        let mut data = vec![];
+        table.write(&mut data).unwrap();
+        assert_eq!(data.len(), NEEDLE_TABLE_LEN);
+        assert_eq!(data[..4], [1, 3, 1, 0]);
+        assert_eq!(data[0x108..0x10C], [0, 0, 255, 0]);
+
+        let read = NeedleTable::build(&mut &data[..]).unwrap();
+        assert_eq!(read, table);
+        assert_eq!(read.needle_color(3), Some(Color::rgb(0, 0, 255)));
+        assert_eq!(read.needle_color(0), None);
+        assert_eq!(read.needle_color(16), None);
+    }
+
+    #[test]
+    fn test_unknown_needle() {
+        let mut data = vec![0; NEEDLE_TABLE_LEN];
+        data[0] = 16;
+        assert!(NeedleTable::build(&mut &data[..]).is_err());
+    }
+}
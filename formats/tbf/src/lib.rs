@@ -0,0 +1,30 @@
+mod header;
+mod read;
+mod stitch_info;
+mod write;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use read::TbfPatternReader;
+pub use write::TbfPatternWriter;
+
+const NAME: &str = "tbf";
+const EXTENSIONS: [&str; 1] = ["tbf"];
+
+#[derive(Default)]
+pub struct TbfPatternFormat {}
+
+impl PatternFormat for TbfPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(TbfPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(TbfPatternWriter::default()))
+    }
+}
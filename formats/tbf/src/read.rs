@@ -0,0 +1,179 @@
+use std::io::Read;
+
+use embroidery_fmt_dst::read_dst_header;
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::ReadByteIterator;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+
+use crate::header::{NeedleTable, NEEDLE_TABLE_LEN};
+use crate::stitch_info::from_bytes;
+
+#[derive(Default)]
+pub struct TbfPatternReader {}
+
+impl PatternReader for TbfPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        // The headers don't have anything to check; so the best that can be done is checking every
+        // command is known.
+        let mut iter = ReadByteIterator::new(item);
+        let result = read_header(&mut iter).and_then(|_| loop {
+            match read_stitch(&mut iter)? {
+                Some(StitchInformation::End) => break Ok(()),
+                None => {
+                    break Err(ReadError::invalid_format(
+                        "The stitches don't finish with an end command",
+                    ))
+                },
+                Some(_) => {},
+            }
+        });
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+        match result {
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+            Ok(()) => Ok(true),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut iter = ReadByteIterator::new(item);
+        let (attributes, needles) = read_header(&mut iter)?;
+        let color_groups = read_stitches(&mut iter, &needles)?;
+        if let Some(err) = iter.error {
+            return Err(err.into());
+        }
+        if color_groups.is_empty() {
+            return Err(ReadError::invalid_format("The file doesn't contain any stitches"));
+        }
+
+        let name = attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                PatternAttribute::Title(title) => Some(title.clone()),
+                _ => None,
+            })
+            .next()
+            .unwrap_or_else(|| "Untitled".to_string());
+        Ok(Pattern {
+            name,
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn read_header(item: &mut dyn Iterator<Item = u8>) -> ReadResult<(Vec<PatternAttribute>, NeedleTable)> {
+    let attributes = read_dst_header(item)?.attributes;
+    let table: Vec<u8> = item.take(NEEDLE_TABLE_LEN).collect();
+    let needles = NeedleTable::build(&mut &table[..])?;
+    Ok((attributes, needles))
+}
+
+pub fn needle_thread(needles: &NeedleTable, needle: u8) -> ReadResult<Thread> {
+    let color = match needles.needle_color(needle) {
+        Some(color) => color,
+        None => return Err(ReadError::invalid_format(format!("Unknown needle {}", needle))),
+    };
+    let mut thread = Thread::new(color, format!("Needle {}", needle), String::new());
+    thread.attributes.insert("needle".to_string(), needle.to_string());
+    Ok(thread)
+}
+
+fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let bytes: Vec<u8> = in_bytes.take(3).collect();
+    match bytes[..] {
+        [] => Ok(None),
+        [b0, b1, control] => Ok(Some(from_bytes([b0, b1, control])?)),
+        _ => Err(ReadError::invalid_format("Stitch data ends part way through a stitch")),
+    }
+}
+
+fn read_stitches(item: &mut dyn Iterator<Item = u8>, needles: &NeedleTable) -> ReadResult<Vec<ColorGroup>> {
+    let mut grouper = StitchGrouper::new();
+    let mut thread = None;
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(stitch) = read_stitch(item)? {
+        match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => {
+                cx += i32::from(dx);
+                cy += i32::from(dy);
+                if stitch_type == StitchType::Regular {
+                    grouper.stitch(from_tenths(cx, cy));
+                } else {
+                    grouper.jump(from_tenths(cx, cy));
+                }
+            },
+            StitchInformation::Trim => grouper.trim(),
+            StitchInformation::NeedleChange(needle) => {
+                grouper.end_color(thread);
+                thread = Some(needle_thread(needles, needle)?);
+            },
+            StitchInformation::End => break,
+        }
+    }
+    Ok(grouper.finish(thread))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header::MAX_NEEDLE;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    fn needles() -> NeedleTable {
+        let mut colors = vec![Color::rgb(0, 0, 0); usize::from(MAX_NEEDLE)];
+        colors[1] = Color::rgb(0, 255, 0);
+        NeedleTable { order: vec![2], colors }
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let needles = needles();
+        let data = [
+            0x02, 0x00, 0x90, // Needle 2
+            0x0A, 0x00, 0x80, // Stitch to (1.0, 0.0)
+            0x00, 0xF6, 0x80, // Stitch to (1.0, 1.0)
+            0x00, 0x00, 0x86, // Trim
+            0x14, 0x00, 0x81, // Jump to (3.0, 1.0)
+            0x00, 0x0A, 0x80, // Stitch to (3.0, 0.0)
+            0x00, 0x00, 0x8F, // End
+            0x0A, 0x00, 0x80, // Ignored
+        ];
+        let thread = needle_thread(&needles, 2).unwrap();
+        assert_eq!(thread.color, Color::rgb(0, 255, 0));
+        assert_eq!(
+            read_stitches(to_u8_iter!(data), &needles).unwrap(),
+            vec![ColorGroup {
+                thread: Some(thread),
+                stitch_groups: vec![
+                    StitchGroup::new(vec![
+                        Stitch::new(0.0, 0.0),
+                        Stitch::new(1.0, 0.0),
+                        Stitch::new(1.0, 1.0)
+                    ])
+                    .with_trim(true)
+                    .with_cut(true),
+                    StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unknown_needle() {
+        let data = [0x10, 0x00, 0x90, 0x01, 0x00, 0x80];
+        assert!(read_stitches(to_u8_iter!(data), &needles()).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// Every stitch is 3 bytes: `dx, dy, control`. The move is a pair of signed bytes in 0.1mm; with y
+// increasing downwards.
+const STITCH: u8 = 0x80;
+const JUMP: u8 = 0x81;
+const TRIM: u8 = 0x86;
+const END: u8 = 0x8F;
+// The needle is stored in the first byte.
+const NEEDLE_CHANGE: u8 = 0x90;
+
+pub const MAX_MOVE: i32 = 127;
+
+pub fn to_bytes(stitch: StitchInformation) -> Option<[u8; 3]> {
+    match stitch {
+        StitchInformation::Move(x, y, stitch_type) => {
+            let control = match stitch_type {
+                StitchType::Regular => STITCH,
+                StitchType::Jump => JUMP,
+                StitchType::Stop | StitchType::JumpStop => return None,
+            };
+            if x == i8::MIN || y == i8::MIN {
+                return None;
+            }
+            Some([x as u8, (-y) as u8, control])
+        },
+        StitchInformation::Trim => Some([0x00, 0x00, TRIM]),
+        StitchInformation::NeedleChange(needle) => Some([needle, 0x00, NEEDLE_CHANGE]),
+        StitchInformation::End => Some([0x00, 0x00, END]),
+    }
+}
+
+pub fn from_bytes([b0, b1, control]: [u8; 3]) -> ReadResult<StitchInformation> {
+    let move_by = |stitch_type| {
+        if b0 as i8 == i8::MIN || b1 as i8 == i8::MIN {
+            return Err(ReadError::invalid_format(format!(
+                "Move of ({}, {}) is too big",
+                b0 as i8, b1 as i8
+            )));
+        }
+        Ok(StitchInformation::Move(b0 as i8, -(b1 as i8), stitch_type))
+    };
+    match control {
+        STITCH => move_by(StitchType::Regular),
+        JUMP => move_by(StitchType::Jump),
+        TRIM => Ok(StitchInformation::Trim),
+        NEEDLE_CHANGE => Ok(StitchInformation::NeedleChange(b0)),
+        END => Ok(StitchInformation::End),
+        _ => Err(ReadError::invalid_format(format!(
+            "Unknown stitch command 0x{:02X}",
+            control
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_bytes() {
+        assert_eq!(
+            to_bytes(StitchInformation::Move(0, 0, StitchType::Regular)),
+            Some([0x00, 0x00, 0x80])
+        );
+        assert_eq!(
+            to_bytes(StitchInformation::Move(8, -8, StitchType::Jump)),
+            Some([0x08, 0x08, 0x81])
+        );
+        assert_eq!(to_bytes(StitchInformation::Move(-128, 0, StitchType::Regular)), None);
+        assert_eq!(to_bytes(StitchInformation::Move(0, 0, StitchType::Stop)), None);
+        assert_eq!(to_bytes(StitchInformation::Trim), Some([0x00, 0x00, 0x86]));
+        assert_eq!(to_bytes(StitchInformation::NeedleChange(5)), Some([0x05, 0x00, 0x90]));
+        assert_eq!(to_bytes(StitchInformation::End), Some([0x00, 0x00, 0x8F]));
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        assert_eq!(
+            from_bytes([0xF8, 0x08, 0x80]).unwrap(),
+            StitchInformation::Move(-8, -8, StitchType::Regular)
+        );
+        assert_eq!(
+            from_bytes([0x03, 0x00, 0x90]).unwrap(),
+            StitchInformation::NeedleChange(3)
+        );
+        assert!(from_bytes([0x00, 0x00, 0x03]).is_err());
+        assert!(from_bytes([0x00, 0x80, 0x80]).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for x in -MAX_MOVE..=MAX_MOVE {
+            for y in -MAX_MOVE..=MAX_MOVE {
+                let stitch = StitchInformation::Move(x as i8, y as i8, StitchType::Regular);
+                assert_eq!(from_bytes(to_bytes(stitch).unwrap()).unwrap(), stitch);
+            }
+        }
+    }
+}
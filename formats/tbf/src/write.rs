@@ -0,0 +1,172 @@
+use std::io::Write;
+
+use embroidery_fmt_dst::stitch_info::{StitchInformation, StitchType};
+use embroidery_fmt_dst::write_dst_header;
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, thread_colors, to_tenths};
+
+use crate::header::{NeedleTable, MAX_COLORS, MAX_NEEDLE};
+use crate::stitch_info::{to_bytes, MAX_MOVE};
+
+#[derive(Default)]
+pub struct TbfPatternWriter {}
+
+impl PatternWriter for TbfPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        if pattern.color_groups.len() > MAX_COLORS {
+            return Err(WriteError::too_many_colors(pattern.color_groups.len(), MAX_COLORS));
+        }
+        let needles = assign_needles(pattern)?;
+        let (stitches, end) = into_tbf_stitches(pattern, &needles.order)?;
+        write_dst_header(pattern, stitches.len(), end, writer)?;
+        needles.write(writer)?;
+        for &st in &stitches {
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            writer.write_all(&to_bytes(st).unwrap())?;
+        }
+        Ok(())
+    }
+}
+
+/// Pick a needle for each color group. Needles are kept from the file the pattern was read from
+/// where possible; otherwise colors share a needle if they're identical.
+fn assign_needles(pattern: &Pattern) -> Result<NeedleTable, WriteError> {
+    let mut colors: Vec<Option<Color>> = vec![None; usize::from(MAX_NEEDLE)];
+    let mut order = Vec::with_capacity(pattern.color_groups.len());
+
+    for (cg, color) in pattern.color_groups.iter().zip(thread_colors(pattern)) {
+        let requested = cg
+            .thread
+            .as_ref()
+            .and_then(|thread| thread.attributes.get("needle"))
+            .and_then(|needle| needle.parse::<u8>().ok())
+            .filter(|needle| (1..=MAX_NEEDLE).contains(needle))
+            .map(|needle| usize::from(needle - 1))
+            .filter(|&idx| colors[idx].is_none_or(|c| c == color));
+        let idx = match requested
+            .or_else(|| colors.iter().position(|&c| c == Some(color)))
+            .or_else(|| colors.iter().position(Option::is_none))
+        {
+            Some(idx) => idx,
+            None => {
+                let mut distinct = Vec::new();
+                for color in thread_colors(pattern) {
+                    if !distinct.contains(&color) {
+                        distinct.push(color);
+                    }
+                }
+                return Err(WriteError::too_many_colors(distinct.len(), usize::from(MAX_NEEDLE)));
+            },
+        };
+        colors[idx] = Some(color);
+        order.push(idx as u8 + 1);
+    }
+    Ok(NeedleTable {
+        order,
+        colors: colors.into_iter().map(Option::unwrap_or_default).collect(),
+    })
+}
+
+/// The stitches to write; along with where the needle ends up in 0.1mm.
+fn into_tbf_stitches(pattern: &Pattern, needles: &[u8]) -> Result<(Vec<StitchInformation>, (i32, i32)), WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+    let mut idx: usize = 0;
+
+    for (cg, &needle) in pattern.color_groups.iter().zip(needles) {
+        re.push(StitchInformation::NeedleChange(needle));
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                for (dx, dy) in split_jump(x - ox, y - oy, MAX_MOVE) {
+                    re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Jump));
+                }
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                if dx.abs() > MAX_MOVE || dy.abs() > MAX_MOVE {
+                    return Err(WriteError::unsupported_stitch_msg(
+                        *s,
+                        Some(idx),
+                        "Stitch jump is too big for the format",
+                    ));
+                }
+                ox = x;
+                oy = y;
+                re.push(StitchInformation::Move(dx as i8, dy as i8, StitchType::Regular));
+                idx += 1;
+            }
+            if sg.cut {
+                re.push(StitchInformation::Trim);
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok((re, (ox, oy)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embroidery_lib::utils::generate_color;
+
+    fn color_group(thread: Option<Thread>) -> ColorGroup {
+        ColorGroup {
+            thread,
+            stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0)]).with_trim(true)],
+        }
+    }
+
+    #[test]
+    fn test_assign_needles() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"Red", &"");
+        let mut blue = Thread::new_str(Color::rgb(0, 0, 255), &"Blue", &"");
+        blue.attributes.insert("needle".to_string(), "5".to_string());
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![
+                color_group(Some(red.clone())),
+                color_group(Some(blue)),
+                color_group(Some(red)),
+                color_group(None),
+            ],
+        };
+        let needles = assign_needles(&pattern).unwrap();
+        assert_eq!(needles.order, vec![1, 5, 1, 2]);
+        assert_eq!(needles.colors[4], Color::rgb(0, 0, 255));
+        assert_eq!(needles.colors[1], generate_color(0, 1));
+
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: (0..=MAX_NEEDLE)
+                .map(|i| color_group(Some(Thread::new_str(Color::rgb(i, 0, 0), &"", &""))))
+                .collect(),
+        };
+        match assign_needles(&pattern) {
+            Err(WriteError::TooManyColors { count, max, ctx: _ }) => assert_eq!((count, max), (16, 15)),
+            res => panic!("Expected too many colors; got {:?}", res.map(|needles| needles.order)),
+        }
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(TbfPatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
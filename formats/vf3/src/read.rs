@@ -2,29 +2,22 @@ use std::io::Read;
 
 use embroidery_lib::format::CollectionReader;
 use embroidery_lib::prelude::*;
-use embroidery_lib::utils::ReadByteIterator;
 
+#[derive(Default)]
 pub struct Vf3CollectionReader {}
 
-impl Default for Vf3CollectionReader {
-    fn default() -> Self {
-        Vf3CollectionReader {}
-    }
-}
-
 impl CollectionReader for Vf3CollectionReader {
-    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
-        // Load the header
-        // Check the last byte of the file? maybe
-        let _iter = ReadByteIterator::new(item);
+    fn is_loadable(&self, _item: &mut dyn Read) -> Result<bool, ReadError> {
+        // TODO: Check the magic bytes once we have some sample files to find out what they are.
         Ok(false)
     }
 
-    fn read_pattern(&self, file: &mut dyn Read) -> Result<PatternCollection, ReadError> {
-        // Read the header
-        let _iter = ReadByteIterator::new(file);
-        // TODO: This
-        return Err(ReadError::invalid_format("oops"));
+    fn read_pattern(&self, _item: &mut dyn Read) -> Result<PatternCollection, ReadError> {
+        // TODO: Without any sample files or documentation of the layout of the embedded designs
+        // there is nothing to decode the collection against.
+        Err(ReadError::invalid_format(
+            "Reading VF3 collections is not supported yet; the layout of the format is unknown",
+        ))
     }
 }
 
@@ -34,8 +27,13 @@ mod tests {
 
     #[test]
     fn test_is_parsable() {
-        // Less than 3 bytes means the iterator has been exhausted.
         let b = b"ab";
-        assert_eq!(Vf3CollectionReader::default().is_loadable(&mut &b[..]).unwrap(), false);
+        assert!(!Vf3CollectionReader::default().is_loadable(&mut &b[..]).unwrap());
+    }
+
+    #[test]
+    fn test_read_pattern_is_unsupported() {
+        let b = b"ab";
+        assert!(Vf3CollectionReader::default().read_pattern(&mut &b[..]).is_err());
     }
 }
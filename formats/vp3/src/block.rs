@@ -0,0 +1,97 @@
+use byteorder::{BigEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::{read_exact, read_exact_magic, read_int};
+use std::io::{Read, Result, Write};
+
+pub const FILE_BLOCK: [u8; 3] = [0x00, 0x02, 0x00];
+pub const DESIGN_BLOCK: [u8; 3] = [0x00, 0x03, 0x00];
+pub const COLOR_BLOCK: [u8; 3] = [0x00, 0x05, 0x00];
+pub const STITCH_BLOCK: [u8; 3] = [0x00, 0x01, 0x00];
+
+/// Read a block of the form `[tag; 3] [length: u32] [content; length]`, returning the content.
+pub fn read_block(file: &mut dyn Read, tag: [u8; 3]) -> ReadResult<Vec<u8>> {
+    read_exact_magic!(file, tag)?;
+    let length = read_int!(file, u32, BigEndian)?;
+    read_exact!(file, vec![_; length as usize])
+}
+
+pub fn write_block(file: &mut dyn Write, tag: [u8; 3], content: &[u8]) -> Result<()> {
+    file.write_all(&tag)?;
+    file.write_u32::<BigEndian>(content.len() as u32)?;
+    file.write_all(content)
+}
+
+/// Read a UTF-16 string prefixed by its length in bytes as a `u16`.
+pub fn read_string_16(file: &mut dyn Read) -> ReadResult<String> {
+    let length = read_int!(file, u16, BigEndian)?;
+    if length % 2 != 0 {
+        return Err(ReadError::invalid_format(format!(
+            "UTF-16 string has an odd length({})",
+            length
+        )));
+    }
+    let data = read_exact!(file, vec![_; length as usize])?;
+    let units: Vec<u16> = data
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    Ok(String::from_utf16_lossy(&units))
+}
+
+pub fn write_string_16(file: &mut dyn Write, value: &str) -> Result<()> {
+    let units: Vec<u16> = value.encode_utf16().collect();
+    file.write_u16::<BigEndian>((units.len() * 2) as u16)?;
+    for unit in units {
+        file.write_u16::<BigEndian>(unit)?;
+    }
+    Ok(())
+}
+
+/// Read a UTF-8 string prefixed by its length in bytes as a `u16`.
+pub fn read_string_8(file: &mut dyn Read) -> ReadResult<String> {
+    let length = read_int!(file, u16, BigEndian)?;
+    let data = read_exact!(file, vec![_; length as usize])?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+pub fn write_string_8(file: &mut dyn Write, value: &str) -> Result<()> {
+    file.write_u16::<BigEndian>(value.len() as u16)?;
+    file.write_all(value.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_block() {
+        let data = b"\x00\x05\x00\0\0\0\x03abcdef";
+        let file = &mut &data[..];
+        assert_eq!(read_block(file, COLOR_BLOCK).unwrap(), b"abc");
+        assert_eq!(file, b"def");
+
+        assert!(read_block(&mut &data[..], DESIGN_BLOCK).is_err());
+    }
+
+    #[test]
+    fn test_write_block() {
+        let mut data = vec![];
+        write_block(&mut data, STITCH_BLOCK, b"abc").unwrap();
+        assert_eq!(data, b"\x00\x01\x00\0\0\0\x03abc");
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut data = vec![];
+        write_string_16(&mut data, "Hé").unwrap();
+        assert_eq!(data, b"\0\x04\0H\0\xE9");
+        assert_eq!(read_string_16(&mut &data[..]).unwrap(), "Hé");
+        assert!(read_string_16(&mut &b"\0\x01\0"[..]).is_err());
+
+        let mut data = vec![];
+        write_string_8(&mut data, "Hé").unwrap();
+        assert_eq!(data, b"\0\x03H\xC3\xA9");
+        assert_eq!(read_string_8(&mut &data[..]).unwrap(), "Hé");
+    }
+}
@@ -0,0 +1,155 @@
+use byteorder::{BigEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::{read_exact, read_exact_magic, read_int};
+use std::io::{Read, Result, Write};
+
+use crate::block::{read_string_16, write_string_16};
+
+pub const MAGIC_BYTES: [u8; 6] = [b'%', b'v', b's', b'm', b'%', 0x00];
+// Sits in the middle of the design header; the byte after it might be a version.
+const DESIGN_MAGIC: [u8; 6] = [b'x', b'x', b'P', b'P', 0x01, 0x00];
+// Unknown; copied from files written by Husqvarna's software.
+const FILE_UNKNOWN: u8 = 0x0C;
+const DESIGN_UNKNOWN: [u8; 18] = [
+    0x64, 0x64, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00,
+];
+
+/// The fields at the start of the file block; which is followed by the design blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternHeader {
+    pub notes: String,
+    // Min x, min y, max x, max y; in µm with y increasing upwards.
+    pub bounds: (i32, i32, i32, i32),
+    pub number_of_stitches: u32,
+    pub number_of_colors: u16,
+    pub number_of_designs: u16,
+}
+
+/// The fields at the start of a design block; which are followed by the color blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesignHeader {
+    /// Every color block's starting position is relative to this; in µm with y increasing upwards.
+    pub center: (i32, i32),
+    // Min x, min y, max x, max y; in µm with y increasing upwards.
+    pub bounds: (i32, i32, i32, i32),
+    pub notes: String,
+    pub number_of_colors: u16,
+}
+
+/// Bounds are stored as right, top, left, bottom.
+fn read_bounds(file: &mut dyn Read) -> ReadResult<(i32, i32, i32, i32)> {
+    let max_x = read_int!(file, i32, BigEndian)?;
+    let max_y = read_int!(file, i32, BigEndian)?;
+    let min_x = read_int!(file, i32, BigEndian)?;
+    let min_y = read_int!(file, i32, BigEndian)?;
+    Ok((min_x, min_y, max_x, max_y))
+}
+
+fn write_bounds(file: &mut dyn Write, (min_x, min_y, max_x, max_y): (i32, i32, i32, i32)) -> Result<()> {
+    file.write_i32::<BigEndian>(max_x)?;
+    file.write_i32::<BigEndian>(max_y)?;
+    file.write_i32::<BigEndian>(min_x)?;
+    file.write_i32::<BigEndian>(min_y)
+}
+
+impl PatternHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let notes = read_string_16(file)?;
+        let bounds = read_bounds(file)?;
+        let number_of_stitches = read_int!(file, u32, BigEndian)?;
+        let number_of_colors = read_int!(file, u16, BigEndian)?;
+        let _ = read_int!(file, u8)?;
+        let number_of_designs = read_int!(file, u16, BigEndian)?;
+        Ok(Self {
+            notes,
+            bounds,
+            number_of_stitches,
+            number_of_colors,
+            number_of_designs,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        write_string_16(file, &self.notes)?;
+        write_bounds(file, self.bounds)?;
+        file.write_u32::<BigEndian>(self.number_of_stitches)?;
+        file.write_u16::<BigEndian>(self.number_of_colors)?;
+        file.write_u8(FILE_UNKNOWN)?;
+        file.write_u16::<BigEndian>(self.number_of_designs)
+    }
+}
+
+impl DesignHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        let center = (read_int!(file, i32, BigEndian)?, read_int!(file, i32, BigEndian)?);
+        // Unknown; all zeros in the files seen so far.
+        read_exact!(file, [_; 9])?;
+        let bounds = read_bounds(file)?;
+        // The width and height; which can be worked out from the bounds.
+        read_exact!(file, [_; 8])?;
+        let notes = read_string_16(file)?;
+        read_exact!(file, [_; 18])?;
+        read_exact_magic!(file, DESIGN_MAGIC)?;
+        let _ = read_string_16(file)?;
+        let number_of_colors = read_int!(file, u16, BigEndian)?;
+        Ok(Self {
+            center,
+            bounds,
+            notes,
+            number_of_colors,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        let (min_x, min_y, max_x, max_y) = self.bounds;
+        file.write_i32::<BigEndian>(self.center.0)?;
+        file.write_i32::<BigEndian>(self.center.1)?;
+        file.write_all(&[0; 9])?;
+        write_bounds(file, self.bounds)?;
+        file.write_i32::<BigEndian>(max_x - min_x)?;
+        file.write_i32::<BigEndian>(max_y - min_y)?;
+        write_string_16(file, &self.notes)?;
+        file.write_all(&DESIGN_UNKNOWN)?;
+        file.write_all(&DESIGN_MAGIC)?;
+        write_string_16(file, "")?;
+        file.write_u16::<BigEndian>(self.number_of_colors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pattern_header_roundtrip() {
+        let header = PatternHeader {
+            notes: "Hello".to_string(),
+            bounds: (-1000, -2000, 3000, 4000),
+            number_of_stitches: 1234,
+            number_of_colors: 3,
+            number_of_designs: 1,
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(data[12..16], [0x00, 0x00, 0x0B, 0xB8]);
+        assert_eq!(PatternHeader::build(&mut &data[..]).unwrap(), header);
+    }
+
+    #[test]
+    fn test_design_header_roundtrip() {
+        let header = DesignHeader {
+            center: (1000, -1000),
+            bounds: (-1000, -2000, 3000, 4000),
+            notes: "".to_string(),
+            number_of_colors: 3,
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(DesignHeader::build(&mut &data[..]).unwrap(), header);
+
+        // Break the magic bytes in the middle.
+        let len = data.len();
+        data[len - 9] = b'?';
+        assert!(DesignHeader::build(&mut &data[..]).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use byteorder::BigEndian;
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::StitchGrouper;
+use embroidery_lib::{read_exact, read_exact_magic, read_int};
+use std::io::Read;
+
+use crate::block::{read_block, read_string_16, COLOR_BLOCK, DESIGN_BLOCK, FILE_BLOCK, STITCH_BLOCK};
+use crate::header::{DesignHeader, PatternHeader, MAGIC_BYTES};
+use crate::thread::read_thread;
+
+// Every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm increments; except when the first
+// byte is `ESCAPE`, in which case the second byte is a command.
+pub const ESCAPE: u8 = 0x80;
+// Followed by `(dx, dy)` as a pair of big endian `i16`s.
+pub const LONG_MOVE: u8 = 0x01;
+pub const END_LONG_MOVE: u8 = 0x02;
+pub const TRIM: u8 = 0x03;
+// Unknown; the stitch data always starts with these.
+pub const STITCH_PREFIX: [u8; 3] = [0x0A, 0xF6, 0x00];
+
+// Positions are stored in µm, but stitches in 0.1mm.
+pub const UM_PER_STITCH_UNIT: i32 = 100;
+
+#[derive(Default)]
+pub struct Vp3PatternReader {}
+
+impl PatternReader for Vp3PatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        let mut buf = [0_u8; MAGIC_BYTES.len()];
+        item.read_exact(&mut buf)?;
+        Ok(buf == MAGIC_BYTES)
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        read_exact_magic!(item, MAGIC_BYTES)?;
+        // The name of the software that wrote the file.
+        let _ = read_string_16(item)?;
+
+        let content = read_block(item, FILE_BLOCK)?;
+        let content = &mut &content[..];
+        let header = PatternHeader::build(content)?;
+        let mut attributes = vec![];
+        if !header.notes.is_empty() {
+            attributes.push(PatternAttribute::Arbitary("notes".to_string(), header.notes));
+        }
+
+        let mut color_groups = Vec::with_capacity(usize::from(header.number_of_colors));
+        for _ in 0..header.number_of_designs {
+            let design = read_block(content, DESIGN_BLOCK)?;
+            color_groups.append(&mut read_design(&mut &design[..], &mut attributes)?);
+        }
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn read_design(item: &mut dyn Read, attributes: &mut Vec<PatternAttribute>) -> ReadResult<Vec<ColorGroup>> {
+    let header = DesignHeader::build(item)?;
+    if !header.notes.is_empty() {
+        attributes.push(PatternAttribute::Arbitary("notes".to_string(), header.notes));
+    }
+
+    let mut grouper = StitchGrouper::new();
+    for _ in 0..header.number_of_colors {
+        let block = read_block(item, COLOR_BLOCK)?;
+        let block = &mut &block[..];
+        // Each color starts at a position relative to the design's center, rather than continuing
+        // from the last stitch.
+        let start_x = read_int!(block, i32, BigEndian)?;
+        let start_y = read_int!(block, i32, BigEndian)?;
+        let thread = read_thread(block)?;
+        // How far the needle moves between the start and end of the block.
+        read_exact!(block, [_; 8])?;
+        let stitch_data = read_block(block, STITCH_BLOCK)?;
+        if stitch_data.len() < STITCH_PREFIX.len() {
+            return Err(ReadError::invalid_format("Stitch block is too short"));
+        }
+        let start = (header.center.0 + start_x, header.center.1 + start_y);
+        read_stitches(&mut grouper, start, &stitch_data[STITCH_PREFIX.len()..])?;
+        grouper.end_color(Some(thread));
+    }
+    Ok(grouper.finish(None))
+}
+
+fn to_stitch(x: i32, y: i32) -> Stitch {
+    Stitch::new(f64::from(x) / 1000., f64::from(y) / 1000.)
+}
+
+fn read_i16(iter: &mut dyn Iterator<Item = u8>) -> ReadResult<i16> {
+    match (iter.next(), iter.next()) {
+        (Some(high), Some(low)) => Ok(i16::from_be_bytes([high, low])),
+        _ => Err(ReadError::invalid_format(
+            "Stitch data ends part way through a long move",
+        )),
+    }
+}
+
+/// Read the stitches of a color block; starting from `(start_x, start_y)` in µm.
+fn read_stitches(grouper: &mut StitchGrouper, (start_x, start_y): (i32, i32), data: &[u8]) -> ReadResult<()> {
+    let mut iter = data.iter().cloned();
+    let mut cx = start_x;
+    let mut cy = start_y;
+    grouper.jump(to_stitch(cx, cy));
+
+    loop {
+        let (first, second) = match (iter.next(), iter.next()) {
+            (Some(first), Some(second)) => (first, second),
+            (None, _) => break,
+            (Some(_), None) => return Err(ReadError::invalid_format("Stitch data ends part way through a stitch")),
+        };
+        if first != ESCAPE {
+            // The y axis points down the page.
+            cx += i32::from(first as i8) * UM_PER_STITCH_UNIT;
+            cy -= i32::from(second as i8) * UM_PER_STITCH_UNIT;
+            grouper.stitch(to_stitch(cx, cy));
+            continue;
+        }
+        match second {
+            LONG_MOVE => {
+                cx += i32::from(read_i16(&mut iter)?) * UM_PER_STITCH_UNIT;
+                cy -= i32::from(read_i16(&mut iter)?) * UM_PER_STITCH_UNIT;
+                grouper.jump(to_stitch(cx, cy));
+            },
+            END_LONG_MOVE => {},
+            TRIM => grouper.trim(),
+            cmd => {
+                return Err(ReadError::invalid_format(format!(
+                    "Unknown stitch command 0x{:02X}",
+                    cmd
+                )))
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_stitches() {
+        let data = [
+            0x0A, 0x00, // Stitch to (2.0, 1.0)
+            0x00, 0xF6, // Stitch to (2.0, 2.0)
+            0x80, 0x03, // Trim
+            0x80, 0x01, 0x01, 0x2C, 0x00, 0x00, // Jump to (32.0, 2.0)
+            0x0A, 0x0A, // Stitch to (33.0, 1.0)
+            0x80, 0x01, 0x00, 0x00, 0x00, 0x0A, // Jump to (33.0, 0.0)
+            0x80, 0x02, // End of long moves
+            0xF6, 0x00, // Stitch to (32.0, 0.0)
+        ];
+        let mut grouper = StitchGrouper::new();
+        read_stitches(&mut grouper, (1000, 1000), &data).unwrap();
+        assert_eq!(
+            grouper.finish(None)[0].stitch_groups,
+            vec![
+                StitchGroup::new(vec![
+                    Stitch::new(1.0, 1.0),
+                    Stitch::new(2.0, 1.0),
+                    Stitch::new(2.0, 2.0)
+                ])
+                .with_trim(true)
+                .with_cut(true),
+                StitchGroup::new(vec![Stitch::new(32.0, 2.0), Stitch::new(33.0, 1.0)]).with_trim(true),
+                StitchGroup::new(vec![Stitch::new(33.0, 0.0), Stitch::new(32.0, 0.0)]).with_trim(true),
+            ]
+        );
+        assert!(read_stitches(&mut StitchGrouper::new(), (0, 0), &[0x80, 0x42]).is_err());
+        assert!(read_stitches(&mut StitchGrouper::new(), (0, 0), &[0x80, 0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_is_loadable() {
+        let reader = Vp3PatternReader::default();
+        assert!(reader.is_loadable(&mut &b"%vsm%\0\0\0"[..]).unwrap());
+        assert!(!reader.is_loadable(&mut &b"%Vp4%\x01\0\0"[..]).unwrap());
+    }
+}
@@ -0,0 +1,107 @@
+use byteorder::{BigEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
+
+use crate::block::{read_string_8, write_string_8};
+
+// Used when a thread didn't come from a VP3 file; these are what Husqvarna's software writes for
+// 40 weight rayon.
+const DEFAULT_THREAD_TYPE: u8 = 5;
+const DEFAULT_WEIGHT: u8 = 40;
+
+/// Read a thread record; which starts with one or more colors, in case the thread is variegated.
+/// Only the first color is kept.
+pub fn read_thread(item: &mut dyn Read) -> ReadResult<Thread> {
+    let color_count = read_int!(item, u8)?;
+    let _transition = read_int!(item, u8)?;
+    if color_count == 0 {
+        return Err(ReadError::invalid_format("Thread doesn't have any colors"));
+    }
+    let mut color = Color::rgb(0, 0, 0);
+    for i in 0..color_count {
+        let [red, green, blue] = read_exact!(item, [_; 3])?;
+        // How much of the thread is this color.
+        let _parts = read_int!(item, u8)?;
+        let _length = read_int!(item, u16, BigEndian)?;
+        if i == 0 {
+            color = Color::rgb(red, green, blue);
+        }
+    }
+    let thread_type = read_int!(item, u8)?;
+    let weight = read_int!(item, u8)?;
+    let code = read_string_8(item)?;
+    let name = read_string_8(item)?;
+    let manufacturer = read_string_8(item)?;
+
+    let mut thread = Thread::new(color, name, code);
+    if !manufacturer.is_empty() {
+        thread.manufacturer = Some(manufacturer);
+    }
+    thread
+        .attributes
+        .insert("thread_type".to_string(), thread_type.to_string());
+    thread.attributes.insert("weight".to_string(), weight.to_string());
+    Ok(thread)
+}
+
+fn u8_attribute(thread: &Thread, key: &str, default: u8) -> u8 {
+    thread
+        .attributes
+        .get(key)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+pub fn write_thread(file: &mut dyn Write, thread: &Thread) -> Result<()> {
+    // One color, with no transition.
+    file.write_all(&[1, 0])?;
+    file.write_all(&[thread.color.red, thread.color.green, thread.color.blue])?;
+    file.write_u8(0)?;
+    file.write_u16::<BigEndian>(0)?;
+    file.write_u8(u8_attribute(thread, "thread_type", DEFAULT_THREAD_TYPE))?;
+    file.write_u8(u8_attribute(thread, "weight", DEFAULT_WEIGHT))?;
+    write_string_8(file, &thread.code)?;
+    write_string_8(file, &thread.name)?;
+    write_string_8(file, thread.manufacturer.as_ref().map_or("", String::as_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_thread() {
+        let data = b"\x02\x00\x00\x43\xb1\x01\x00\x00\xff\xff\xff\x01\x00\x00\x05\x28\
+                     \x00\x042527\x00\x0cSoldier Blue\x00\x16Robison-Anton Rayon 40";
+        let thread = read_thread(&mut &data[..]).unwrap();
+        assert_eq!(thread.color, Color::rgb(0x00, 0x43, 0xb1));
+        assert_eq!(thread.name, "Soldier Blue");
+        assert_eq!(thread.code, "2527");
+        assert_eq!(thread.manufacturer, Some("Robison-Anton Rayon 40".to_string()));
+        assert_eq!(thread.attributes.get("thread_type"), Some(&"5".to_string()));
+        assert_eq!(thread.attributes.get("weight"), Some(&"40".to_string()));
+
+        assert!(read_thread(&mut &b"\x00\x00\x05\x28\0\0\0\0\0\0"[..]).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut thread = Thread::new_str(Color::rgb(1, 2, 3), &"Name", &"1234");
+        thread.manufacturer = Some("Sulky".to_string());
+        thread.attributes.insert("thread_type".to_string(), "3".to_string());
+        thread.attributes.insert("weight".to_string(), "30".to_string());
+        let mut data = vec![];
+        write_thread(&mut data, &thread).unwrap();
+        assert_eq!(read_thread(&mut &data[..]).unwrap(), thread);
+
+        // Threads from other formats get the defaults.
+        let thread = Thread::new_str(Color::rgb(1, 2, 3), &"Name", &"");
+        let mut data = vec![];
+        write_thread(&mut data, &thread).unwrap();
+        let read = read_thread(&mut &data[..]).unwrap();
+        assert_eq!(read.manufacturer, None);
+        assert_eq!(read.attributes.get("weight"), Some(&"40".to_string()));
+    }
+}
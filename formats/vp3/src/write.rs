@@ -0,0 +1,196 @@
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{pattern_threads, split_jump, to_tenths};
+
+use crate::block::{write_block, write_string_16, COLOR_BLOCK, DESIGN_BLOCK, FILE_BLOCK, STITCH_BLOCK};
+use crate::header::{DesignHeader, PatternHeader, MAGIC_BYTES};
+use crate::read::{END_LONG_MOVE, ESCAPE, LONG_MOVE, STITCH_PREFIX, TRIM, UM_PER_STITCH_UNIT};
+use crate::thread::write_thread;
+
+const MAX_JUMP: i32 = 127;
+const PRODUCER: &str = "Produced by embroidery-rust";
+
+#[derive(Default)]
+pub struct Vp3PatternWriter {}
+
+impl PatternWriter for Vp3PatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        let (min_x, min_y) = (to_tenths(min_x), to_tenths(min_y));
+        let (max_x, max_y) = (to_tenths(max_x), to_tenths(max_y));
+        let center = ((min_x + max_x) / 2, (min_y + max_y) / 2);
+        let bounds = (
+            min_x * UM_PER_STITCH_UNIT,
+            min_y * UM_PER_STITCH_UNIT,
+            max_x * UM_PER_STITCH_UNIT,
+            max_y * UM_PER_STITCH_UNIT,
+        );
+
+        let mut color_blocks = vec![];
+        let mut number_of_stitches = 0;
+        for (cg, thread) in pattern.color_groups.iter().zip(pattern_threads(pattern)) {
+            let stitches = into_vp3_stitches(cg, &mut number_of_stitches)?;
+            write_color_block(&mut color_blocks, center, &thread, &stitches)?;
+        }
+
+        let mut design = vec![];
+        DesignHeader {
+            center: (center.0 * UM_PER_STITCH_UNIT, center.1 * UM_PER_STITCH_UNIT),
+            bounds,
+            notes: String::new(),
+            number_of_colors: pattern.color_groups.len() as u16,
+        }
+        .write(&mut design)?;
+        design.append(&mut color_blocks);
+
+        let notes = pattern
+            .attributes
+            .iter()
+            .filter_map(|attr| match attr {
+                PatternAttribute::Arbitary(key, value) if key == "notes" => Some(value.clone()),
+                _ => None,
+            })
+            .next()
+            .unwrap_or_default();
+        let mut content = vec![];
+        PatternHeader {
+            notes,
+            bounds,
+            number_of_stitches: number_of_stitches as u32,
+            number_of_colors: pattern.color_groups.len() as u16,
+            number_of_designs: 1,
+        }
+        .write(&mut content)?;
+        write_block(&mut content, DESIGN_BLOCK, &design)?;
+
+        writer.write_all(&MAGIC_BYTES)?;
+        write_string_16(writer, PRODUCER)?;
+        write_block(writer, FILE_BLOCK, &content)?;
+        Ok(())
+    }
+}
+
+/// The stitches of one color block; as the start & end positions in 0.1mm, and the stitch data.
+struct ColorBlockStitches {
+    start: (i32, i32),
+    end: (i32, i32),
+    data: Vec<u8>,
+}
+
+fn into_vp3_stitches(cg: &ColorGroup, idx: &mut usize) -> Result<ColorBlockStitches, WriteError> {
+    let start = match cg.iter_stitches().next() {
+        Some(s) => (to_tenths(s.x), to_tenths(s.y)),
+        None => (0, 0),
+    };
+    let mut data = STITCH_PREFIX.to_vec();
+    let (mut ox, mut oy) = start;
+
+    for sg in &cg.stitch_groups {
+        let mut iter = sg.stitches.iter();
+        if let Some(s) = iter.next() {
+            let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+            let jumps = split_jump(x - ox, y - oy, i32::from(i16::MAX));
+            if !jumps.is_empty() {
+                for (dx, dy) in jumps {
+                    data.extend_from_slice(&[ESCAPE, LONG_MOVE]);
+                    data.extend_from_slice(&(dx as i16).to_be_bytes());
+                    // The y axis points down the page.
+                    data.extend_from_slice(&(-dy as i16).to_be_bytes());
+                    *idx += 1;
+                }
+                data.extend_from_slice(&[ESCAPE, END_LONG_MOVE]);
+            }
+            ox = x;
+            oy = y;
+        }
+        for s in iter {
+            let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+            let (dx, dy) = (x - ox, y - oy);
+            if dx.abs() > MAX_JUMP || dy.abs() > MAX_JUMP {
+                return Err(WriteError::unsupported_stitch_msg(
+                    *s,
+                    Some(*idx),
+                    "Stitch jump is too big for the format",
+                ));
+            }
+            ox = x;
+            oy = y;
+            data.extend_from_slice(&[dx as i8 as u8, -dy as i8 as u8]);
+            *idx += 1;
+        }
+        if sg.cut {
+            data.extend_from_slice(&[ESCAPE, TRIM]);
+            *idx += 1;
+        }
+    }
+    Ok(ColorBlockStitches {
+        start,
+        end: (ox, oy),
+        data,
+    })
+}
+
+fn write_color_block(
+    file: &mut dyn Write,
+    center: (i32, i32),
+    thread: &Thread,
+    stitches: &ColorBlockStitches,
+) -> std::io::Result<()> {
+    let mut content = vec![];
+    content.write_i32::<BigEndian>((stitches.start.0 - center.0) * UM_PER_STITCH_UNIT)?;
+    content.write_i32::<BigEndian>((stitches.start.1 - center.1) * UM_PER_STITCH_UNIT)?;
+    write_thread(&mut content, thread)?;
+    content.write_i32::<BigEndian>((stitches.end.0 - stitches.start.0) * UM_PER_STITCH_UNIT)?;
+    content.write_i32::<BigEndian>((stitches.end.1 - stitches.start.1) * UM_PER_STITCH_UNIT)?;
+    write_block(&mut content, STITCH_BLOCK, &stitches.data)?;
+    write_block(file, COLOR_BLOCK, &content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_into_vp3_stitches() {
+        let cg = ColorGroup {
+            thread: None,
+            stitch_groups: vec![
+                StitchGroup::new(vec![Stitch::new(1.0, 1.0), Stitch::new(2.0, 0.0)]).with_cut(true),
+                StitchGroup::new(vec![Stitch::new(32.0, 0.0), Stitch::new(32.0, 0.5)]),
+            ],
+        };
+        let mut idx = 0;
+        let stitches = into_vp3_stitches(&cg, &mut idx).unwrap();
+        assert_eq!(stitches.start, (10, 10));
+        assert_eq!(stitches.end, (320, 5));
+        assert_eq!(
+            stitches.data,
+            vec![
+                0x0A, 0xF6, 0x00, // Prefix
+                0x0A, 0x0A, // Stitch to (2.0, 0.0)
+                0x80, 0x03, // Trim
+                0x80, 0x01, 0x01, 0x2C, 0x00, 0x00, // Jump to (32.0, 0.0)
+                0x80, 0x02, // End of long moves
+                0x00, 0xFB, // Stitch to (32.0, 0.5)
+            ]
+        );
+        assert_eq!(idx, 4);
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.8, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        assert!(Vp3PatternWriter::default().write_pattern(&pattern, &mut data).is_err());
+    }
+}
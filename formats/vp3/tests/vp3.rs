@@ -0,0 +1,33 @@
+use embroidery_lib::format::{PatternReader, PatternWriter};
+use embroidery_lib::prelude::*;
+
+use embroidery_fmt_vp3::{Vp3PatternReader, Vp3PatternWriter};
+use embroidery_fmt_vp4::Vp4PatternReader;
+
+use std::io::Cursor;
+
+fn load_sample() -> Pattern {
+    let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+    Vp4PatternReader {}.read_pattern(&mut Cursor::new(data)).unwrap()
+}
+
+#[test]
+fn test_hedera_roundtrip() {
+    let pattern = load_sample();
+    let mut data = Vec::new();
+    Vp3PatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+    let loader = Vp3PatternReader::default();
+    assert!(loader.is_loadable(&mut Cursor::new(&data)).unwrap());
+    let read = loader.read_pattern(&mut Cursor::new(&data)).unwrap();
+
+    assert_eq!(read.color_groups.len(), 3);
+    for (read_cg, cg) in read.color_groups.iter().zip(&pattern.color_groups) {
+        assert_eq!(read_cg.stitch_groups, cg.stitch_groups);
+        let (read_thread, thread) = (read_cg.thread.as_ref().unwrap(), cg.thread.as_ref().unwrap());
+        assert_eq!(read_thread.color, thread.color);
+        assert_eq!(read_thread.name, thread.name);
+        assert_eq!(read_thread.code, thread.code);
+        assert_eq!(read_thread.manufacturer, thread.manufacturer);
+        assert_eq!(read_thread.attributes.get("weight"), Some(&"40".to_string()));
+    }
+}
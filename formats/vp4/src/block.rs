@@ -0,0 +1,63 @@
+use byteorder::LittleEndian;
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::{read_exact, read_exact_magic, read_int};
+use std::io::Read;
+
+/// Read a block of the form `[tag; 4] [unknown: u32] [length: u32] [content; length]`, returning
+/// the content.
+pub fn read_block(file: &mut dyn Read, tag: &[u8; 4]) -> ReadResult<Vec<u8>> {
+    read_exact_magic!(file, tag)?;
+    // Always 0 in the files seen so far.
+    let _ = read_int!(file, u32, LittleEndian)?;
+    let length = read_int!(file, u32, LittleEndian)?;
+    read_exact!(file, vec![_; length as usize])
+}
+
+/// Read a string prefixed by its length as a `u16`.
+pub fn read_string(file: &mut dyn Read) -> ReadResult<String> {
+    let length = read_int!(file, u16, LittleEndian)?;
+    let data = read_exact!(file, vec![_; length as usize])?;
+    Ok(String::from_utf8_lossy(&data).into_owned())
+}
+
+/// Read a `nttn` block; which contains a list of named strings such as notes(`ntes`) and
+/// settings(`stgs`).
+pub fn read_named_strings(file: &mut dyn Read) -> ReadResult<Vec<(String, String)>> {
+    let content = read_block(file, b"nttn")?;
+    let content = &mut &content[..];
+    let count = read_int!(content, u16, LittleEndian)?;
+    let mut strings = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let name = read_exact!(content, [_; 4])?;
+        let value = read_string(content)?;
+        strings.push((String::from_utf8_lossy(&name).into_owned(), value));
+    }
+    Ok(strings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_block() {
+        let data = b"hoop\0\0\0\0\x03\0\0\0abcdef";
+        let file = &mut &data[..];
+        assert_eq!(read_block(file, b"hoop").unwrap(), b"abc");
+        assert_eq!(file, b"def");
+
+        assert!(read_block(&mut &data[..], b"thrd").is_err());
+    }
+
+    #[test]
+    fn test_read_named_strings() {
+        let data = b"nttn\0\0\0\0\x10\0\0\0\x02\0ntes\x02\0histgs\0\0";
+        assert_eq!(
+            read_named_strings(&mut &data[..]).unwrap(),
+            vec![
+                ("ntes".to_string(), "hi".to_string()),
+                ("stgs".to_string(), "".to_string())
+            ]
+        );
+    }
+}
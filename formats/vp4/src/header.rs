@@ -1,20 +1,104 @@
-use embroidery_lib::format::PatternReader;
-use embroidery_lib::prelude::*;
+use byteorder::LittleEndian;
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::{read_exact, read_exact_magic, read_int};
 use std::io::Read;
 
-const MAGIC_BYTES: [u8; 6] = [b'%', b'V', b'P', b'4', b'%', 0x01];
+use crate::block::{read_block, read_named_strings, read_string};
 
-#[derive(Default)]
-pub struct Vp4PatternReader {}
+pub const MAGIC_BYTES: [u8; 6] = [b'%', b'V', b'p', b'4', b'%', 0x01];
 
-impl PatternReader for Vp4PatternReader {
-    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
-        let mut buf = [0_u8; 6 /* MAGIC_BYTES.len() */];
-        item.read_exact(&mut buf)?;
-        Ok(buf == MAGIC_BYTES)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hoop {
+    pub name: String,
+    pub kind: String,
+    // In 0.1mm
+    pub width: u16,
+    pub height: u16,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PatternHeader {
+    pub named_strings: Vec<(String, String)>,
+    // Includes the moves to the start of each color and every trim.
+    pub number_of_stitches: u32,
+    pub number_of_colors: u32,
+    // Min x, min y, max x, max y; in 0.1mm.
+    pub bounds: (i16, i16, i16, i16),
+    pub hoop: Hoop,
+}
+
+impl PatternHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        read_exact_magic!(file, MAGIC_BYTES)?;
+        // Unknown; the same in every file seen so far.
+        read_exact!(file, [_; 23])?;
+
+        let info = read_block(file, b"info")?;
+        let info = &mut &info[..];
+        let named_strings = read_named_strings(info)?;
+        let number_of_stitches = read_int!(info, u32, LittleEndian)?;
+        let number_of_colors = read_int!(info, u32, LittleEndian)?;
+        let _ = read_int!(info, u16, LittleEndian)?;
+        let bounds = (
+            read_int!(info, i16, LittleEndian)?,
+            read_int!(info, i16, LittleEndian)?,
+            read_int!(info, i16, LittleEndian)?,
+            read_int!(info, i16, LittleEndian)?,
+        );
+
+        let _ = read_int!(file, u8)?;
+        let hoop = read_block(file, b"hoop")?;
+        let hoop = &mut &hoop[..];
+        let name = read_string(hoop)?;
+        let kind = read_string(hoop)?;
+        let _ = read_int!(hoop, u8)?;
+        let hoop = Hoop {
+            name,
+            kind,
+            width: read_int!(hoop, u16, LittleEndian)?,
+            height: read_int!(hoop, u16, LittleEndian)?,
+        };
+        let _ = read_int!(file, u8)?;
+
+        Ok(Self {
+            named_strings,
+            number_of_stitches,
+            number_of_colors,
+            bounds,
+            hoop,
+        })
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn read_pattern(&self, _item: &mut dyn Read) -> Result<Pattern, ReadError> {
-        Err(ReadError::invalid_format("AAA"))
+    #[test]
+    fn test_header_build() {
+        let data: &[u8] = include_bytes!("../tests/test_data/premier+/hedera1.vp4");
+        let file = &mut &data[..];
+        let header = PatternHeader::build(file).unwrap();
+        assert_eq!(
+            header,
+            PatternHeader {
+                named_strings: vec![
+                    ("ntes".to_string(), "".to_string()),
+                    ("stgs".to_string(), "".to_string())
+                ],
+                number_of_stitches: 1508,
+                number_of_colors: 3,
+                bounds: (-77, -225, 77, 225),
+                hoop: Hoop {
+                    name: "UniLarge3".to_string(),
+                    kind: "Universal".to_string(),
+                    width: 2600,
+                    height: 2000,
+                },
+            }
+        );
+        // The stitch blocks follow directly after the header.
+        assert_eq!(&file[..4], b"sbds");
     }
 }
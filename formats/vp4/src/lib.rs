@@ -1 +1,28 @@
+mod block;
 mod header;
+mod read;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+pub use self::read::Vp4PatternReader;
+
+const NAME: &str = "vp4";
+const EXTENSIONS: [&str; 1] = ["vp4"];
+
+#[derive(Default)]
+pub struct Vp4PatternFormat {}
+
+impl PatternFormat for Vp4PatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(Vp4PatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        None
+    }
+}
@@ -0,0 +1,196 @@
+use byteorder::LittleEndian;
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+use embroidery_lib::{read_exact, read_int};
+use std::io::Read;
+
+use crate::block::{read_block, read_named_strings, read_string};
+use crate::header::{PatternHeader, MAGIC_BYTES};
+
+// Every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm increments; except when the first
+// byte is `ESCAPE`, in which case the second byte is a command.
+const ESCAPE: u8 = 0x80;
+// Followed by `(dx, dy)` as a pair of little endian `i16`s.
+const LONG_MOVE: u8 = 0x01;
+const END_LONG_MOVE: u8 = 0x02;
+const TRIM: u8 = 0x03;
+
+#[derive(Default)]
+pub struct Vp4PatternReader {}
+
+impl PatternReader for Vp4PatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        let mut buf = [0_u8; MAGIC_BYTES.len()];
+        item.read_exact(&mut buf)?;
+        Ok(buf == MAGIC_BYTES)
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let header = PatternHeader::build(item)?;
+
+        let mut attributes = named_strings_to_attributes(header.named_strings);
+        attributes.push(PatternAttribute::Arbitary("hoop".to_string(), header.hoop.name));
+
+        let designs = read_block(item, b"sbds")?;
+        let designs = &mut &designs[..];
+        let design_count = read_int!(designs, u16, LittleEndian)?;
+        let mut color_groups = Vec::with_capacity(header.number_of_colors as usize);
+        for _ in 0..design_count {
+            let design = read_block(designs, b"sbdn")?;
+            color_groups.append(&mut read_design(&mut &design[..], &mut attributes)?);
+        }
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes,
+            color_groups,
+        })
+    }
+}
+
+fn named_strings_to_attributes(named_strings: Vec<(String, String)>) -> Vec<PatternAttribute> {
+    named_strings
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(name, value)| PatternAttribute::Arbitary(name, value))
+        .collect()
+}
+
+fn read_design(item: &mut dyn Read, attributes: &mut Vec<PatternAttribute>) -> ReadResult<Vec<ColorGroup>> {
+    // Unknown; all zeros in the files seen so far.
+    read_exact!(item, [_; 17])?;
+    attributes.append(&mut named_strings_to_attributes(read_named_strings(item)?));
+    let _ = read_int!(item, u8)?;
+
+    let color_count = read_int!(item, u32, LittleEndian)?;
+    let mut grouper = StitchGrouper::new();
+    for _ in 0..color_count {
+        // Each color starts at an absolute position, rather than continuing from the last stitch.
+        let start_x = read_int!(item, i16, LittleEndian)?;
+        let start_y = read_int!(item, i16, LittleEndian)?;
+        let thread = read_thread(&mut &read_block(item, b"thrd")?[..])?;
+        let stitch_len = read_int!(item, u32, LittleEndian)?;
+        let stitch_data = read_exact!(item, vec![_; stitch_len as usize])?;
+        read_stitches(&mut grouper, (i32::from(start_x), i32::from(start_y)), &stitch_data)?;
+        grouper.end_color(Some(thread));
+    }
+    Ok(grouper.finish(None))
+}
+
+fn read_thread(item: &mut dyn Read) -> ReadResult<Thread> {
+    let manufacturer = read_string(item)?;
+    let code = read_string(item)?;
+    let name = read_string(item)?;
+    let _ = read_int!(item, u8)?;
+    let weight = read_int!(item, u8)?;
+    let [red, green, blue] = read_exact!(item, [_; 3])?;
+
+    let mut thread = Thread::new(Color::rgb(red, green, blue), name, code);
+    if !manufacturer.is_empty() {
+        thread.manufacturer = Some(manufacturer);
+    }
+    thread.attributes.insert("weight".to_string(), weight.to_string());
+    Ok(thread)
+}
+
+fn to_stitch(x: i32, y: i32) -> Stitch {
+    // The y axis points down the page.
+    from_tenths(x, -y)
+}
+
+fn read_i16(iter: &mut dyn Iterator<Item = u8>) -> ReadResult<i16> {
+    match (iter.next(), iter.next()) {
+        (Some(low), Some(high)) => Ok(i16::from_le_bytes([low, high])),
+        _ => Err(ReadError::invalid_format(
+            "Stitch data ends part way through a long move",
+        )),
+    }
+}
+
+/// Read the stitches of a color block; starting from `(start_x, start_y)`.
+fn read_stitches(grouper: &mut StitchGrouper, (start_x, start_y): (i32, i32), data: &[u8]) -> ReadResult<()> {
+    let mut iter = data.iter().cloned();
+    let mut cx = start_x;
+    let mut cy = start_y;
+    grouper.jump(to_stitch(cx, cy));
+
+    loop {
+        let (first, second) = match (iter.next(), iter.next()) {
+            (Some(first), Some(second)) => (first, second),
+            (None, _) => break,
+            (Some(_), None) => return Err(ReadError::invalid_format("Stitch data ends part way through a stitch")),
+        };
+        if first != ESCAPE {
+            cx += i32::from(first as i8);
+            cy += i32::from(second as i8);
+            grouper.stitch(to_stitch(cx, cy));
+            continue;
+        }
+        match second {
+            LONG_MOVE => {
+                cx += i32::from(read_i16(&mut iter)?);
+                cy += i32::from(read_i16(&mut iter)?);
+                grouper.jump(to_stitch(cx, cy));
+            },
+            END_LONG_MOVE => {},
+            TRIM => grouper.trim(),
+            cmd => {
+                return Err(ReadError::invalid_format(format!(
+                    "Unknown stitch command 0x{:02X}",
+                    cmd
+                )))
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_stitches() {
+        let data = [
+            0x0A, 0x00, // Stitch to (2.0, -1.0)
+            0x00, 0xF6, // Stitch to (2.0, 0.0)
+            0x80, 0x03, // Trim
+            0x80, 0x01, 0x2C, 0x01, 0x00, 0x00, // Jump to (32.0, 0.0)
+            0x0A, 0x0A, // Stitch to (33.0, -1.0)
+            0x80, 0x01, 0x00, 0x00, 0x0A, 0x00, // Jump to (33.0, -2.0)
+            0x80, 0x02, // End of long moves
+            0xF6, 0x00, // Stitch to (32.0, -2.0)
+        ];
+        let mut grouper = StitchGrouper::new();
+        read_stitches(&mut grouper, (10, 10), &data).unwrap();
+        assert_eq!(
+            grouper.finish(None)[0].stitch_groups,
+            vec![
+                StitchGroup::new(vec![
+                    Stitch::new(1.0, -1.0),
+                    Stitch::new(2.0, -1.0),
+                    Stitch::new(2.0, 0.0)
+                ])
+                .with_trim(true)
+                .with_cut(true),
+                StitchGroup::new(vec![Stitch::new(32.0, 0.0), Stitch::new(33.0, -1.0)]).with_trim(true),
+                StitchGroup::new(vec![Stitch::new(33.0, -2.0), Stitch::new(32.0, -2.0)]).with_trim(true),
+            ]
+        );
+        assert!(read_stitches(&mut StitchGrouper::new(), (0, 0), &[0x80, 0x42]).is_err());
+        assert!(read_stitches(&mut StitchGrouper::new(), (0, 0), &[0x80, 0x01, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_read_thread() {
+        let data = b"\x16\0Robison-Anton Rayon 40\x04\x002527\x0c\0Soldier Blue\x08\x28\x00\x43\xb1\0\0";
+        let thread = read_thread(&mut &data[..]).unwrap();
+        assert_eq!(thread.color, Color::rgb(0x00, 0x43, 0xb1));
+        assert_eq!(thread.name, "Soldier Blue");
+        assert_eq!(thread.code, "2527");
+        assert_eq!(thread.manufacturer, Some("Robison-Anton Rayon 40".to_string()));
+        assert_eq!(thread.attributes.get("weight"), Some(&"40".to_string()));
+    }
+}
@@ -0,0 +1,108 @@
+use byteorder::{LittleEndian, WriteBytesExt};
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+use embroidery_lib::{read_exact, read_int};
+use std::io::{Read, Result, Write};
+
+/// The stitches always start here; the gap after the fields below is unused.
+pub const HEADER_LEN: usize = 0x100;
+const STITCH_COUNT_OFFSET: usize = 0x17;
+const COLOR_COUNT_OFFSET: usize = 0x27;
+const PALETTE_OFFSET_OFFSET: usize = 0xFC;
+// Color count + padding + size + end + origin.
+const FIELDS_LEN: usize = 2 + 2 + 4 + 4 + 4;
+
+// The palette starts with 2 unknown bytes, followed by `0x00, red, green, blue` for each color.
+pub const PALETTE_PADDING: usize = 2;
+pub const PALETTE_ENTRY_LEN: usize = 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternHeader {
+    pub number_of_stitches: u32,
+    pub number_of_colors: u16,
+    /// The width and height of the design.
+    pub size: (u16, u16),
+    /// Where the needle finishes; relative to where it started.
+    pub end: (i16, i16),
+    /// The distance from the left and top edges of the design to where the needle started.
+    pub origin: (i16, i16),
+    /// The absolute offset of the palette, which comes after the stitches.
+    pub palette_offset: u32,
+}
+
+impl PatternHeader {
+    pub fn build(file: &mut dyn Read) -> ReadResult<Self> {
+        read_exact!(file, [_; STITCH_COUNT_OFFSET])?;
+        let number_of_stitches = read_int!(file, u32, LittleEndian)?;
+        read_exact!(file, [_; COLOR_COUNT_OFFSET - STITCH_COUNT_OFFSET - 4])?;
+        let number_of_colors = read_int!(file, u16, LittleEndian)?;
+        let _padding = read_int!(file, u16, LittleEndian)?;
+        let size = (read_int!(file, u16, LittleEndian)?, read_int!(file, u16, LittleEndian)?);
+        let end = (read_int!(file, i16, LittleEndian)?, read_int!(file, i16, LittleEndian)?);
+        let origin = (read_int!(file, i16, LittleEndian)?, read_int!(file, i16, LittleEndian)?);
+        read_exact!(file, vec![_; PALETTE_OFFSET_OFFSET - COLOR_COUNT_OFFSET - FIELDS_LEN])?;
+        let palette_offset = read_int!(file, u32, LittleEndian)?;
+        if (palette_offset as usize) < HEADER_LEN {
+            return Err(ReadError::invalid_format(format!(
+                "Palette offset({}) points inside the header",
+                palette_offset
+            )));
+        }
+        Ok(PatternHeader {
+            number_of_stitches,
+            number_of_colors,
+            size,
+            end,
+            origin,
+            palette_offset,
+        })
+    }
+
+    pub fn write(&self, file: &mut dyn Write) -> Result<()> {
+        file.write_all(&[0; STITCH_COUNT_OFFSET])?;
+        file.write_u32::<LittleEndian>(self.number_of_stitches)?;
+        file.write_all(&[0; COLOR_COUNT_OFFSET - STITCH_COUNT_OFFSET - 4])?;
+        file.write_u16::<LittleEndian>(self.number_of_colors)?;
+        file.write_u16::<LittleEndian>(0)?;
+        file.write_u16::<LittleEndian>(self.size.0)?;
+        file.write_u16::<LittleEndian>(self.size.1)?;
+        file.write_i16::<LittleEndian>(self.end.0)?;
+        file.write_i16::<LittleEndian>(self.end.1)?;
+        file.write_i16::<LittleEndian>(self.origin.0)?;
+        file.write_i16::<LittleEndian>(self.origin.1)?;
+        file.write_all(&[0; PALETTE_OFFSET_OFFSET - COLOR_COUNT_OFFSET - FIELDS_LEN])?;
+        file.write_u32::<LittleEndian>(self.palette_offset)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let header = PatternHeader {
+            number_of_stitches: 1234,
+            number_of_colors: 3,
+            size: (500, 250),
+            end: (-10, 20),
+            origin: (100, 200),
+            palette_offset: 0x1234,
+        };
+        let mut data = vec![];
+        header.write(&mut data).unwrap();
+        assert_eq!(data.len(), HEADER_LEN);
+        assert_eq!(data[0x17..0x1B], [0xD2, 0x04, 0x00, 0x00]);
+        assert_eq!(data[0x27..0x29], [0x03, 0x00]);
+        assert_eq!(data[0xFC..], [0x34, 0x12, 0x00, 0x00]);
+        assert_eq!(PatternHeader::build(&mut &data[..]).unwrap(), header);
+    }
+
+    #[test]
+    fn test_palette_inside_header() {
+        let mut data = vec![0; HEADER_LEN];
+        data[0xFC] = 0x10;
+        assert!(PatternHeader::build(&mut &data[..]).is_err());
+    }
+}
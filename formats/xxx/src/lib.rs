@@ -0,0 +1,30 @@
+mod header;
+mod read;
+mod stitch_info;
+mod write;
+
+pub use read::XxxPatternReader;
+pub use write::XxxPatternWriter;
+
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+
+const NAME: &str = "xxx";
+const EXTENSIONS: [&str; 1] = ["xxx"];
+
+#[derive(Default)]
+pub struct XxxPatternFormat {}
+
+impl PatternFormat for XxxPatternFormat {
+    fn name<'a>(&self) -> &'a str {
+        NAME
+    }
+    fn extensions<'a, 'b>(&self) -> &'a [&'b str] {
+        &EXTENSIONS
+    }
+    fn reader(&self) -> Option<Box<dyn PatternReader>> {
+        Some(Box::from(XxxPatternReader::default()))
+    }
+    fn writer(&self) -> Option<Box<dyn PatternWriter>> {
+        Some(Box::from(XxxPatternWriter::default()))
+    }
+}
@@ -0,0 +1,180 @@
+use std::io::Read;
+
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{from_tenths, StitchGrouper};
+
+use crate::header::{PatternHeader, HEADER_LEN, PALETTE_ENTRY_LEN, PALETTE_PADDING};
+use crate::stitch_info::{read_stitch, StitchInformation, StitchType};
+
+#[derive(Default)]
+pub struct XxxPatternReader {}
+
+impl PatternReader for XxxPatternReader {
+    fn is_loadable(&self, item: &mut dyn Read) -> Result<bool, ReadError> {
+        match PatternHeader::build(item) {
+            Err(ReadError::InvalidFormat(_, _)) => Ok(false),
+            Err(error) => Err(error),
+            Ok(_) => Ok(true),
+        }
+    }
+
+    fn read_pattern(&self, item: &mut dyn Read) -> Result<Pattern, ReadError> {
+        let mut data = Vec::new();
+        item.read_to_end(&mut data)?;
+        if data.len() < HEADER_LEN {
+            return Err(ReadError::invalid_format("The file is too short to have a header"));
+        }
+        let header = PatternHeader::build(&mut &data[..])?;
+
+        let palette_offset = header.palette_offset as usize;
+        let palette_len = PALETTE_PADDING + PALETTE_ENTRY_LEN * usize::from(header.number_of_colors);
+        if palette_offset + palette_len > data.len() {
+            return Err(ReadError::invalid_format(format!(
+                "The palette({} colors at {}) goes past the end of the file",
+                header.number_of_colors, palette_offset
+            )));
+        }
+        let threads: Vec<Thread> = data[palette_offset + PALETTE_PADDING..palette_offset + palette_len]
+            .chunks(PALETTE_ENTRY_LEN)
+            .map(|entry| {
+                let color = Color::rgb(entry[1], entry[2], entry[3]);
+                Thread::new(color, color.to_string(), String::new())
+            })
+            .collect();
+
+        let mut iter = data[HEADER_LEN..palette_offset].iter().cloned();
+        let color_groups = read_stitches(&threads, &mut iter)?;
+
+        Ok(Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups,
+        })
+    }
+}
+
+fn read_stitches(threads: &[Thread], item: &mut dyn Iterator<Item = u8>) -> ReadResult<Vec<ColorGroup>> {
+    let mut thread_iter = threads.iter();
+    let mut grouper = StitchGrouper::new();
+    let mut cx: i32 = 0;
+    let mut cy: i32 = 0;
+
+    while let Some(stitch) = read_stitch(item)? {
+        let (dx, dy, stitch_type) = match stitch {
+            StitchInformation::Move(dx, dy, stitch_type) => (i32::from(dx), i32::from(dy), stitch_type),
+            StitchInformation::End => break,
+        };
+        cx += dx;
+        cy += dy;
+        let to = from_tenths(cx, cy);
+        match stitch_type {
+            StitchType::Regular => grouper.stitch(to),
+            StitchType::Jump => grouper.jump(to),
+            StitchType::Trim => {
+                grouper.trim();
+                grouper.jump(to);
+            },
+            StitchType::ColorChange => {
+                // Each color change moves onto the next thread, even if nothing was sewn with the
+                // current one.
+                grouper.end_color(thread_iter.next().cloned());
+                grouper.jump(to);
+            },
+        }
+    }
+    Ok(grouper.finish(thread_iter.next().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! to_u8_iter {
+        ($t:expr) => {
+            &mut $t.iter().map(|&x| x)
+        };
+    }
+
+    #[test]
+    fn test_read_stitches() {
+        let red = Thread::new_str(Color::rgb(255, 0, 0), &"#FF0000", &"");
+        let blue = Thread::new_str(Color::rgb(0, 0, 240), &"#0000F0", &"");
+        let data = [
+            0x0A, 0x00, // Stitch to (1.0, 0.0)
+            0x00, 0x0A, // Stitch to (1.0, 1.0)
+            0x7F, 0x03, 0x00, 0x00, // Trim
+            0x7F, 0x01, 0x14, 0x00, // Jump to (3.0, 1.0)
+            0x00, 0xF6, // Stitch to (3.0, 0.0)
+            0x7F, 0x08, 0xF6, 0x00, // Color change, moving to (2.0, 0.0)
+            0x0A, 0x0A, // Stitch to (3.0, 1.0)
+            0x7F, 0x7F, 0x02, 0x14, // End
+            0x0A, 0x0A, // Ignored
+        ];
+        let color_groups = read_stitches(&[red.clone(), blue.clone()], to_u8_iter!(data)).unwrap();
+        assert_eq!(
+            color_groups,
+            vec![
+                ColorGroup {
+                    thread: Some(red),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![
+                            Stitch::new(0.0, 0.0),
+                            Stitch::new(1.0, 0.0),
+                            Stitch::new(1.0, 1.0)
+                        ])
+                        .with_trim(true)
+                        .with_cut(true),
+                        StitchGroup::new(vec![Stitch::new(3.0, 1.0), Stitch::new(3.0, 0.0)]).with_trim(true),
+                    ],
+                },
+                ColorGroup {
+                    thread: Some(blue),
+                    stitch_groups: vec![
+                        StitchGroup::new(vec![Stitch::new(2.0, 0.0), Stitch::new(3.0, 1.0)]).with_trim(true)
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_pattern() {
+        let mut data = vec![0; HEADER_LEN];
+        data[0x27] = 1;
+        data[0xFC] = 0x06;
+        data[0xFD] = 0x01;
+        data.extend_from_slice(&[0x0A, 0x00, 0x7F, 0x7F, 0x02, 0x14]);
+        data.extend_from_slice(&[0x00, 0x00, 0x00, 0x12, 0x34, 0x56]);
+
+        let pattern = XxxPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(pattern.color_groups.len(), 1);
+        let thread = pattern.color_groups[0].thread.as_ref().unwrap();
+        assert_eq!(thread.color, Color::rgb(0x12, 0x34, 0x56));
+        assert_eq!(thread.name, "#123456");
+        assert_eq!(
+            pattern.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_read_invalid() {
+        let reader = XxxPatternReader::default();
+        let mut data = vec![0; HEADER_LEN];
+        assert!(!reader.is_loadable(&mut &data[..]).unwrap());
+
+        // The palette offset is past the end of the file.
+        data[0x27] = 1;
+        data[0xFD] = 0x02;
+        match reader.read_pattern(&mut &data[..]) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            result => panic!("Expected an invalid format error; got {:?}", result),
+        }
+        match reader.read_pattern(&mut &b"LA:crown FS 40     \r"[..]) {
+            Err(ReadError::InvalidFormat(_, _)) => {},
+            result => panic!("Expected an invalid format error; got {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use embroidery_lib::errors::ReadResult;
+use embroidery_lib::prelude::*;
+
+// Every stitch is a pair of signed bytes `(dx, dy)` in 0.1mm increments; except when the first
+// byte is `ESCAPE`, in which case the second byte is a command followed by a `(dx, dy)` pair; or
+// when it's `LONG_JUMP`, in which case it's followed by a pair of 16 bit little endian values.
+pub const ESCAPE: u8 = 0x7F;
+pub const LONG_JUMP: u8 = 0x7D;
+// Some software writes this instead of `LONG_JUMP`.
+const LONG_JUMP_ALT: u8 = 0x7E;
+pub const JUMP: u8 = 0x01;
+pub const TRIM: u8 = 0x03;
+// Anything from here on is a color change; some software counts up from this for each color.
+pub const COLOR_CHANGE: u8 = 0x08;
+pub const END: [u8; 4] = [ESCAPE, ESCAPE, 0x02, 0x14];
+
+// The first byte of a regular stitch can't be `LONG_JUMP`, `LONG_JUMP_ALT` or `ESCAPE`.
+pub const MAX_SHORT: i16 = 124;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchType {
+    Regular,
+    Jump,
+    Trim,
+    ColorChange,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum StitchInformation {
+    Move(i16, i16, StitchType),
+    End,
+}
+
+fn is_short(value: i16) -> bool {
+    (-MAX_SHORT..=MAX_SHORT).contains(&value)
+}
+
+impl StitchInformation {
+    pub fn to_bytes(self) -> Option<Vec<u8>> {
+        match self {
+            StitchInformation::Move(dx, dy, StitchType::Jump) if !is_short(dx) || !is_short(dy) => {
+                let mut bytes = vec![LONG_JUMP];
+                bytes.extend_from_slice(&dx.to_le_bytes());
+                bytes.extend_from_slice(&dy.to_le_bytes());
+                Some(bytes)
+            },
+            StitchInformation::Move(dx, dy, _) if !is_short(dx) || !is_short(dy) => None,
+            StitchInformation::Move(dx, dy, StitchType::Regular) => Some(vec![dx as u8, dy as u8]),
+            StitchInformation::Move(dx, dy, stitch_type) => {
+                let command = match stitch_type {
+                    StitchType::Jump => JUMP,
+                    StitchType::Trim => TRIM,
+                    _ => COLOR_CHANGE,
+                };
+                Some(vec![ESCAPE, command, dx as u8, dy as u8])
+            },
+            StitchInformation::End => Some(END.to_vec()),
+        }
+    }
+}
+
+fn next_byte(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<u8> {
+    in_bytes
+        .next()
+        .ok_or_else(|| ReadError::invalid_format("Stitch data ends part way through a stitch"))
+}
+
+pub fn read_stitch(in_bytes: &mut dyn Iterator<Item = u8>) -> ReadResult<Option<StitchInformation>> {
+    let first = match in_bytes.next() {
+        Some(first) => first,
+        None => return Ok(None),
+    };
+    let second = next_byte(in_bytes)?;
+    match first {
+        LONG_JUMP | LONG_JUMP_ALT => {
+            let dx = i16::from_le_bytes([second, next_byte(in_bytes)?]);
+            let dy = i16::from_le_bytes([next_byte(in_bytes)?, next_byte(in_bytes)?]);
+            Ok(Some(StitchInformation::Move(dx, dy, StitchType::Jump)))
+        },
+        ESCAPE => {
+            let (dx, dy) = (next_byte(in_bytes)? as i8, next_byte(in_bytes)? as i8);
+            let stitch_type = match second {
+                ESCAPE => return Ok(Some(StitchInformation::End)),
+                JUMP => StitchType::Jump,
+                TRIM => StitchType::Trim,
+                cmd if cmd >= COLOR_CHANGE => StitchType::ColorChange,
+                cmd => {
+                    return Err(ReadError::invalid_format(format!(
+                        "Unknown stitch command 0x{:02X}",
+                        cmd
+                    )))
+                },
+            };
+            Ok(Some(StitchInformation::Move(i16::from(dx), i16::from(dy), stitch_type)))
+        },
+        _ => Ok(Some(StitchInformation::Move(
+            i16::from(first as i8),
+            i16::from(second as i8),
+            StitchType::Regular,
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(data: &[u8]) -> ReadResult<Vec<StitchInformation>> {
+        let mut iter = data.iter().cloned();
+        let mut stitches = Vec::new();
+        while let Some(stitch) = read_stitch(&mut iter)? {
+            stitches.push(stitch);
+        }
+        Ok(stitches)
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let to_bytes = |dx, dy, st| StitchInformation::Move(dx, dy, st).to_bytes();
+        assert_eq!(to_bytes(1, -1, StitchType::Regular), Some(vec![0x01, 0xFF]));
+        assert_eq!(to_bytes(125, 0, StitchType::Regular), None);
+        assert_eq!(
+            to_bytes(-124, 124, StitchType::Jump),
+            Some(vec![0x7F, 0x01, 0x84, 0x7C])
+        );
+        assert_eq!(
+            to_bytes(300, -2, StitchType::Jump),
+            Some(vec![0x7D, 0x2C, 0x01, 0xFE, 0xFF])
+        );
+        assert_eq!(to_bytes(0, 0, StitchType::Trim), Some(vec![0x7F, 0x03, 0x00, 0x00]));
+        assert_eq!(
+            to_bytes(0, 0, StitchType::ColorChange),
+            Some(vec![0x7F, 0x08, 0x00, 0x00])
+        );
+        assert_eq!(StitchInformation::End.to_bytes(), Some(vec![0x7F, 0x7F, 0x02, 0x14]));
+    }
+
+    #[test]
+    fn test_read_stitch() {
+        assert_eq!(
+            read_all(&[
+                0x01, 0xFF, // Stitch (1, -1)
+                0x7E, 0x2C, 0x01, 0xFE, 0xFF, // Jump (300, -2)
+                0x7F, 0x01, 0x0A, 0x00, // Jump (10, 0)
+                0x7F, 0x03, 0x00, 0x00, // Trim
+                0x7F, 0x09, 0x00, 0x00, // Color change
+                0x7F, 0x7F, 0x02, 0x14, // End
+            ])
+            .unwrap(),
+            vec![
+                StitchInformation::Move(1, -1, StitchType::Regular),
+                StitchInformation::Move(300, -2, StitchType::Jump),
+                StitchInformation::Move(10, 0, StitchType::Jump),
+                StitchInformation::Move(0, 0, StitchType::Trim),
+                StitchInformation::Move(0, 0, StitchType::ColorChange),
+                StitchInformation::End,
+            ]
+        );
+        assert!(read_all(&[0x01]).is_err());
+        assert!(read_all(&[0x7D, 0x01, 0x00]).is_err());
+        assert!(read_all(&[0x7F, 0x02, 0x00, 0x00]).is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let stitches = [
+            StitchInformation::Move(0, 0, StitchType::Regular),
+            StitchInformation::Move(-124, 124, StitchType::Regular),
+            StitchInformation::Move(-3000, 0, StitchType::Jump),
+            StitchInformation::Move(0, 0, StitchType::Trim),
+            StitchInformation::Move(5, 5, StitchType::ColorChange),
+            StitchInformation::End,
+        ];
+        let data: Vec<u8> = stitches.iter().flat_map(|s| s.to_bytes().unwrap()).collect();
+        assert_eq!(read_all(&data).unwrap(), stitches.to_vec());
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use embroidery_lib::format::PatternWriter;
+use embroidery_lib::prelude::*;
+use embroidery_lib::utils::{split_jump, thread_colors, to_tenths};
+
+use crate::header::{PatternHeader, HEADER_LEN, PALETTE_PADDING};
+use crate::stitch_info::{StitchInformation, StitchType, MAX_SHORT};
+
+#[derive(Default)]
+pub struct XxxPatternWriter {}
+
+impl PatternWriter for XxxPatternWriter {
+    fn write_pattern(&self, pattern: &Pattern, writer: &mut dyn Write) -> Result<(), WriteError> {
+        let stitches = into_xxx_stitches(pattern)?;
+        let stitch_data: Vec<u8> = stitches
+            .iter()
+            // Use unwrap because any stitch that's invalid here is definitely a program error.
+            .flat_map(|st| st.to_bytes().unwrap())
+            .collect();
+        let colors = thread_colors(pattern);
+
+        let (min_x, min_y, max_x, max_y) = pattern.get_bounds();
+        let end = pattern.iter_stitches().last().cloned().unwrap_or_else(Stitch::zero);
+        let header = PatternHeader {
+            number_of_stitches: stitches.len() as u32,
+            number_of_colors: colors.len() as u16,
+            size: (to_tenths(max_x - min_x) as u16, to_tenths(max_y - min_y) as u16),
+            end: (to_tenths(end.x) as i16, to_tenths(end.y) as i16),
+            origin: (to_tenths(-min_x) as i16, to_tenths(max_y) as i16),
+            palette_offset: (HEADER_LEN + stitch_data.len()) as u32,
+        };
+        header.write(writer)?;
+        writer.write_all(&stitch_data)?;
+
+        writer.write_all(&[0; PALETTE_PADDING])?;
+        for color in colors {
+            writer.write_all(&[0x00, color.red, color.green, color.blue])?;
+        }
+        Ok(())
+    }
+}
+
+fn into_xxx_stitches(pattern: &Pattern) -> Result<Vec<StitchInformation>, WriteError> {
+    let mut re = vec![];
+    let mut ox: i32 = 0;
+    let mut oy: i32 = 0;
+
+    for (i, cg) in pattern.color_groups.iter().enumerate() {
+        if i != 0 {
+            re.push(StitchInformation::Move(0, 0, StitchType::ColorChange));
+        }
+        for sg in &cg.stitch_groups {
+            let mut iter = sg.stitches.iter();
+            if let Some(s) = iter.next() {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                re.append(&mut safe_jump_to(x - ox, y - oy));
+                ox = x;
+                oy = y;
+            }
+            for s in iter {
+                let (x, y) = (to_tenths(s.x), to_tenths(s.y));
+                let (dx, dy) = (x - ox, y - oy);
+                ox = x;
+                oy = y;
+                if dx.abs() > i32::from(MAX_SHORT) || dy.abs() > i32::from(MAX_SHORT) {
+                    // Only jumps can be long; so a long stitch is sewn as several in a straight line.
+                    for (dx, dy) in split_jump(dx, dy, i32::from(MAX_SHORT)) {
+                        re.push(StitchInformation::Move(dx as i16, dy as i16, StitchType::Regular));
+                    }
+                } else {
+                    re.push(StitchInformation::Move(dx as i16, dy as i16, StitchType::Regular));
+                }
+            }
+            if sg.cut {
+                re.push(StitchInformation::Move(0, 0, StitchType::Trim));
+            }
+        }
+    }
+    re.push(StitchInformation::End);
+    Ok(re)
+}
+
+/// Split a move into as few jumps as possible; none at all if it doesn't go anywhere. Long jumps
+/// can go most of the way across any hoop in one go.
+fn safe_jump_to(delta_x: i32, delta_y: i32) -> Vec<StitchInformation> {
+    split_jump(delta_x, delta_y, i32::from(i16::MAX))
+        .into_iter()
+        .map(|(dx, dy)| StitchInformation::Move(dx as i16, dy as i16, StitchType::Jump))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read::XxxPatternReader;
+    use embroidery_fmt_vp4::Vp4PatternReader;
+    use embroidery_lib::format::PatternReader;
+
+    #[test]
+    fn test_header() {
+        let data: &[u8] = include_bytes!("../../vp4/tests/test_data/premier+/hedera1.vp4");
+        let pattern = Vp4PatternReader {}.read_pattern(&mut &data[..]).unwrap();
+        let mut data = vec![];
+        XxxPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+
+        let header = PatternHeader::build(&mut &data[..]).unwrap();
+        assert_eq!(header.number_of_colors, 3);
+        assert_eq!(header.size, (154, 450));
+        assert_eq!(header.end, (5, 186));
+        assert_eq!(header.origin, (77, 225));
+    }
+
+    #[test]
+    fn test_stitch_too_long() {
+        let pattern = Pattern {
+            name: "Untitled".to_string(),
+            attributes: vec![],
+            color_groups: vec![ColorGroup {
+                thread: None,
+                stitch_groups: vec![StitchGroup::new(vec![Stitch::new(0.0, 0.0), Stitch::new(12.5, 0.0)])],
+            }],
+        };
+        let mut data = vec![];
+        XxxPatternWriter::default().write_pattern(&pattern, &mut data).unwrap();
+        let read = XxxPatternReader::default().read_pattern(&mut &data[..]).unwrap();
+        assert_eq!(
+            read.color_groups[0].stitch_groups[0].stitches,
+            vec![Stitch::new(0.0, 0.0), Stitch::new(6.2, 0.0), Stitch::new(12.5, 0.0)]
+        );
+    }
+}
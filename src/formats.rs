@@ -1,17 +1,52 @@
-use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter};
+use embroidery_lib::format::{PatternFormat, PatternReader, PatternWriter, SidecarFormat};
+use embroidery_lib::sidecar::{EdrSidecarFormat, InfSidecarFormat};
 
+use embroidery_fmt_barudan::{DsbPatternFormat, U01PatternFormat};
 use embroidery_fmt_csv::CsvPatternFormat;
 use embroidery_fmt_dst::DstPatternFormat;
+use embroidery_fmt_embr::EmbrPatternFormat;
+use embroidery_fmt_exp::ExpPatternFormat;
+use embroidery_fmt_gcode::GcodePatternFormat;
+use embroidery_fmt_gif::GifPatternFormat;
 use embroidery_fmt_hus::{HusPatternFormat, VipPatternFormat};
+use embroidery_fmt_jef::JefPatternFormat;
+use embroidery_fmt_json::JsonPatternFormat;
+use embroidery_fmt_pcs::PcsPatternFormat;
+use embroidery_fmt_pdf::PdfPatternFormat;
+use embroidery_fmt_pes::PesPatternFormat;
+use embroidery_fmt_png::PngPatternFormat;
+use embroidery_fmt_sew::SewPatternFormat;
 use embroidery_fmt_svg::SvgPatternFormat;
+use embroidery_fmt_tbf::TbfPatternFormat;
+use embroidery_fmt_vp3::Vp3PatternFormat;
+use embroidery_fmt_vp4::Vp4PatternFormat;
+use embroidery_fmt_xxx::XxxPatternFormat;
 
 pub fn get_all() -> Vec<Box<dyn PatternFormat>> {
     vec![
         Box::new(CsvPatternFormat::default()),
+        Box::new(DsbPatternFormat::default()),
         Box::new(DstPatternFormat::default()),
+        Box::new(EmbrPatternFormat::default()),
+        Box::new(GcodePatternFormat::default()),
+        Box::new(GifPatternFormat::default()),
         Box::new(HusPatternFormat::default()),
+        Box::new(JefPatternFormat::default()),
+        Box::new(JsonPatternFormat::default()),
+        Box::new(PcsPatternFormat::default()),
+        Box::new(PdfPatternFormat::default()),
+        Box::new(PesPatternFormat::default()),
+        Box::new(PngPatternFormat::default()),
+        Box::new(SewPatternFormat::default()),
         Box::new(SvgPatternFormat::default()),
+        Box::new(TbfPatternFormat::default()),
+        Box::new(U01PatternFormat::default()),
         Box::new(VipPatternFormat::default()),
+        Box::new(Vp3PatternFormat::default()),
+        Box::new(Vp4PatternFormat::default()),
+        Box::new(XxxPatternFormat::default()),
+        // EXP has no header; so it's only tried once every other format has turned the file down.
+        Box::new(ExpPatternFormat::default()),
     ]
 }
 pub fn get_readers() -> Vec<Box<dyn PatternReader>> {
@@ -21,3 +56,12 @@ pub fn get_readers() -> Vec<Box<dyn PatternReader>> {
 pub fn get_writers() -> Vec<Box<dyn PatternWriter>> {
     get_all().into_iter().filter_map(|format| format.writer()).collect()
 }
+
+/// The sidecar to write next to a format that doesn't store thread colors.
+pub fn get_sidecar_for(format: &dyn PatternFormat) -> Option<Box<dyn SidecarFormat>> {
+    match format.name() {
+        "exp" => Some(Box::new(InfSidecarFormat::default())),
+        "dsb" | "dst" | "u01" => Some(Box::new(EdrSidecarFormat::default())),
+        _ => None,
+    }
+}
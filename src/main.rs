@@ -14,12 +14,36 @@ use std::path::Path;
 
 use simplelog::*;
 
-use embroidery_lib::prelude::{ReadError, WriteError};
+use embroidery_lib::format::SidecarFormat;
+use embroidery_lib::prelude::{Pattern, ReadError, WriteError};
+use embroidery_lib::sidecar::{self, apply_threads};
+use embroidery_lib::utils::pattern_threads;
 
 use crate::error::Error;
-use crate::formats::get_all;
+use crate::formats::{get_all, get_sidecar_for};
 use std::env;
 
+/// Fill in the pattern's threads from a sidecar file with the same base name; if there is one.
+fn load_sidecar(path: &Path, pattern: &mut Pattern) -> Result<(), Error> {
+    for format in sidecar::get_all() {
+        for ext in format.extensions() {
+            for candidate in &[path.with_extension(ext), path.with_extension(ext.to_uppercase())] {
+                if !candidate.is_file() {
+                    continue;
+                }
+                if let Some(reader) = format.reader() {
+                    let mut file = BufReader::new(File::open(candidate)?);
+                    let threads = reader.read_threads(&mut file)?;
+                    info!("Using {} threads from {}", threads.len(), candidate.display());
+                    apply_threads(pattern, threads);
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
 fn main() -> Result<(), Error> {
     TermLogger::init(
         LevelFilter::Debug,
@@ -40,7 +64,14 @@ fn main() -> Result<(), Error> {
         let mut loader_result = None;
         {
             let mut reader = BufReader::new(File::open(file.clone())?);
-            for (i, format) in loader_unloaders.iter().enumerate() {
+            // Several formats have little or no header to check; so trust the extension first.
+            let ext = path.extension().map(|ext| ext.to_string_lossy().to_lowercase());
+            let (mut by_ext, rest): (Vec<_>, Vec<_>) = loader_unloaders
+                .iter()
+                .enumerate()
+                .partition(|(_, format)| ext.as_ref().is_some_and(|ext| format.extensions().contains(&&ext[..])));
+            by_ext.extend(rest);
+            for (i, format) in by_ext {
                 reader.seek(std::io::SeekFrom::Start(0))?;
                 if let Some(loader) = format.reader() {
                     match loader.read_pattern(&mut reader) {
@@ -60,9 +91,17 @@ fn main() -> Result<(), Error> {
                 }
             }
         }
-        let (loader_idx, pattern) =
+        let (loader_idx, mut pattern) =
             loader_result.ok_or_else(|| format!("The path cannot be read by any of the loaders: {}", file_name))?;
+        // Only let a sidecar override the colors of formats that can't store their own.
+        if get_sidecar_for(loader_unloaders[loader_idx].as_ref()).is_some()
+            || pattern.color_groups.iter().all(|cg| cg.thread.is_none())
+        {
+            load_sidecar(path, &mut pattern)?;
+        }
 
+        // The formats that lose the colors share a sidecar file; so each one is only written once.
+        let mut sidecars: Vec<Box<dyn SidecarFormat>> = Vec::new();
         for (i, format) in loader_unloaders.iter().enumerate() {
             if i == loader_idx {
                 continue;
@@ -90,8 +129,28 @@ fn main() -> Result<(), Error> {
                         )
                         .into());
                     },
+                    Err(WriteError::TooManyColors { count, max, ctx: _ }) => {
+                        return Err(format!(
+                            "Writer {} only supports {} colors; but {} has {}",
+                            ext, max, file_name, count,
+                        )
+                        .into());
+                    },
                     Err(WriteError::Std(err, _)) => return Err(err.into()),
                 }
+                if let Some(sidecar) = get_sidecar_for(format.as_ref()) {
+                    if sidecars.iter().all(|s| s.name() != sidecar.name()) {
+                        sidecars.push(sidecar);
+                    }
+                }
+            }
+        }
+        // Keep the colors in a file alongside the outputs; with the same base name as them.
+        for sidecar in sidecars {
+            if let Some(sidecar_writer) = sidecar.writer() {
+                let output = path.with_file_name(format!("{}.{}", file_name, sidecar.extensions()[0]));
+                let mut out = BufWriter::new(File::create(output)?);
+                sidecar_writer.write_threads(&pattern_threads(&pattern), &mut out)?;
             }
         }
     }
@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use embroidery_fmt_xxx::XxxPatternReader;
+use embroidery_lib::format::PatternReader;
+use embroidery_lib::prelude::*;
+
+/// An empty directory for a test to convert files in; the CLI writes its output next to the input.
+fn work_dir(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn copy_fixture(fixture: &str, dir: &Path) -> PathBuf {
+    let fixture = Path::new(env!("CARGO_MANIFEST_DIR")).join(fixture);
+    let input = dir.join(fixture.file_name().unwrap());
+    fs::copy(&fixture, &input).unwrap();
+    input
+}
+
+fn convert(input: &Path) {
+    let output = Command::new(env!("CARGO_BIN_EXE_embroidery-rust"))
+        .arg(input)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "Converting {} failed:\n{}",
+        input.display(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_convert_vp4() {
+    let dir = work_dir("convert_vp4");
+    let input = copy_fixture("formats/vp4/tests/test_data/premier+/hedera1.vp4", &dir);
+    convert(&input);
+    // The EXP file is only written when some other reader loaded the input.
+    for ext in &["dst", "dsb", "exp", "xxx"] {
+        assert!(dir.join(format!("hedera1.vp4.{}", ext)).is_file(), "No {} output", ext);
+    }
+    // The formats that lose the colors share one sidecar for each sidecar format.
+    let mut sidecars: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".edr") || name.ends_with(".inf"))
+        .collect();
+    sidecars.sort();
+    assert_eq!(sidecars, vec!["hedera1.vp4.edr", "hedera1.vp4.inf"]);
+
+    // Which gives the colors back to the EXP file.
+    convert(&dir.join("hedera1.vp4.exp"));
+    let mut data: &[u8] = &fs::read(dir.join("hedera1.vp4.exp.xxx")).unwrap();
+    let pattern = XxxPatternReader::default().read_pattern(&mut data).unwrap();
+    let colors: Vec<_> = pattern
+        .color_groups
+        .iter()
+        .map(|cg| cg.thread.as_ref().unwrap().color)
+        .collect();
+    assert_eq!(
+        colors,
+        vec![Color::rgb(0, 67, 177), Color::rgb(38, 169, 19), Color::rgb(225, 0, 0)]
+    );
+}
+
+#[test]
+fn test_convert_xxx() {
+    let dir = work_dir("convert_xxx");
+    let input = copy_fixture("tests/dst/test_data/OSHLogo.dst", &dir);
+    convert(&input);
+    convert(&dir.join("OSHLogo.dst.xxx"));
+    // Every format but the one that read the input is written.
+    assert!(!dir.join("OSHLogo.dst.xxx.xxx").exists());
+    for ext in &["dst", "dsb", "exp", "sew"] {
+        assert!(
+            dir.join(format!("OSHLogo.dst.xxx.{}", ext)).is_file(),
+            "No {} output",
+            ext
+        );
+    }
+}
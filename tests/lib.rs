@@ -1,2 +1,3 @@
+pub mod cli;
 pub mod dst;
 extern crate embroidery_lib;